@@ -1,5 +1,5 @@
-use crate::cron::CronTable;
-use crate::ftp::FtpTable;
+use crate::cron::{AnacronTable, AtQueue, CronAccess, CronQuery, CronRaw, CronTable};
+use crate::ftp::{FtpStats, FtpTable, VsftpdSettings};
 use crate::menu::MainMenu;
 use crate::mysql::Mysql;
 use ratatui::style::{self, Style};
@@ -30,7 +30,14 @@ impl Default for App {
 pub enum Screen {
     MainMenu(MainMenu),
     CronTable(CronTable),
+    AnacronTable(AnacronTable),
+    AtQueue(AtQueue),
+    CronQuery(CronQuery),
+    CronRaw(CronRaw),
+    CronAccess(CronAccess),
     FtpTable(FtpTable),
+    FtpStats(FtpStats),
+    VsftpdSettings(VsftpdSettings),
     Mysql(Mysql),
     Quit,
 }
@@ -90,7 +97,14 @@ impl Screen {
         match self {
             Screen::MainMenu(menu) => Some(menu),
             Screen::CronTable(cron) => Some(cron),
+            Screen::AnacronTable(anacron) => Some(anacron),
+            Screen::AtQueue(at) => Some(at),
+            Screen::CronQuery(query) => Some(query),
+            Screen::CronRaw(raw) => Some(raw),
+            Screen::CronAccess(access) => Some(access),
             Screen::FtpTable(ftp) => Some(ftp),
+            Screen::FtpStats(stats) => Some(stats),
+            Screen::VsftpdSettings(settings) => Some(settings),
             Screen::Mysql(mysql) => Some(mysql),
             Screen::Quit => None,
         }
@@ -102,7 +116,14 @@ impl Widget for &mut Screen {
         match self {
             Screen::MainMenu(menu) => menu.render(area, buf),
             Screen::CronTable(cron) => cron.render(area, buf),
+            Screen::AnacronTable(anacron) => anacron.render(area, buf),
+            Screen::AtQueue(at) => at.render(area, buf),
+            Screen::CronQuery(query) => query.render(area, buf),
+            Screen::CronRaw(raw) => raw.render(area, buf),
+            Screen::CronAccess(access) => access.render(area, buf),
             Screen::FtpTable(ftp) => ftp.render(area, buf),
+            Screen::FtpStats(stats) => stats.render(area, buf),
+            Screen::VsftpdSettings(settings) => settings.render(area, buf),
             Screen::Mysql(mysql) => mysql.render(area, buf),
             Screen::Quit => (),
         }
@@ -0,0 +1,369 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::cron::backend::cron_user;
+use crate::cron::diagnostics::{check_permission, CRON_ALLOW, CRON_DENY};
+use crate::cron::{CronTable, EditWindowStyles, TableStyles};
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyModifiers, MouseEvent},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    style::Style,
+    text::{Line, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tui_textarea::TextArea;
+
+const CONFIRM_INFO_TEXT: &str = " (Esc) Cancel | (y) Confirm ";
+
+/// One of the two access control files, edited as one user name per line.
+struct AccessFile {
+    path: PathBuf,
+    editor: TextArea<'static>,
+    exists: bool,
+    modified: bool,
+}
+
+impl AccessFile {
+    fn load(path: &Path) -> io::Result<Self> {
+        let (content, exists) = match fs::read_to_string(path) {
+            Ok(content) => (content, true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (String::new(), false),
+            Err(err) => return Err(err),
+        };
+        let mut editor = TextArea::from(content.lines());
+        editor.set_cursor_line_style(Style::default());
+        Ok(Self {
+            path: path.to_path_buf(),
+            editor,
+            exists,
+            modified: false,
+        })
+    }
+
+    fn content(&self) -> String {
+        let mut content = self
+            .editor
+            .lines()
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        content.push('\n');
+        content
+    }
+
+    /// Lines that cannot be user names.
+    fn invalid_lines(&self) -> Vec<usize> {
+        self.editor
+            .lines()
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.trim().contains(char::is_whitespace))
+            .map(|(index, _)| index + 1)
+            .collect()
+    }
+
+    fn title(&self) -> String {
+        let state = match (self.exists, self.modified) {
+            (false, _) => " (missing)",
+            (true, true) => " (modified)",
+            (true, false) => "",
+        };
+        format!("{}{}", self.path.display(), state)
+    }
+
+    fn is_allow(&self) -> bool {
+        self.path == Path::new(CRON_ALLOW)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.editor
+            .lines()
+            .iter()
+            .all(|line| line.trim().is_empty())
+    }
+}
+
+/// Changes that alter who may use cron and wait for a `y` first.
+#[derive(Clone, Copy)]
+enum Confirm {
+    SaveEmptyAllow,
+    Delete,
+}
+
+/// Edits `/etc/cron.allow` and `/etc/cron.deny`, showing whether the managed
+/// user may use cron under the saved rules.
+pub struct CronAccess {
+    files: Vec<AccessFile>,
+    current: usize,
+    styles: TableStyles,
+    input_styles: EditWindowStyles,
+    message: Option<String>,
+    confirm: Option<Confirm>,
+}
+
+impl ScreenTrait for CronAccess {
+    fn new() -> Self {
+        let mut message = None;
+        let files = [CRON_ALLOW, CRON_DENY]
+            .into_iter()
+            .map(|path| {
+                AccessFile::load(Path::new(path)).unwrap_or_else(|err| {
+                    tracing::error!("Error reading {}: {}", path, err);
+                    message = Some(format!("Error reading {}: {}", path, err));
+                    AccessFile {
+                        path: PathBuf::from(path),
+                        editor: TextArea::default(),
+                        exists: false,
+                        modified: false,
+                    }
+                })
+            })
+            .collect();
+
+        let mut access = Self {
+            files,
+            current: 0,
+            styles: TableStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            message,
+            confirm: None,
+        };
+        if access.message.is_none() {
+            access.message = Some(permission_summary());
+        }
+        access
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+        let editor_areas =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(rects[0]);
+
+        for (index, file) in self.files.iter_mut().enumerate() {
+            let (border_style, cursor_style) = if index == self.current {
+                (
+                    self.input_styles.selected_input_border_style,
+                    self.input_styles.cursor_style,
+                )
+            } else {
+                (
+                    self.input_styles.unselected_input_border_style,
+                    Style::default(),
+                )
+            };
+            let border_style = if file.invalid_lines().is_empty() {
+                border_style
+            } else {
+                self.input_styles.invalid_input_style
+            };
+            file.editor.set_cursor_style(cursor_style);
+            file.editor.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(file.title()),
+            );
+            file.editor.render(editor_areas[index], buf);
+        }
+
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, rects[1], buf);
+
+        self.render_footer(
+            rects[2],
+            buf,
+            vec![
+                ("<Esc>", "Return to cron jobs"),
+                ("<Tab>", "Switch file"),
+                ("<Ctrl+s>", "Save file"),
+                ("<Ctrl+d>", "Delete file"),
+            ],
+        );
+
+        if let Some(confirm) = self.confirm {
+            self.render_confirm(confirm, area, buf);
+        }
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if let Some(confirm) = self.confirm {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.confirm = None;
+                    match confirm {
+                        Confirm::SaveEmptyAllow => self.write(),
+                        Confirm::Delete => self.remove(),
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.confirm = None,
+                _ => {}
+            }
+            None
+        } else if key.code == KeyCode::Esc {
+            Some(Screen::CronTable(CronTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl CronAccess {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        let ctrl_pressed = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Tab => {
+                self.current = (self.current + 1) % self.files.len();
+            }
+            KeyCode::Char('s') if ctrl_pressed => self.save(),
+            KeyCode::Char('d') if ctrl_pressed => self.delete(),
+            _ => {
+                let file = &mut self.files[self.current];
+                if file.editor.input(key) {
+                    file.modified = true;
+                }
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        let file = &mut self.files[self.current];
+        let invalid = file.invalid_lines();
+        if !invalid.is_empty() {
+            self.message = Some(format!(
+                "Not saved: lines {:?} contain spaces, expected one user name per line",
+                invalid
+            ));
+            return;
+        }
+        if file.is_allow() && file.is_empty() {
+            self.confirm = Some(Confirm::SaveEmptyAllow);
+            return;
+        }
+        self.write();
+    }
+
+    fn write(&mut self) {
+        let file = &mut self.files[self.current];
+        // Writing in place keeps the mode and owner of an existing file.
+        match fs::write(&file.path, file.content()) {
+            Ok(_) => {
+                file.exists = true;
+                file.modified = false;
+                self.message = Some(format!(
+                    "Saved {}. {}",
+                    file.path.display(),
+                    permission_summary()
+                ));
+            }
+            Err(err) => {
+                self.message = Some(format!("Error writing {}: {}", file.path.display(), err));
+            }
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.files[self.current].exists {
+            self.confirm = Some(Confirm::Delete);
+        }
+    }
+
+    fn remove(&mut self) {
+        let file = &mut self.files[self.current];
+        match fs::remove_file(&file.path) {
+            Ok(_) => {
+                file.exists = false;
+                file.modified = false;
+                file.editor = TextArea::default();
+                file.editor.set_cursor_line_style(Style::default());
+                self.message = Some(format!(
+                    "Deleted {}. {}",
+                    file.path.display(),
+                    permission_summary()
+                ));
+            }
+            Err(err) => {
+                self.message = Some(format!("Error deleting {}: {}", file.path.display(), err));
+            }
+        }
+    }
+
+    /// What confirming `confirm` does to the cron permissions.
+    fn consequence(&self, confirm: Confirm) -> String {
+        let file = &self.files[self.current];
+        match confirm {
+            Confirm::SaveEmptyAllow => format!(
+                "An empty {} lets only root use cron, every other user is refused.",
+                CRON_ALLOW
+            ),
+            Confirm::Delete if file.is_allow() => format!(
+                "Without {} every user not listed in {} may use cron.",
+                CRON_ALLOW, CRON_DENY
+            ),
+            Confirm::Delete if self.files.iter().any(|other| other.is_allow() && other.exists) => {
+                format!(
+                    "{} takes precedence, nobody gains access until it is removed too.",
+                    CRON_ALLOW
+                )
+            }
+            Confirm::Delete => format!(
+                "Without {} and {} the cron default applies: every user on most systems, only root on some.",
+                CRON_ALLOW, CRON_DENY
+            ),
+        }
+    }
+
+    fn render_confirm(&self, confirm: Confirm, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Max(7)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Widget::render(Clear, area, buf);
+
+        let path = self.files[self.current].path.display();
+        let title = match confirm {
+            Confirm::SaveEmptyAllow => format!(" Save empty {} ", path),
+            Confirm::Delete => format!(" Delete {} ", path),
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(CONFIRM_INFO_TEXT).centered())
+            .style(self.input_styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.input_styles.window_border_style);
+        let lines = vec![
+            Line::styled(self.consequence(confirm), self.input_styles.title_style),
+            Line::raw(""),
+            Line::raw(format!("Now: {}", permission_summary())),
+        ];
+        let report = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .block(block);
+        Widget::render(report, area, buf);
+    }
+}
+
+/// Describes what the saved files mean for the managed user.
+fn permission_summary() -> String {
+    let user = cron_user();
+    let check = check_permission(&user, Path::new(CRON_ALLOW), Path::new(CRON_DENY));
+    let verdict = if check.passed { "may" } else { "may not" };
+    format!("{} {} use cron: {}", user, verdict, check.detail)
+}
@@ -0,0 +1,610 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::cron::{CronTable, EditWindowStyles, TableStyles};
+use ratatui::{
+    crossterm::event::{self, KeyCode, MouseEvent},
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    text::{Line, Text},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, TableState, Wrap,
+    },
+};
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use unicode_width::UnicodeWidthStr;
+
+const ITEM_HEIGHT: usize = 4;
+const CONFIRM_INFO_TEXT: &str = " (Esc) Cancel | (y) Run ";
+const ANACRONTAB: &str = "/etc/anacrontab";
+const ANACRON_SPOOL: &str = "/var/spool/anacron";
+const CRON_DIRECTORIES: [(&str, &str); 4] = [
+    ("/etc/cron.hourly", "hourly"),
+    ("/etc/cron.daily", "daily"),
+    ("/etc/cron.weekly", "weekly"),
+    ("/etc/cron.monthly", "monthly"),
+];
+
+/// A job started by anacron, either an `/etc/anacrontab` entry or a script
+/// inside one of the `/etc/cron.{hourly,daily,weekly,monthly}` directories.
+pub struct AnacronJob {
+    pub name: String,
+    pub period: String,
+    pub delay: String,
+    pub last_run: String,
+    pub command: String,
+    pub skip_reason: Option<String>,
+}
+
+impl AnacronJob {
+    fn ref_array(&self) -> [&str; 5] {
+        [
+            &self.name,
+            &self.period,
+            &self.delay,
+            &self.last_run,
+            self.skip_reason.as_deref().unwrap_or("Runs"),
+        ]
+    }
+}
+
+/// A single line of `/etc/anacrontab`.
+pub struct AnacrontabEntry {
+    pub period: String,
+    pub delay: String,
+    pub job_id: String,
+    pub command: String,
+}
+
+pub struct AnacronTable {
+    state: TableState,
+    items: Vec<AnacronJob>,
+    longest_item_lens: (u16, u16, u16, u16, u16),
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    input_styles: EditWindowStyles,
+    message: Option<String>,
+    /// Index of the job waiting for the run to be confirmed.
+    confirm: Option<usize>,
+    /// Name of the job running in the background and where its result arrives.
+    running: Option<(String, Receiver<io::Result<String>>)>,
+}
+
+impl ScreenTrait for AnacronTable {
+    fn new() -> Self {
+        let (items, message) =
+            match load_anacron_jobs(Path::new(ANACRONTAB), Path::new(ANACRON_SPOOL)) {
+                Ok(items) => (items, None),
+                Err(err) => {
+                    tracing::error!("Error reading anacron jobs: {}", err);
+                    (
+                        Vec::new(),
+                        Some(format!("Error reading anacron jobs: {}", err)),
+                    )
+                }
+            };
+        let scroll_position = if items.is_empty() {
+            0
+        } else {
+            (items.len() - 1) * ITEM_HEIGHT
+        };
+        Self {
+            state: TableState::default().with_selected(0),
+            longest_item_lens: constraint_len_calculator(&items),
+            scroll_state: ScrollbarState::new(scroll_position),
+            styles: TableStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            items,
+            message,
+            confirm: None,
+            running: None,
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.poll_running();
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+
+        self.render_table(rects[0], buf);
+        self.render_scrollbar(rects[0], buf);
+        self.render_message(rects[1], buf);
+        self.render_footer(
+            rects[2],
+            buf,
+            vec![
+                ("<Esc>", "Return to cron jobs"),
+                ("<↓↑>", "Move up and down"),
+                ("<r>", "Run selected job now"),
+            ],
+        );
+
+        if let Some(index) = self.confirm {
+            self.render_confirm(index, area, buf);
+        }
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if let Some(index) = self.confirm {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.confirm = None;
+                    self.start(index);
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.confirm = None,
+                _ => {}
+            }
+            None
+        } else if key.code == KeyCode::Esc {
+            Some(Screen::CronTable(CronTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl AnacronTable {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.next_row();
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.previous_row();
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.first_row();
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.last_row();
+            }
+            KeyCode::Char('r') => {
+                let Some(index) = self.state.selected().filter(|&i| i < self.items.len()) else {
+                    return;
+                };
+                let job = &self.items[index];
+                if let Some((name, _)) = &self.running {
+                    self.message = Some(format!("{} is still running", name));
+                } else if let Some(reason) = &job.skip_reason {
+                    // run-parts would not run it either, so neither do we.
+                    self.message = Some(format!("{}: not run, {}", job.name, reason));
+                } else {
+                    self.confirm = Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs the job on a separate thread so a long script does not freeze
+    /// the screen; its result is picked up by `poll_running`.
+    fn start(&mut self, index: usize) {
+        let job = &self.items[index];
+        let command = job.command.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(run_now(&command));
+        });
+        self.message = Some(format!("{}: running", job.name));
+        self.running = Some((job.name.clone(), receiver));
+    }
+
+    fn poll_running(&mut self) {
+        let Some((name, receiver)) = &self.running else {
+            return;
+        };
+        let message = match receiver.try_recv() {
+            Ok(Ok(status)) => format!("{}: {}", name, status),
+            Ok(Err(err)) => format!("{}: failed to start ({})", name, err),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => format!("{}: run aborted", name),
+        };
+        self.message = Some(message);
+        self.running = None;
+    }
+
+    fn render_confirm(&self, index: usize, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Max(7)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Widget::render(Clear, area, buf);
+
+        let job = &self.items[index];
+        let block = Block::default()
+            .title(format!(" Run {} now ", job.name))
+            .title_bottom(Line::from(CONFIRM_INFO_TEXT).centered())
+            .style(self.input_styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.input_styles.window_border_style);
+        let lines = vec![
+            Line::styled(job.command.as_str(), self.input_styles.title_style),
+            Line::raw(""),
+            Line::raw("The job runs as the current user, outside of anacron."),
+        ];
+        let report = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .block(block);
+        Widget::render(report, area, buf);
+    }
+
+    fn next_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i < self.items.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+    }
+
+    fn previous_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.items.len() - 1,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+    }
+
+    fn first_row(&mut self) {
+        self.state.select(Some(0));
+        self.scroll_state = self.scroll_state.position(0);
+    }
+
+    fn last_row(&mut self) {
+        if !self.items.is_empty() {
+            let last_index = self.items.len() - 1;
+            self.state.select(Some(last_index));
+            self.scroll_state = self.scroll_state.position(last_index * ITEM_HEIGHT);
+        }
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let header = ["Job", "Period", "Delay", "Last Run", "Status"]
+            .into_iter()
+            .map(|title| Cell::from(Text::from(format!("\n{}\n", title))))
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(3);
+        let rows = self.items.iter().enumerate().map(|(i, data)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            let style = if data.skip_reason.is_some() {
+                self.styles.skipped_row_style.bg(color)
+            } else {
+                self.styles.row_style.bg(color)
+            };
+            data.ref_array()
+                .into_iter()
+                .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
+                .collect::<Row>()
+                .style(style)
+                .height(ITEM_HEIGHT.try_into().unwrap())
+        });
+        let bar = " ▌ ";
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Min(self.longest_item_lens.0 + 1),
+                Constraint::Length(self.longest_item_lens.1 + 2),
+                Constraint::Length(self.longest_item_lens.2 + 2),
+                Constraint::Length(self.longest_item_lens.3 + 2),
+                Constraint::Min(self.longest_item_lens.4),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.styles.selected_row_style)
+        .style(
+            self.styles
+                .row_style
+                .bg(if (self.items.len() + 1).is_multiple_of(2) {
+                    self.styles.alt_row_color
+                } else {
+                    self.styles.normal_row_color
+                }),
+        )
+        .highlight_symbol(Text::from(vec![
+            "".into(),
+            bar.into(),
+            bar.into(),
+            "".into(),
+        ]))
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+
+    fn render_message(&mut self, area: Rect, buf: &mut Buffer) {
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, area, buf);
+    }
+}
+
+/// Parses the contents of an anacrontab file, skipping comments and
+/// environment assignments.
+pub fn parse_anacrontab(content: &str) -> Vec<AnacrontabEntry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 4 || parts[0].contains('=') {
+                return None;
+            }
+            Some(AnacrontabEntry {
+                period: parts[0].to_string(),
+                delay: parts[1].to_string(),
+                job_id: parts[2].to_string(),
+                command: parts[3..].join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Returns the reason `run-parts` would skip a script with the given name
+/// and permissions, or `None` if the script is run.
+pub fn run_parts_skip_reason(name: &str, mode: u32) -> Option<String> {
+    if name.contains('.') {
+        Some("Skipped: name contains a dot".to_string())
+    } else if mode & 0o111 == 0 {
+        Some("Skipped: not executable".to_string())
+    } else {
+        None
+    }
+}
+
+/// Collects the anacrontab entries and the scripts in the cron period directories.
+/// Scripts are matched to the anacrontab entry that runs their directory to
+/// get the delay and the last run timestamp.
+pub fn load_anacron_jobs(anacrontab: &Path, spool: &Path) -> io::Result<Vec<AnacronJob>> {
+    let entries = match fs::read_to_string(anacrontab) {
+        Ok(content) => parse_anacrontab(&content),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+
+    let mut jobs = Vec::new();
+
+    for entry in &entries {
+        let runs_directory = CRON_DIRECTORIES
+            .iter()
+            .any(|(directory, _)| entry.command.contains(directory));
+        if !runs_directory {
+            jobs.push(AnacronJob {
+                name: entry.job_id.clone(),
+                period: format_period(&entry.period),
+                delay: format_delay(&entry.delay),
+                last_run: last_run(spool, &entry.job_id),
+                command: entry.command.clone(),
+                skip_reason: None,
+            });
+        }
+    }
+
+    for (directory, label) in CRON_DIRECTORIES {
+        let entry = entries
+            .iter()
+            .find(|entry| entry.command.contains(directory));
+        let (delay, last_run) = match entry {
+            Some(entry) => (format_delay(&entry.delay), last_run(spool, &entry.job_id)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        for script in list_scripts(Path::new(directory))? {
+            let name = script
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mode = fs::metadata(&script)?.permissions().mode();
+            jobs.push(AnacronJob {
+                name: format!("{}/{}", label, name),
+                period: label.to_string(),
+                delay: delay.clone(),
+                last_run: last_run.clone(),
+                skip_reason: run_parts_skip_reason(&name, mode),
+                command: script.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    Ok(jobs)
+}
+
+fn list_scripts(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let read_dir = match fs::read_dir(directory) {
+        Ok(read_dir) => read_dir,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut scripts = Vec::new();
+    for entry in read_dir {
+        let path = entry?.path();
+        if path.is_file() {
+            scripts.push(path);
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
+
+/// Reads the `YYYYMMDD` timestamp anacron keeps for a job id.
+fn last_run(spool: &Path, job_id: &str) -> String {
+    match fs::read_to_string(spool.join(job_id)) {
+        Ok(content) => {
+            let stamp = content.trim();
+            if stamp.len() == 8 && stamp.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}-{}-{}", &stamp[..4], &stamp[4..6], &stamp[6..])
+            } else {
+                stamp.to_string()
+            }
+        }
+        Err(_) => "Never".to_string(),
+    }
+}
+
+fn format_period(period: &str) -> String {
+    match period {
+        "1" => "1 day".to_string(),
+        p if p.starts_with('@') => p.to_string(),
+        p => format!("{} days", p),
+    }
+}
+
+fn format_delay(delay: &str) -> String {
+    match delay {
+        "0" => "none".to_string(),
+        "1" => "1 minute".to_string(),
+        d => format!("{} minutes", d),
+    }
+}
+
+fn run_now(command: &str) -> io::Result<String> {
+    let output = if Path::new(command).is_file() {
+        Command::new(command).output()?
+    } else {
+        Command::new("sh").arg("-c").arg(command).output()?
+    };
+
+    if output.status.success() {
+        Ok("finished successfully".to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(format!(
+            "{} {}",
+            output.status,
+            stderr.lines().last().unwrap_or("").trim()
+        ))
+    }
+}
+
+fn constraint_len_calculator(items: &[AnacronJob]) -> (u16, u16, u16, u16, u16) {
+    let mut lens = [0usize; 5];
+    for item in items {
+        for (len, content) in lens.iter_mut().zip(item.ref_array()) {
+            *len = (*len).max(UnicodeWidthStr::width(content));
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    (
+        lens[0] as u16,
+        lens[1] as u16,
+        lens[2] as u16,
+        lens[3] as u16,
+        lens[4] as u16,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/anacrontab");
+
+    #[test]
+    fn parse_anacrontab_skips_comments_and_assignments() {
+        let entries = parse_anacrontab(FIXTURE);
+        let found: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.period.as_str(),
+                    entry.delay.as_str(),
+                    entry.job_id.as_str(),
+                    entry.command.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("1", "5", "cron.daily", "run-parts --report /etc/cron.daily"),
+                (
+                    "7",
+                    "10",
+                    "cron.weekly",
+                    "run-parts --report /etc/cron.weekly"
+                ),
+                (
+                    "@monthly",
+                    "15",
+                    "cron.monthly",
+                    "run-parts --report /etc/cron.monthly"
+                ),
+                ("3", "0", "backup", "/usr/local/bin/backup --full"),
+            ]
+        );
+    }
+
+    #[test]
+    fn run_parts_skips_dotted_and_non_executable_scripts() {
+        assert_eq!(run_parts_skip_reason("logrotate", 0o755), None);
+        assert_eq!(run_parts_skip_reason("owner-only", 0o700), None);
+        assert_eq!(
+            run_parts_skip_reason("backup.sh", 0o755).as_deref(),
+            Some("Skipped: name contains a dot")
+        );
+        assert_eq!(
+            run_parts_skip_reason("logrotate", 0o644).as_deref(),
+            Some("Skipped: not executable")
+        );
+    }
+
+    #[test]
+    fn last_run_formats_the_spool_timestamp() {
+        let spool = std::env::temp_dir().join(format!("tuixel-anacron-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&spool);
+        fs::create_dir_all(&spool).unwrap();
+        fs::write(spool.join("cron.daily"), "20261017\n").unwrap();
+        fs::write(spool.join("odd"), "yesterday\n").unwrap();
+
+        assert_eq!(last_run(&spool, "cron.daily"), "2026-10-17");
+        assert_eq!(last_run(&spool, "odd"), "yesterday");
+        assert_eq!(last_run(&spool, "cron.weekly"), "Never");
+        fs::remove_dir_all(spool).unwrap();
+    }
+}
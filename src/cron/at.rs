@@ -0,0 +1,593 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::config::CONFIG;
+use crate::cron::query::{parse_datetime, DATE_FORMAT};
+use crate::cron::utils::first_line;
+use crate::cron::{CronTable, EditWindowStyles, TableStyles};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyModifiers, MouseEvent},
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    widgets::{
+        Block, Borders, Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState, Wrap,
+    },
+};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use tui_textarea::TextArea;
+
+/// `atq` prints times like `Mon Oct 19 14:00:00 2026` in the server's local time.
+const ATQ_TIME_FORMAT: &str = "%a %b %e %H:%M:%S %Y";
+const BATCH_QUEUE: char = 'b';
+
+/// A job waiting in the `at` queue, as listed by `atq`.
+pub struct AtJob {
+    pub id: u32,
+    pub time: NaiveDateTime,
+    pub queue: char,
+    pub user: String,
+}
+
+impl AtJob {
+    fn ref_array(&self, timezone: &Tz) -> [String; 4] {
+        let queue = match self.queue {
+            '=' => "= (running)".to_string(),
+            BATCH_QUEUE => "b (batch)".to_string(),
+            queue => queue.to_string(),
+        };
+        [
+            self.id.to_string(),
+            format_local_time(&self.time, timezone),
+            queue,
+            self.user.clone(),
+        ]
+    }
+}
+
+/// Parses the output of `atq`, one job per line with the job number and a tab
+/// before the time, queue and user:
+///
+/// ```text
+/// 12<TAB>Mon Oct 19 14:00:00 2026 a root
+/// ```
+///
+/// Lines that do not match are skipped.
+pub fn parse_atq(output: &str) -> Vec<AtJob> {
+    let mut jobs: Vec<AtJob> = output
+        .lines()
+        .filter_map(|line| {
+            let (id, rest) = line.split_once('\t')?;
+            let mut fields: Vec<&str> = rest.split_whitespace().collect();
+            let user = fields.pop()?.to_string();
+            let queue = fields.pop()?.chars().next()?;
+            let time = NaiveDateTime::parse_from_str(&fields.join(" "), ATQ_TIME_FORMAT).ok()?;
+            Some(AtJob {
+                id: id.trim().parse().ok()?,
+                time,
+                queue,
+                user,
+            })
+        })
+        .collect();
+    jobs.sort_by_key(|job| (job.time, job.id));
+    jobs
+}
+
+/// Extracts the command from the script printed by `at -c`. `at` stores the
+/// environment and working directory first and passes the command to the shell
+/// as a here-document:
+///
+/// ```text
+/// ${SHELL:-/bin/sh} << 'marcinDELIMITER2b6d9c5e'
+/// /usr/local/bin/deploy.sh
+/// marcinDELIMITER2b6d9c5e
+/// ```
+///
+/// Returns `None` if the script has no such here-document.
+pub fn parse_at_script(script: &str) -> Option<String> {
+    let mut lines = script.lines();
+    let delimiter = lines.by_ref().find_map(|line| {
+        let (_, quoted) = line.split_once("<< '")?;
+        quoted.strip_suffix('\'')
+    })?;
+
+    let command: Vec<&str> = lines.take_while(|line| *line != delimiter).collect();
+    Some(command.join("\n"))
+}
+
+fn format_local_time(time: &NaiveDateTime, timezone: &Tz) -> String {
+    match Local.from_local_datetime(time).earliest() {
+        Some(local) => local
+            .with_timezone(timezone)
+            .format(DATE_FORMAT)
+            .to_string(),
+        None => time.format(DATE_FORMAT).to_string(),
+    }
+}
+
+fn run_command(command: &mut Command) -> io::Result<String> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn load_at_jobs() -> io::Result<Vec<AtJob>> {
+    run_command(&mut Command::new("atq")).map(|output| parse_atq(&output))
+}
+
+pub fn read_at_script(id: u32) -> io::Result<String> {
+    run_command(Command::new("at").arg("-c").arg(id.to_string()))
+}
+
+pub fn remove_at_job(id: u32) -> io::Result<()> {
+    run_command(Command::new("atrm").arg(id.to_string())).map(|_| ())
+}
+
+/// Schedules `command` with `at` at `time`, or with `batch` when `time` is
+/// `None`. Returns what `at` reported, e.g. `job 12 at Mon Oct 19 14:00:00 2026`.
+pub fn schedule_at_job(command: &str, time: Option<DateTime<Tz>>) -> io::Result<String> {
+    let mut process = match time {
+        // `at -t` takes the time in the server's local timezone.
+        Some(time) => {
+            let local = time.with_timezone(&Local);
+            let mut process = Command::new("at");
+            process
+                .arg("-t")
+                .arg(local.format("%Y%m%d%H%M").to_string());
+            process
+        }
+        None => Command::new("batch"),
+    };
+    let mut child = process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(command.as_bytes())?;
+        stdin.write_all(b"\n")?;
+    }
+
+    let output = child.wait_with_output()?;
+    // `at` reports the scheduled job and its warnings on stderr.
+    let report = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return Err(io::Error::other(report));
+    }
+    Ok(report
+        .lines()
+        .find(|line| line.starts_with("job "))
+        .unwrap_or(&report)
+        .to_string())
+}
+
+#[derive(PartialEq)]
+enum Focus {
+    List,
+    Time,
+    Command,
+}
+
+pub struct AtQueue {
+    state: TableState,
+    items: Vec<AtJob>,
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    input_styles: EditWindowStyles,
+    script: Option<String>,
+    time: TextArea<'static>,
+    command: TextArea<'static>,
+    focus: Focus,
+    message: Option<String>,
+}
+
+impl ScreenTrait for AtQueue {
+    fn new() -> Self {
+        let mut queue = Self {
+            state: TableState::default().with_selected(0),
+            items: Vec::new(),
+            scroll_state: ScrollbarState::new(0),
+            styles: TableStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            script: None,
+            time: TextArea::default(),
+            command: TextArea::default(),
+            focus: Focus::List,
+            message: None,
+        };
+        queue.refresh();
+        queue
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let form_height = if self.focus == Focus::List { 0 } else { 3 };
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(form_height),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+        let [table_area, script_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(rects[0]);
+
+        self.render_table(table_area, buf);
+        self.render_scrollbar(table_area, buf);
+        self.render_script(script_area, buf);
+        if self.focus != Focus::List {
+            self.render_form(rects[1], buf);
+        }
+        self.render_message(rects[2], buf);
+        let keybinds = if self.focus == Focus::List {
+            vec![
+                ("<Esc>", "Return to cron jobs"),
+                ("<↓↑>", "Move up and down"),
+                ("<n>", "Schedule a new job"),
+                ("<d>", "Remove selected job (atrm)"),
+                ("<r>", "Refresh"),
+            ]
+        } else {
+            vec![
+                ("<Esc>", "Cancel"),
+                ("<Tab>", "Switch between time and command"),
+                ("<↓↑>", "Time ±15 minutes"),
+                ("<PgDn/PgUp>", "Time ±1 day"),
+                ("<Enter>", "Schedule with at"),
+                ("<Ctrl+b>", "Queue with batch"),
+            ]
+        };
+        self.render_footer(rects[3], buf, keybinds);
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if key.code == KeyCode::Esc && self.focus == Focus::List {
+            Some(Screen::CronTable(CronTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl AtQueue {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        if self.focus == Focus::List {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => self.next_row(),
+                KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
+                KeyCode::Char('n') => self.open_form(),
+                KeyCode::Char('d') => self.remove_selected(),
+                KeyCode::Char('r') => self.refresh(),
+                _ => {}
+            }
+            return;
+        }
+
+        let ctrl_pressed = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.focus = Focus::List,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Time => Focus::Command,
+                    _ => Focus::Time,
+                };
+            }
+            KeyCode::Enter => self.schedule(false),
+            KeyCode::Char('b') if ctrl_pressed => self.schedule(true),
+            KeyCode::Up if self.focus == Focus::Time => self.shift_time(Duration::minutes(15)),
+            KeyCode::Down if self.focus == Focus::Time => self.shift_time(-Duration::minutes(15)),
+            KeyCode::PageUp if self.focus == Focus::Time => self.shift_time(Duration::days(1)),
+            KeyCode::PageDown if self.focus == Focus::Time => self.shift_time(-Duration::days(1)),
+            _ => {
+                match self.focus {
+                    Focus::Time => self.time.input(key),
+                    _ => self.command.input(key),
+                };
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        match load_at_jobs() {
+            Ok(jobs) => {
+                self.message = Some(format!("{} jobs in the at queue", jobs.len()));
+                self.items = jobs;
+            }
+            Err(err) => {
+                tracing::error!("Error running atq: {}", err);
+                self.message = Some(format!("Error running atq: {}", err));
+                self.items.clear();
+            }
+        }
+        self.scroll_state = ScrollbarState::new(self.items.len().saturating_sub(1));
+        if self.state.selected().is_none_or(|i| i >= self.items.len()) {
+            self.state.select(Some(0));
+        }
+        self.load_script();
+    }
+
+    fn load_script(&mut self) {
+        self.script = self
+            .state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(|job| {
+                read_at_script(job.id).unwrap_or_else(|err| format!("Error running at -c: {}", err))
+            });
+    }
+
+    fn remove_selected(&mut self) {
+        let Some(job) = self.state.selected().and_then(|i| self.items.get(i)) else {
+            return;
+        };
+        let id = job.id;
+        match remove_at_job(id) {
+            Ok(_) => {
+                self.refresh();
+                self.message = Some(format!("Removed job {}", id));
+            }
+            Err(err) => self.message = Some(format!("Error running atrm {}: {}", id, err)),
+        }
+    }
+
+    /// Opens the form with the time set to the next full hour.
+    fn open_form(&mut self) {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        let now = Utc::now().with_timezone(&timezone);
+        let next_hour = (now + Duration::hours(1))
+            .with_minute(0)
+            .unwrap_or(now)
+            .format(DATE_FORMAT)
+            .to_string();
+
+        self.time = TextArea::from([next_hour]);
+        self.command = TextArea::default();
+        self.command.set_placeholder_text("Command to run once");
+        self.focus = Focus::Time;
+    }
+
+    fn shift_time(&mut self, duration: Duration) {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        if let Ok(time) = parse_datetime(first_line(&self.time), &timezone) {
+            self.time = TextArea::from([(time + duration).format(DATE_FORMAT).to_string()]);
+        }
+    }
+
+    fn schedule(&mut self, batch: bool) {
+        let command = first_line(&self.command).to_string();
+        if command.is_empty() {
+            self.message = Some("Enter a command to schedule".to_string());
+            return;
+        }
+
+        let time = if batch {
+            None
+        } else {
+            let timezone = CONFIG.read().unwrap().get_timezone();
+            match parse_datetime(first_line(&self.time), &timezone) {
+                Ok(time) if time > Utc::now() => Some(time),
+                Ok(_) => {
+                    self.message = Some("The time must be in the future".to_string());
+                    return;
+                }
+                Err(err) => {
+                    self.message = Some(err);
+                    return;
+                }
+            }
+        };
+
+        match schedule_at_job(&command, time) {
+            Ok(report) => {
+                self.focus = Focus::List;
+                self.refresh();
+                self.message = Some(report);
+            }
+            Err(err) => self.message = Some(format!("Error scheduling job: {}", err)),
+        }
+    }
+
+    fn next_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i < self.items.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i);
+        self.load_script();
+    }
+
+    fn previous_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.items.len() - 1,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i);
+        self.load_script();
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        let time_title = format!("Time ({})", timezone);
+        let header = ["Job", time_title.as_str(), "Queue", "User"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(1);
+        let rows = self.items.iter().enumerate().map(|(i, job)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            job.ref_array(&timezone)
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(self.styles.row_style.bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Length(6),
+                Constraint::Length(24),
+                Constraint::Length(12),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.styles.selected_row_style)
+        .style(self.styles.row_style.bg(self.styles.normal_row_color))
+        .highlight_symbol(" ▌ ")
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+
+    /// Shows the command of the selected job followed by the full script.
+    fn render_script(&mut self, area: Rect, buf: &mut Buffer) {
+        let text = match &self.script {
+            Some(script) => match parse_at_script(script) {
+                Some(command) => format!("{}\n\n--- at -c ---\n{}", command, script),
+                None => script.clone(),
+            },
+            None => String::new(),
+        };
+        let script = Paragraph::new(text)
+            .style(self.styles.row_style.bg(self.styles.alt_row_color))
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::LEFT).title(" Command "));
+        Widget::render(script, area, buf);
+    }
+
+    fn render_form(&mut self, area: Rect, buf: &mut Buffer) {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        let [time_area, command_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Fill(1)]).areas(area);
+
+        for (input, focus, title, input_area) in [
+            (
+                &mut self.time,
+                Focus::Time,
+                format!("Run at ({})", timezone),
+                time_area,
+            ),
+            (
+                &mut self.command,
+                Focus::Command,
+                "Command".to_string(),
+                command_area,
+            ),
+        ] {
+            let (border_style, cursor_style) = if self.focus == focus {
+                (
+                    self.input_styles.selected_input_border_style,
+                    self.input_styles.cursor_style,
+                )
+            } else {
+                (
+                    self.input_styles.unselected_input_border_style,
+                    Style::default(),
+                )
+            };
+            input.set_cursor_line_style(Style::default());
+            input.set_cursor_style(cursor_style);
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(title),
+            );
+            input.render(input_area, buf);
+        }
+    }
+
+    fn render_message(&mut self, area: Rect, buf: &mut Buffer) {
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn parse_atq_reads_queue_and_time() {
+        let jobs = parse_atq(include_str!("../../tests/fixtures/atq.txt"));
+
+        let ids: Vec<u32> = jobs.iter().map(|job| job.id).collect();
+        assert_eq!(ids, [7, 12, 14]);
+
+        let running = &jobs[0];
+        assert_eq!(running.queue, '=');
+        assert_eq!(running.user, "bob");
+        assert_eq!(
+            running.time,
+            NaiveDate::from_ymd_opt(2026, 10, 5)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap()
+        );
+
+        assert_eq!(jobs[1].queue, 'a');
+        assert_eq!(jobs[1].user, "root");
+        assert_eq!(jobs[2].queue, BATCH_QUEUE);
+        assert_eq!(jobs[2].user, "alice");
+    }
+
+    #[test]
+    fn parse_at_script_skips_the_environment() {
+        let command = parse_at_script(include_str!("../../tests/fixtures/at-script.txt"));
+
+        assert_eq!(
+            command.as_deref(),
+            Some("/usr/local/bin/deploy.sh --env prod\necho \"deployed\" | mail -s deploy ops")
+        );
+    }
+
+    #[test]
+    fn parse_at_script_without_here_document() {
+        assert_eq!(parse_at_script("#!/bin/sh\numask 22\n"), None);
+    }
+}
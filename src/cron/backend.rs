@@ -0,0 +1,327 @@
+use crate::ftp::backend::temporary_path;
+use crate::log::PROJECT_NAME;
+use lazy_static::lazy_static;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+const SPOOL_DIR: &str = "/var/spool/cron/crontabs";
+/// Group of the spool files, which lets the setgid `crontab` command read them.
+const CRONTAB_GROUP: &str = "crontab";
+
+lazy_static! {
+    pub static ref BACKEND_ENV: String = format!("{}_CRON_BACKEND", PROJECT_NAME.clone());
+    pub static ref USER_ENV: String = format!("{}_CRON_USER", PROJECT_NAME.clone());
+    static ref DEMO_BACKEND: MemoryBackend = MemoryBackend::new(Some(DEMO_CRONTAB));
+}
+
+const DEMO_CRONTAB: &str = "\
+# Renew certificates
+# tuixel: tags=web owner=ops
+0 3 * * * certbot renew --quiet
+
+# Nightly database dump
+# tuixel: tags=backup,db owner=dba
+30 2 * * * mysqldump --all-databases > /var/backups/all.sql
+
+# Clean up temporary files
+15 4 * * 0 find /tmp -type f -mtime +7 -delete
+";
+
+/// Storage for the text of a single user's crontab.
+pub trait CrontabBackend {
+    /// Returns the crontab text, or `None` if the user has no crontab.
+    fn read(&self) -> io::Result<Option<String>>;
+
+    /// Replaces the crontab with `content`.
+    fn write(&self, content: &str) -> io::Result<()>;
+
+    /// A short label for the UI, e.g. `crontab -u alice`.
+    fn describe(&self) -> String;
+}
+
+/// Reads and installs crontabs through the `crontab` command.
+pub struct CommandBackend {
+    user: Option<String>,
+}
+
+impl CommandBackend {
+    pub fn new(user: Option<String>) -> Self {
+        Self { user }
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("crontab");
+        if let Some(user) = &self.user {
+            command.arg("-u").arg(user);
+        }
+        command
+    }
+}
+
+impl CrontabBackend for CommandBackend {
+    fn read(&self) -> io::Result<Option<String>> {
+        let output = self.command().arg("-l").stdout(Stdio::piped()).output()?;
+
+        if !output.status.success() {
+            let stderr_output = String::from_utf8_lossy(&output.stderr);
+
+            if stderr_output.contains("no crontab for") {
+                return Ok(None);
+            }
+
+            return Err(io::Error::other("Failed to read crontab"));
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+    }
+
+    fn write(&self, content: &str) -> io::Result<()> {
+        let mut process = self.command().stdin(Stdio::piped()).spawn()?;
+
+        if let Some(stdin) = process.stdin.as_mut() {
+            stdin.write_all(content.as_bytes())?;
+        }
+
+        let status = process.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("crontab exited with {}", status)));
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        match &self.user {
+            Some(user) => format!("crontab -u {}", user),
+            None => "crontab".to_string(),
+        }
+    }
+}
+
+/// Reads and writes the spool file of a user directly, e.g.
+/// `/var/spool/cron/crontabs/<user>`. The spool directory can point into a
+/// mounted system image.
+pub struct SpoolBackend {
+    path: PathBuf,
+    user: String,
+    etc: Option<PathBuf>,
+}
+
+impl SpoolBackend {
+    pub fn new(spool_dir: &Path, user: &str) -> Self {
+        Self {
+            path: spool_dir.join(user),
+            user: user.to_string(),
+            etc: system_etc(spool_dir),
+        }
+    }
+
+    /// Owner of a new spool file: the user, and the `crontab` group or else
+    /// the group of the spool directory, looked up in the `etc` of the system
+    /// the spool belongs to. A spool outside any `var/spool` tree takes the
+    /// owner of its directory.
+    fn new_file_owner(&self) -> io::Result<(u32, u32)> {
+        let directory = fs::metadata(self.path.parent().unwrap_or(Path::new("/")))?;
+        let Some(etc) = &self.etc else {
+            return Ok((directory.uid(), directory.gid()));
+        };
+        let passwd = etc.join("passwd");
+        let uid = find_id(&passwd, &self.user).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in {}", self.user, passwd.display()),
+            )
+        })?;
+        let gid = find_id(&etc.join("group"), CRONTAB_GROUP).unwrap_or(directory.gid());
+        Ok((uid, gid))
+    }
+}
+
+/// The `etc` directory of the system a spool directory belongs to: `/etc`
+/// for `/var/spool/cron/crontabs`, `<image>/etc` for a spool inside a
+/// mounted image.
+fn system_etc(spool_dir: &Path) -> Option<PathBuf> {
+    let components: Vec<_> = spool_dir.components().collect();
+    let var = components
+        .windows(2)
+        .rposition(|pair| pair[0].as_os_str() == "var" && pair[1].as_os_str() == "spool")?;
+    let root: PathBuf = components[..var].iter().collect();
+    Some(root.join("etc"))
+}
+
+/// The id in the third field of the `name` entry of a `passwd` or `group`
+/// file.
+fn find_id(file: &Path, name: &str) -> Option<u32> {
+    fs::read_to_string(file)
+        .ok()?
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields[0] == name)
+        .and_then(|fields| fields.get(2)?.parse().ok())
+}
+
+impl CrontabBackend for SpoolBackend {
+    fn read(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes to a temporary file next to the spool file and renames it into
+    /// place, so the cron daemon never sees a partial file and notices the
+    /// changed directory. The file has mode `0600`; ownership of an existing
+    /// spool file is kept and a new one is given to the user.
+    fn write(&self, content: &str) -> io::Result<()> {
+        let (uid, gid) = match fs::metadata(&self.path) {
+            Ok(metadata) => (metadata.uid(), metadata.gid()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => self.new_file_owner()?,
+            Err(err) => return Err(err),
+        };
+        // The leading dot hides the temporary file from cron.
+        let temporary = temporary_path(&self.path);
+
+        let write = || -> io::Result<()> {
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&temporary)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            std::os::unix::fs::chown(&temporary, Some(uid), Some(gid))?;
+            fs::rename(&temporary, &self.path)
+        };
+        write().inspect_err(|_| {
+            let _ = fs::remove_file(&temporary);
+        })
+    }
+
+    fn describe(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+/// Keeps the crontab in memory, for tests and the demo mode.
+pub struct MemoryBackend {
+    content: Arc<Mutex<Option<String>>>,
+}
+
+impl MemoryBackend {
+    pub fn new(content: Option<&str>) -> Self {
+        Self {
+            content: Arc::new(Mutex::new(content.map(str::to_string))),
+        }
+    }
+
+    /// A backend sharing one process wide demo crontab, so the content
+    /// survives switching between screens.
+    pub fn shared() -> Self {
+        Self {
+            content: DEMO_BACKEND.content.clone(),
+        }
+    }
+}
+
+impl CrontabBackend for MemoryBackend {
+    fn read(&self) -> io::Result<Option<String>> {
+        Ok(self.content.lock().unwrap().clone())
+    }
+
+    fn write(&self, content: &str) -> io::Result<()> {
+        *self.content.lock().unwrap() = Some(content.to_string());
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        "in-memory crontab".to_string()
+    }
+}
+
+/// Picks the backend from the `TUIXEL_CRON_BACKEND` environment variable:
+/// `command` (default), `spool`, `spool:<directory>` or `memory`.
+/// `TUIXEL_CRON_USER` selects whose crontab is managed.
+pub fn backend_from_env() -> Box<dyn CrontabBackend> {
+    let user = std::env::var(USER_ENV.clone()).ok();
+
+    match std::env::var(BACKEND_ENV.clone()).as_deref() {
+        Ok("memory") => Box::new(MemoryBackend::shared()),
+        Ok("spool") => Box::new(SpoolBackend::new(Path::new(SPOOL_DIR), &cron_user())),
+        Ok(value) if value.starts_with("spool:") => Box::new(SpoolBackend::new(
+            Path::new(value.trim_start_matches("spool:")),
+            &cron_user(),
+        )),
+        _ => Box::new(CommandBackend::new(user)),
+    }
+}
+
+/// The user whose crontab is managed: `TUIXEL_CRON_USER`, or the current user.
+pub fn cron_user() -> String {
+    std::env::var(USER_ENV.clone())
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "root".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn spool_write_creates_a_private_file_of_the_user() {
+        let image = std::env::temp_dir().join(format!("tuixel-spool-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&image);
+        let spool_dir = image.join("var/spool/cron/crontabs");
+        fs::create_dir_all(&spool_dir).unwrap();
+        fs::create_dir_all(image.join("etc")).unwrap();
+        // The image lists the current user, so chown works without root.
+        let owner = fs::metadata(&spool_dir).unwrap();
+        fs::write(
+            image.join("etc/passwd"),
+            format!(
+                "alice:x:{}:{}::/home/alice:/bin/sh\n",
+                owner.uid(),
+                owner.gid()
+            ),
+        )
+        .unwrap();
+        fs::write(
+            image.join("etc/group"),
+            format!("crontab:x:{}:\n", owner.gid()),
+        )
+        .unwrap();
+        let backend = SpoolBackend::new(&spool_dir, "alice");
+
+        backend.write("0 3 * * * true\n").unwrap();
+        let metadata = fs::metadata(spool_dir.join("alice")).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!((metadata.uid(), metadata.gid()), (owner.uid(), owner.gid()));
+        assert_eq!(backend.read().unwrap().as_deref(), Some("0 3 * * * true\n"));
+
+        let missing = SpoolBackend::new(&spool_dir, "tuixel.no-such-user");
+        assert!(missing.write("0 3 * * * true\n").is_err());
+        let names: Vec<_> = fs::read_dir(&spool_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["alice"]);
+        fs::remove_dir_all(image).unwrap();
+    }
+
+    #[test]
+    fn system_etc_follows_the_spool_directory() {
+        assert_eq!(
+            system_etc(Path::new("/var/spool/cron/crontabs")),
+            Some(PathBuf::from("/etc"))
+        );
+        assert_eq!(
+            system_etc(Path::new("/mnt/image/var/spool/cron")),
+            Some(PathBuf::from("/mnt/image/etc"))
+        );
+        assert_eq!(system_etc(Path::new("/srv/crontabs")), None);
+    }
+}
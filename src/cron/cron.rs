@@ -1,45 +1,71 @@
 use crate::app::{Screen, ScreenTrait};
-use crate::cron::utils::{from_crontab, save_to_crontab};
-use crate::cron::{Inputs, TableStyles};
+use crate::config::CONFIG;
+use crate::cron::backend::backend_from_env;
+use crate::cron::diagnostics::DiagnosticsPopup;
+use crate::cron::history::{format_duration, load_history, new_history_id, sparkline, RunRecord};
+use crate::cron::ical::{export_ics, export_weeks, MAX_RUNS_PER_JOB};
+use crate::cron::templates::{save_user_template, TemplateAction, TemplatePicker};
+use crate::cron::utils::{from_crontab, remove_job, save_to_crontab};
+use crate::cron::{
+    AnacronTable, AtQueue, CronAccess, CronQuery, CronRaw, CrontabBackend, EditWindowStyles,
+    Inputs, TableStyles,
+};
 use crate::menu::MainMenu;
 use ratatui::{
     crossterm::event::{self, KeyCode, MouseEvent},
     layout::{Constraint, Layout, Margin, Rect},
-    prelude::{Buffer, StatefulWidget},
-    text::Text,
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    text::{Line, Span, Text},
     widgets::{
-        Cell, HighlightSpacing, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table,
-        TableState,
+        Block, Borders, Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState,
     },
 };
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tui_textarea::{CursorMove, TextArea};
 use unicode_width::UnicodeWidthStr;
 
 const ITEM_HEIGHT: usize = 4;
+const GROUP_HEIGHT: u16 = 2;
+const UNTAGGED_GROUP: &str = "untagged";
+const RUNS_COLUMN_WIDTH: u16 = 24;
+const SPARKLINE_RUNS: usize = 10;
+const HISTORY_HEIGHT: u16 = 12;
 
+#[derive(Default)]
 pub struct CronJob {
     pub cron_notation: String,
     pub job: String,
     pub job_description: String,
     pub next_execution: String,
-}
-
-impl Default for CronJob {
-    fn default() -> Self {
-        Self {
-            cron_notation: String::new(),
-            job: String::new(),
-            job_description: String::new(),
-            next_execution: String::new(),
-        }
-    }
+    pub tags: Vec<String>,
+    pub owner: String,
+    pub disabled: bool,
+    /// Set when the job was disabled by maintenance mode rather than by hand.
+    pub suspended: bool,
+    /// Id of the run history when the job runs through the recording wrapper.
+    pub history: Option<String>,
+    /// Crontab lines written back verbatim ahead of the job, such as
+    /// environment settings and `@daily` style schedules.
+    pub verbatim: Vec<String>,
 }
 
 impl CronJob {
-    const fn ref_array(&self) -> [&String; 3] {
+    fn to_row_array(&self) -> [String; 5] {
+        let next_execution = if self.suspended {
+            "Suspended (maintenance)".to_string()
+        } else if self.disabled {
+            "Disabled".to_string()
+        } else {
+            self.next_execution.clone()
+        };
         [
-            &self.cron_notation,
-            &self.next_execution,
-            &self.job_description,
+            self.cron_notation.clone(),
+            next_execution,
+            self.job_description.clone(),
+            self.tags.join(", "),
+            self.owner.clone(),
         ]
     }
 
@@ -49,29 +75,55 @@ impl CronJob {
             job: cron_job.job,
             job_description: cron_job.job_description,
             next_execution: cron_job.next_execution,
+            tags: cron_job.tags,
+            owner: cron_job.owner,
+            disabled: cron_job.disabled,
+            suspended: cron_job.suspended,
+            history: cron_job.history,
+            verbatim: cron_job.verbatim,
         }
     }
 }
 
+/// A visible row of the table: either a collapsible tag section header or a job.
+enum TableRow {
+    Group { tag: String, count: usize },
+    Job(usize),
+}
+
 pub struct CronTable {
     state: TableState,
     items: Vec<CronJob>,
-    longest_item_lens: (u16, u16, u16),
+    rows: Vec<TableRow>,
+    longest_item_lens: (u16, u16, u16, u16, u16),
     scroll_state: ScrollbarState,
     styles: TableStyles,
     show_popup: bool,
     inputs: Inputs,
+    group_by_tag: bool,
+    collapsed_groups: HashSet<String>,
+    owner_filter: Option<String>,
+    backend: Box<dyn CrontabBackend>,
+    templates: Option<TemplatePicker>,
+    diagnostics: Option<DiagnosticsPopup>,
+    histories: HashMap<String, Vec<RunRecord>>,
+    show_history: bool,
+    marked: HashSet<usize>,
+    /// The number of weeks asked for before exporting the calendar.
+    weeks_input: Option<TextArea<'static>>,
+    weeks_error: Option<String>,
+    input_styles: EditWindowStyles,
+    message: Option<String>,
 }
 
 impl ScreenTrait for CronTable {
     fn new() -> Self {
-        let cron_jobs_vec = from_crontab().unwrap_or_else(|err| {
+        let backend = backend_from_env();
+        let cron_jobs_vec = from_crontab(backend.as_ref()).unwrap_or_else(|err| {
             tracing::error!("Error reading crontab: {}", err);
             vec![CronJob {
                 cron_notation: format!("Error: {}", err),
-                job: String::new(),
-                job_description: String::new(),
-                next_execution: String::new(),
+                ..Default::default()
             }]
         });
         let scroll_position = if cron_jobs_vec.is_empty() {
@@ -79,37 +131,94 @@ impl ScreenTrait for CronTable {
         } else {
             (cron_jobs_vec.len() - 1) * ITEM_HEIGHT
         };
-        Self {
+        let mut cron_table = Self {
             state: TableState::default().with_selected(0),
             longest_item_lens: constraint_len_calculator(&cron_jobs_vec),
             scroll_state: ScrollbarState::new(scroll_position),
             styles: TableStyles::new(),
             items: cron_jobs_vec,
+            rows: Vec::new(),
             show_popup: false,
             inputs: Inputs::default(),
-        }
+            group_by_tag: false,
+            collapsed_groups: HashSet::new(),
+            owner_filter: None,
+            backend,
+            templates: None,
+            diagnostics: None,
+            histories: HashMap::new(),
+            show_history: false,
+            marked: HashSet::new(),
+            weeks_input: None,
+            weeks_error: None,
+            input_styles: EditWindowStyles::new(),
+            message: None,
+        };
+        cron_table.refresh_rows();
+        cron_table.load_histories();
+        cron_table
     }
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let vertical = Layout::vertical([Constraint::Min(1), Constraint::Length(2)]);
+        let vertical = Layout::vertical([Constraint::Min(1), Constraint::Length(3)]);
         let rects = vertical.split(area);
+        let history_height = if self.show_history { HISTORY_HEIGHT } else { 0 };
+        let weeks_height = if self.weeks_input.is_some() { 3 } else { 0 };
+        let message_height = if self.message.is_some() { 1 } else { 0 };
+        let [table_area, history_area, weeks_area, message_area] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(history_height),
+            Constraint::Length(weeks_height),
+            Constraint::Length(message_height),
+        ])
+        .areas(rects[0]);
 
-        self.render_table(rects[0], buf);
-        self.render_scrollbar(rects[0], buf);
+        self.render_table(table_area, buf);
+        self.render_scrollbar(table_area, buf);
+        if self.show_history {
+            self.render_history(history_area, buf);
+        }
+        self.render_weeks_input(weeks_area, buf);
+        if let Some(message) = &self.message {
+            let message = Paragraph::new(message.as_str())
+                .style(self.styles.message_style)
+                .centered();
+            Widget::render(message, message_area, buf);
+        }
         self.render_footer(
             rects[1],
             buf,
             vec![
                 ("<Esc>", "Return to the main menu"),
-                ("<Enter>", "Edit selected cron"),
+                ("<Enter>", "Edit selected cron / toggle section"),
                 ("<↓↑>", "Move up and down"),
                 ("<d>", "Delete selected cron"),
                 ("<n>", "Add new cron"),
+                ("<t>", "New cron from template"),
+                ("<c>", "Clone selected cron"),
+                ("<T>", "Save selected cron as template"),
+                ("<s>", "Group by tag"),
+                ("<o>", "Filter by owner"),
+                ("<m>", "Toggle maintenance mode"),
+                ("<a>", "Anacron and periodic scripts"),
+                ("<b>", "One-off at/batch jobs"),
+                ("<q>", "Query runs in a time window"),
+                ("<r>", "Edit raw crontab text"),
+                ("<D>", "Diagnose selected cron"),
+                ("<p>", "Edit cron.allow / cron.deny"),
+                ("<R>", "Record run history of selected cron"),
+                ("<h>", "Show run history"),
+                ("<Space>", "Mark cron"),
+                ("<i>", "Export marked crons as iCalendar"),
             ],
         );
 
         if self.show_popup {
             self.inputs.render_inputs(rects[0], buf);
+        } else if let Some(templates) = &mut self.templates {
+            templates.render(rects[0], buf);
+        } else if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.render(rects[0], buf);
         }
     }
 
@@ -118,8 +227,26 @@ impl ScreenTrait for CronTable {
         key: event::KeyEvent,
         _mouse: Option<MouseEvent>,
     ) -> Option<Screen> {
-        if key.code == KeyCode::Esc && self.show_popup == false {
+        let popup_open = self.show_popup
+            || self.templates.is_some()
+            || self.diagnostics.is_some()
+            || self.weeks_input.is_some();
+        if key.code == KeyCode::Esc && !popup_open {
             Some(Screen::MainMenu(MainMenu::new()))
+        } else if key.code == KeyCode::Char('a') && !popup_open {
+            Some(Screen::AnacronTable(AnacronTable::new()))
+        } else if key.code == KeyCode::Char('b') && !popup_open {
+            Some(Screen::AtQueue(AtQueue::new()))
+        } else if key.code == KeyCode::Char('q') && !popup_open {
+            Some(Screen::CronQuery(CronQuery::new()))
+        } else if key.code == KeyCode::Char('p')
+            && !self.show_popup
+            && self.templates.is_none()
+            && self.weeks_input.is_none()
+        {
+            Some(Screen::CronAccess(CronAccess::new()))
+        } else if key.code == KeyCode::Char('r') && !popup_open {
+            Some(Screen::CronRaw(CronRaw::new()))
         } else {
             self.handle_keys(key);
             None
@@ -129,9 +256,48 @@ impl ScreenTrait for CronTable {
 
 impl CronTable {
     fn handle_keys(&mut self, key: event::KeyEvent) {
-        if self.show_popup == true {
-            self.inputs
-                .handle_inputs(key, &mut self.show_popup, &mut self.items, &mut self.state);
+        if self.show_popup {
+            let mut selected_job = self.selected_job();
+            self.inputs.handle_inputs(
+                key,
+                &mut self.show_popup,
+                &mut self.items,
+                &mut selected_job,
+                self.backend.as_ref(),
+            );
+            if !self.show_popup {
+                self.refresh_rows();
+                if let Some(index) = selected_job {
+                    self.select_job(index);
+                }
+            }
+        } else if let Some(templates) = &mut self.templates {
+            match templates.handle_key(key) {
+                TemplateAction::None => {}
+                TemplateAction::Cancel => self.templates = None,
+                TemplateAction::Use(cron_job) => {
+                    self.templates = None;
+                    self.show_popup = true;
+                    self.inputs.init_prefilled(&cron_job);
+                }
+            }
+        } else if self.diagnostics.is_some() {
+            if key.code == KeyCode::Esc {
+                self.diagnostics = None;
+            }
+        } else if let Some(weeks_input) = &mut self.weeks_input {
+            match key.code {
+                KeyCode::Enter => self.apply_weeks_input(),
+                KeyCode::Esc => {
+                    self.weeks_input = None;
+                    self.weeks_error = None;
+                }
+                _ => {
+                    if weeks_input.input(key) {
+                        self.weeks_error = None;
+                    }
+                }
+            }
         } else {
             match key.code {
                 KeyCode::Char('j') | KeyCode::Down => {
@@ -150,29 +316,402 @@ impl CronTable {
                     self.show_popup = true;
                     self.inputs.init_empty();
                 }
+                KeyCode::Char('t') => {
+                    self.templates = Some(TemplatePicker::new());
+                }
+                KeyCode::Char('c') => {
+                    if let Some(index) = self.selected_job() {
+                        self.show_popup = true;
+                        self.inputs.init_prefilled(&self.items[index]);
+                    }
+                }
+                KeyCode::Char('D') => {
+                    if let Some(index) = self.selected_job() {
+                        self.diagnostics = Some(DiagnosticsPopup::new(
+                            &self.items[index],
+                            self.backend.as_ref(),
+                        ));
+                    }
+                }
+                KeyCode::Char('R') => {
+                    self.toggle_recording();
+                }
+                KeyCode::Char('h') => {
+                    self.show_history = !self.show_history;
+                    self.load_histories();
+                }
+                KeyCode::Char('T') => {
+                    if let Some(index) = self.selected_job() {
+                        self.message = Some(match save_user_template(&self.items[index]) {
+                            Ok(_) => "Saved as template".to_string(),
+                            Err(err) => format!("Error saving cron template: {}", err),
+                        });
+                    }
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(index) = self.selected_job() {
+                        if !self.marked.remove(&index) {
+                            self.marked.insert(index);
+                        }
+                        self.next_row();
+                    }
+                }
+                KeyCode::Char('i') if !self.export_indexes().is_empty() => {
+                    let mut weeks_input = TextArea::from([export_weeks().to_string()]);
+                    weeks_input.set_cursor_line_style(Style::default());
+                    weeks_input.set_cursor_style(self.input_styles.cursor_style);
+                    weeks_input.move_cursor(CursorMove::End);
+                    self.weeks_input = Some(weeks_input);
+                }
                 KeyCode::Char('d') => {
-                    let index = self.state.selected().unwrap();
-                    self.items.remove(index);
-                    save_to_crontab(&self.items).unwrap_or_else(|err| {
-                        eprint!("Error saving to crontab: {}", err);
-                    });
+                    if let Some(index) = self.selected_job() {
+                        self.marked.clear();
+                        remove_job(&mut self.items, index);
+                        save_to_crontab(self.backend.as_ref(), &self.items).unwrap_or_else(|err| {
+                            eprint!("Error saving to crontab: {}", err);
+                        });
+                        self.refresh_rows();
+                    }
+                }
+                KeyCode::Char('s') => {
+                    self.group_by_tag = !self.group_by_tag;
+                    self.refresh_rows();
+                    self.first_row();
+                }
+                KeyCode::Char('m') => {
+                    self.toggle_maintenance();
                 }
-                KeyCode::Enter => {
-                    if !self.items.is_empty() {
+                KeyCode::Char('o') => {
+                    self.owner_filter = self.next_owner_filter();
+                    self.refresh_rows();
+                    self.first_row();
+                }
+                KeyCode::Enter => match self.state.selected().and_then(|i| self.rows.get(i)) {
+                    Some(TableRow::Group { tag, .. }) => {
+                        let tag = tag.clone();
+                        if !self.collapsed_groups.remove(&tag) {
+                            self.collapsed_groups.insert(tag);
+                        }
+                        self.refresh_rows();
+                    }
+                    Some(TableRow::Job(index)) => {
+                        let index = *index;
                         self.show_popup = true;
                         self.inputs.is_new = false;
-                        self.inputs.init(&mut self.items, &mut self.state);
+                        self.inputs.init(&self.items, index);
                     }
-                }
+                    None => {}
+                },
                 _ => {}
             }
         }
     }
 
+    /// Returns the index into `items` of the job under the cursor, if the
+    /// cursor is on a job row.
+    fn selected_job(&self) -> Option<usize> {
+        match self.state.selected().and_then(|i| self.rows.get(i)) {
+            Some(TableRow::Job(index)) => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn select_job(&mut self, index: usize) {
+        if let Some(position) = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, TableRow::Job(i) if *i == index))
+        {
+            self.state.select(Some(position));
+            self.scroll_state = self.scroll_state.position(position * ITEM_HEIGHT);
+        }
+    }
+
+    /// Rebuilds the visible rows from the jobs, applying the owner filter and
+    /// the tag sections.
+    fn refresh_rows(&mut self) {
+        let visible: Vec<usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| !job.job.is_empty() || !job.cron_notation.is_empty())
+            .filter(|(_, job)| match &self.owner_filter {
+                Some(owner) => &job.owner == owner,
+                None => true,
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        self.rows = if self.group_by_tag {
+            let tags: BTreeSet<&str> = visible
+                .iter()
+                .flat_map(|&index| self.items[index].tags.iter().map(String::as_str))
+                .collect();
+
+            let mut rows = Vec::new();
+            let groups = tags.into_iter().map(Some).chain([None]);
+            for tag in groups {
+                let members: Vec<usize> = visible
+                    .iter()
+                    .copied()
+                    .filter(|&index| match tag {
+                        Some(tag) => self.items[index].tags.iter().any(|t| t == tag),
+                        None => self.items[index].tags.is_empty(),
+                    })
+                    .collect();
+                if members.is_empty() {
+                    continue;
+                }
+                let tag = tag.unwrap_or(UNTAGGED_GROUP).to_string();
+                let collapsed = self.collapsed_groups.contains(&tag);
+                rows.push(TableRow::Group {
+                    tag,
+                    count: members.len(),
+                });
+                if !collapsed {
+                    rows.extend(members.into_iter().map(TableRow::Job));
+                }
+            }
+            rows
+        } else {
+            visible.into_iter().map(TableRow::Job).collect()
+        };
+
+        self.longest_item_lens = constraint_len_calculator(&self.items);
+        self.scroll_state = self
+            .scroll_state
+            .content_length(self.rows.len().saturating_sub(1) * ITEM_HEIGHT);
+        if self.state.selected().is_none_or(|i| i >= self.rows.len()) {
+            self.state
+                .select(if self.rows.is_empty() { None } else { Some(0) });
+        }
+    }
+
+    /// Number of jobs currently suspended by maintenance mode.
+    fn suspended_count(&self) -> usize {
+        self.items.iter().filter(|job| job.suspended).count()
+    }
+
+    /// Enters maintenance mode by disabling every active job, or leaves it by
+    /// re-enabling exactly the jobs maintenance mode disabled.
+    fn toggle_maintenance(&mut self) {
+        let leaving = self.suspended_count() > 0;
+        for job in self.items.iter_mut().filter(|job| !job.job.is_empty()) {
+            if leaving && job.suspended {
+                job.disabled = false;
+                job.suspended = false;
+            } else if !leaving && !job.disabled {
+                job.disabled = true;
+                job.suspended = true;
+            }
+        }
+        save_to_crontab(self.backend.as_ref(), &self.items).unwrap_or_else(|err| {
+            eprint!("Error saving to crontab: {}", err);
+        });
+    }
+
+    /// The marked jobs, or the selected job if none is marked.
+    fn export_indexes(&self) -> Vec<usize> {
+        let mut indexes: Vec<usize> = self.marked.iter().copied().collect();
+        if indexes.is_empty() {
+            indexes.extend(self.selected_job());
+        }
+        indexes.sort();
+        indexes
+    }
+
+    /// Exports the calendar for the number of weeks typed into the prompt.
+    fn apply_weeks_input(&mut self) {
+        let Some(weeks_input) = &self.weeks_input else {
+            return;
+        };
+        match weeks_input.lines().join("").trim().parse::<i64>() {
+            Ok(weeks) if weeks > 0 => {
+                self.weeks_input = None;
+                self.weeks_error = None;
+                self.export_calendar(weeks);
+            }
+            _ => self.weeks_error = Some("a positive number of weeks".to_string()),
+        }
+    }
+
+    /// Exports the runs of the next `weeks` weeks as an iCalendar file.
+    fn export_calendar(&mut self, weeks: i64) {
+        let indexes = self.export_indexes();
+        let jobs: Vec<&CronJob> = indexes.iter().map(|&index| &self.items[index]).collect();
+        if jobs.is_empty() {
+            return;
+        }
+
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        self.message = Some(match export_ics(&jobs, &timezone, weeks) {
+            Ok((path, 0)) => format!("Exported {} jobs to {}", jobs.len(), path.display()),
+            Ok((path, truncated)) => format!(
+                "Exported {} jobs to {}, {} of them cut at {} runs",
+                jobs.len(),
+                path.display(),
+                truncated,
+                MAX_RUNS_PER_JOB
+            ),
+            Err(err) => format!("Export failed: {}", err),
+        });
+    }
+
+    fn render_weeks_input(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(weeks_input) = &mut self.weeks_input else {
+            return;
+        };
+        let (title, border_style) = match &self.weeks_error {
+            Some(error) => (
+                format!("Weeks to export ({})", error),
+                self.input_styles.invalid_input_style,
+            ),
+            None => (
+                "Weeks to export (Enter) Export | (Esc) Cancel".to_string(),
+                self.input_styles.selected_input_border_style,
+            ),
+        };
+        weeks_input.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(title),
+        );
+        weeks_input.render(area, buf);
+    }
+
+    /// Wraps the selected job in the recording wrapper, or unwraps it.
+    fn toggle_recording(&mut self) {
+        let Some(index) = self.selected_job() else {
+            return;
+        };
+        let job = &mut self.items[index];
+        job.history = match job.history {
+            Some(_) => None,
+            None => Some(new_history_id()),
+        };
+        save_to_crontab(self.backend.as_ref(), &self.items).unwrap_or_else(|err| {
+            eprint!("Error saving to crontab: {}", err);
+        });
+        self.load_histories();
+    }
+
+    fn load_histories(&mut self) {
+        self.histories = self
+            .items
+            .iter()
+            .filter_map(|job| job.history.as_ref())
+            .map(|id| {
+                let runs = load_history(id).unwrap_or_else(|err| {
+                    tracing::error!("Error reading run history {}: {}", id, err);
+                    Vec::new()
+                });
+                (id.clone(), runs)
+            })
+            .collect();
+    }
+
+    /// The last run indicator and the duration sparkline of a job.
+    fn runs_summary(&self, job: &CronJob) -> Line<'static> {
+        let Some(id) = &job.history else {
+            return Line::from("");
+        };
+        let runs = self.histories.get(id).map(Vec::as_slice).unwrap_or(&[]);
+        let Some(last) = runs.last() else {
+            return Line::from("Recording, no runs yet");
+        };
+
+        let indicator = if last.succeeded() {
+            Span::styled("✔", self.styles.success_style)
+        } else {
+            Span::styled(format!("✘ {}", last.exit_code), self.styles.failure_style)
+        };
+        let recent = &runs[runs.len().saturating_sub(SPARKLINE_RUNS)..];
+        Line::from(vec![
+            indicator,
+            Span::raw(format!(" {} ", format_duration(last.duration_seconds()))),
+            Span::styled(sparkline(recent), self.styles.sparkline_style),
+        ])
+    }
+
+    /// Lists the recorded runs of the selected job, newest first, with their output.
+    fn render_history(&self, area: Rect, buf: &mut Buffer) {
+        let job = self.selected_job().map(|index| &self.items[index]);
+        let title = match job {
+            Some(job) if !job.job_description.is_empty() => {
+                format!(" Run history: {} ", job.job_description)
+            }
+            Some(job) => format!(" Run history: {} ", job.job),
+            None => " Run history ".to_string(),
+        };
+
+        let lines: Vec<Line> = match job.and_then(|job| job.history.as_ref()) {
+            None => vec![Line::from(
+                "Runs of this job are not recorded, press R to record them",
+            )],
+            Some(id) => {
+                let timezone = CONFIG.read().unwrap().get_timezone();
+                let runs = self.histories.get(id).map(Vec::as_slice).unwrap_or(&[]);
+                runs.iter()
+                    .rev()
+                    .flat_map(|run| {
+                        let status = if run.succeeded() {
+                            Span::styled("✔ exit 0", self.styles.success_style)
+                        } else {
+                            Span::styled(
+                                format!("✘ exit {}", run.exit_code),
+                                self.styles.failure_style,
+                            )
+                        };
+                        let header = Line::from(vec![
+                            Span::raw(format!(
+                                "{}  ",
+                                run.start
+                                    .with_timezone(&timezone)
+                                    .format("%Y-%m-%d %H:%M:%S")
+                            )),
+                            status,
+                            Span::raw(format!("  {}", format_duration(run.duration_seconds()))),
+                        ]);
+                        std::iter::once(header).chain(run.output.iter().map(|line| {
+                            Line::styled(format!("    {}", line), self.styles.disabled_row_style)
+                        }))
+                    })
+                    .collect()
+            }
+        };
+
+        let history = Paragraph::new(Text::from(lines))
+            .style(self.styles.message_style)
+            .block(Block::default().borders(Borders::TOP).title(title));
+        Widget::render(history, area, buf);
+    }
+
+    /// Cycles the owner filter through every owner found in the jobs.
+    fn next_owner_filter(&self) -> Option<String> {
+        let owners: BTreeSet<&str> = self
+            .items
+            .iter()
+            .map(|job| job.owner.as_str())
+            .filter(|owner| !owner.is_empty())
+            .collect();
+
+        match &self.owner_filter {
+            None => owners.into_iter().next().map(str::to_string),
+            Some(current) => owners
+                .into_iter()
+                .find(|owner| *owner > current.as_str())
+                .map(str::to_string),
+        }
+    }
+
     fn next_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.items.len() - 1 {
+                if i >= self.rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -185,10 +724,13 @@ impl CronTable {
     }
 
     fn previous_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - 1
+                    self.rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -205,31 +747,88 @@ impl CronTable {
     }
 
     fn last_row(&mut self) {
-        if !self.items.is_empty() {
-            let last_index = self.items.len() - 1;
+        if !self.rows.is_empty() {
+            let last_index = self.rows.len() - 1;
             self.state.select(Some(last_index));
             self.scroll_state = self.scroll_state.position(last_index * ITEM_HEIGHT);
         }
     }
 
+    fn header_titles(&self) -> [String; 6] {
+        let owner_title = match &self.owner_filter {
+            Some(owner) => format!("Owner = {}", owner),
+            None => "Owner".to_string(),
+        };
+        let (notation_banner, execution_banner) = match self.suspended_count() {
+            0 => (String::new(), String::new()),
+            count => (
+                "⚠ MAINTENANCE".to_string(),
+                format!("{} jobs suspended", count),
+            ),
+        };
+        [
+            format!("{}\nCron Notation\n", notation_banner),
+            format!("{}\nNext Execution\n", execution_banner),
+            format!("{}\nDescription\n", self.backend.describe()),
+            "\nTags\n".to_string(),
+            format!("\n{}\n", owner_title),
+            "\nLast Runs\n".to_string(),
+        ]
+    }
+
     fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
-        let header = ["Cron Notation", "Next Execution", "Description"]
+        let header_style = if self.suspended_count() > 0 {
+            self.styles.maintenance_header_style
+        } else {
+            self.styles.header_style
+        };
+        let header = self
+            .header_titles()
             .into_iter()
-            .map(|title| Cell::from(Text::from(format!("\n{}\n", title)))) // Adds top and bottom padding
+            .map(|title| Cell::from(Text::from(title)))
             .collect::<Row>()
-            .style(self.styles.header_style)
+            .style(header_style)
             .height(3);
-        let rows = self.items.iter().enumerate().map(|(i, data)| {
+        let rows = self.rows.iter().enumerate().map(|(i, row)| {
             let color = match i % 2 {
                 0 => self.styles.normal_row_color,
                 _ => self.styles.alt_row_color,
             };
-            let item = data.ref_array();
-            item.into_iter()
-                .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
-                .collect::<Row>()
-                .style(self.styles.row_style.bg(color))
-                .height(ITEM_HEIGHT.try_into().unwrap())
+            match row {
+                TableRow::Group { tag, count } => {
+                    let marker = if self.collapsed_groups.contains(tag) {
+                        "▶"
+                    } else {
+                        "▼"
+                    };
+                    Row::new([Cell::from(Text::from(format!(
+                        "\n{} {} ({})",
+                        marker, tag, count
+                    )))])
+                    .style(self.styles.group_row_style)
+                    .height(GROUP_HEIGHT)
+                }
+                TableRow::Job(index) => {
+                    let job = &self.items[*index];
+                    let style = if job.disabled {
+                        self.styles.disabled_row_style.bg(color)
+                    } else {
+                        self.styles.row_style.bg(color)
+                    };
+                    let runs = Text::from(vec![Line::from(""), self.runs_summary(job)]);
+                    let mut cells = job.to_row_array();
+                    if self.marked.contains(index) {
+                        cells[0] = format!("● {}", cells[0]);
+                    }
+                    cells
+                        .into_iter()
+                        .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
+                        .chain([Cell::from(runs)])
+                        .collect::<Row>()
+                        .style(style)
+                        .height(ITEM_HEIGHT.try_into().unwrap())
+                }
+            }
         });
         let bar = " ▌ ";
         let t = Table::new(
@@ -239,6 +838,9 @@ impl CronTable {
                 Constraint::Length(self.longest_item_lens.0 + 8),
                 Constraint::Min(self.longest_item_lens.1 + 1),
                 Constraint::Min(self.longest_item_lens.2),
+                Constraint::Min(self.longest_item_lens.3 + 1),
+                Constraint::Min(self.longest_item_lens.4 + 1),
+                Constraint::Length(RUNS_COLUMN_WIDTH),
             ],
         )
         .header(header)
@@ -246,7 +848,7 @@ impl CronTable {
         .style(
             self.styles
                 .row_style
-                .bg(if (self.items.len() + 1) % 2 == 0 {
+                .bg(if (self.rows.len() + 1).is_multiple_of(2) {
                     self.styles.alt_row_color
                 } else {
                     self.styles.normal_row_color
@@ -281,7 +883,7 @@ impl CronTable {
     }
 }
 
-fn constraint_len_calculator(items: &[CronJob]) -> (u16, u16, u16) {
+fn constraint_len_calculator(items: &[CronJob]) -> (u16, u16, u16, u16, u16) {
     let cron_notation_len = items
         .iter()
         .map(|job| job.cron_notation.as_str())
@@ -303,10 +905,26 @@ fn constraint_len_calculator(items: &[CronJob]) -> (u16, u16, u16) {
         .max()
         .unwrap_or(0);
 
+    let tags_len = items
+        .iter()
+        .map(|job| job.tags.join(", "))
+        .map(|tags| UnicodeWidthStr::width(tags.as_str()))
+        .max()
+        .unwrap_or(0);
+
+    let owner_len = items
+        .iter()
+        .map(|job| job.owner.as_str())
+        .map(UnicodeWidthStr::width)
+        .max()
+        .unwrap_or(0);
+
     #[allow(clippy::cast_possible_truncation)]
     (
         cron_notation_len as u16,
         next_execution_len as u16,
         job_description_len as u16,
+        tags_len as u16,
+        owner_len as u16,
     )
 }
@@ -0,0 +1,382 @@
+use crate::cron::backend::cron_user;
+use crate::cron::syntax::validate_cron_notation;
+use crate::cron::utils::expand_cron_macro;
+use crate::cron::{CronJob, CrontabBackend, EditWindowStyles};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+pub const CRON_ALLOW: &str = "/etc/cron.allow";
+pub const CRON_DENY: &str = "/etc/cron.deny";
+const PASSWD: &str = "/etc/passwd";
+const GROUP: &str = "/etc/group";
+/// The `PATH` cron uses unless the crontab sets its own.
+const DEFAULT_CRON_PATH: &str = "/usr/bin:/bin";
+const CRON_DAEMONS: [&str; 4] = ["cron", "crond", "cronie", "fcron"];
+const SHELL_BUILTINS: [&str; 14] = [
+    "cd", "echo", "test", "[", "true", "false", "exit", "export", "source", ".", "exec", "eval",
+    "set", "printf",
+];
+
+const INFO_TEXT: &str = "(Esc) Close | (p) Edit cron.allow / cron.deny";
+
+/// The outcome of a single diagnostic check.
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, passed: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Runs every check that could explain why `cron_job` does not run.
+pub fn run_diagnostics(cron_job: &CronJob, backend: &dyn CrontabBackend) -> Vec<Check> {
+    let user = cron_user();
+    let content = backend.read().unwrap_or_else(|err| {
+        tracing::error!("Error reading crontab: {}", err);
+        None
+    });
+    let path = cron_path(content.as_deref().unwrap_or(""));
+
+    let mut checks = vec![
+        check_enabled(cron_job),
+        check_schedule(&cron_job.cron_notation),
+        check_daemon(Path::new("/proc")),
+        check_permission(&user, Path::new(CRON_ALLOW), Path::new(CRON_DENY)),
+        check_trailing_newline(content.as_deref()),
+        check_percent_signs(&cron_job.job),
+    ];
+    checks.extend(check_command(&cron_job.job, &path, &user));
+    checks
+}
+
+fn check_enabled(cron_job: &CronJob) -> Check {
+    let name = "Job is enabled";
+    if cron_job.suspended {
+        Check::new(name, false, "Suspended by maintenance mode")
+    } else if cron_job.disabled {
+        Check::new(name, false, "The job line is commented out")
+    } else {
+        Check::new(name, true, "The job line is active")
+    }
+}
+
+fn check_schedule(cron_notation: &str) -> Check {
+    let name = "Schedule is valid";
+    let expanded = expand_cron_macro(cron_notation.trim());
+    match expanded {
+        None => Check::new(name, true, "@reboot: runs when the cron daemon starts"),
+        Some(expanded) => match validate_cron_notation(&expanded) {
+            Ok(_) => Check::new(name, true, cron_notation),
+            Err(err) => Check::new(name, false, err),
+        },
+    }
+}
+
+/// Looks for a cron daemon among the running processes.
+pub fn check_daemon(proc_dir: &Path) -> Check {
+    let name = "Cron daemon is running";
+    let entries = match fs::read_dir(proc_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            return Check::new(
+                name,
+                false,
+                format!("Cannot read {}: {}", proc_dir.display(), err),
+            )
+        }
+    };
+
+    for entry in entries.flatten() {
+        let pid = entry.file_name().to_string_lossy().to_string();
+        if !pid.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(comm) = fs::read_to_string(entry.path().join("comm")) {
+            let comm = comm.trim();
+            if CRON_DAEMONS.contains(&comm) {
+                return Check::new(name, true, format!("{} (pid {})", comm, pid));
+            }
+        }
+    }
+    Check::new(name, false, "No cron, crond or cronie process found")
+}
+
+/// Applies the `cron.allow` / `cron.deny` rules: if `cron.allow` exists only
+/// the users listed there may use cron, otherwise everyone not listed in
+/// `cron.deny`.
+pub fn check_permission(user: &str, allow: &Path, deny: &Path) -> Check {
+    let name = "User may use cron";
+    let listed = |path: &Path| {
+        fs::read_to_string(path)
+            .ok()
+            .map(|content| content.lines().map(str::trim).any(|line| line == user))
+    };
+
+    match (listed(allow), listed(deny)) {
+        (Some(true), _) => Check::new(
+            name,
+            true,
+            format!("{} is listed in {}", user, allow.display()),
+        ),
+        (Some(false), _) => Check::new(
+            name,
+            false,
+            format!("{} exists and does not list {}", allow.display(), user),
+        ),
+        (None, Some(true)) => Check::new(
+            name,
+            false,
+            format!("{} is listed in {}", user, deny.display()),
+        ),
+        (None, Some(false)) => Check::new(
+            name,
+            true,
+            format!("{} is not listed in {}", user, deny.display()),
+        ),
+        (None, None) => Check::new(
+            name,
+            true,
+            "Neither cron.allow nor cron.deny exists, most cron daemons allow every user",
+        ),
+    }
+}
+
+/// cron ignores a last line that does not end with a newline.
+pub fn check_trailing_newline(content: Option<&str>) -> Check {
+    let name = "Crontab ends with a newline";
+    match content {
+        None => Check::new(name, false, "The user has no crontab"),
+        Some(content) if content.is_empty() || content.ends_with('\n') => {
+            Check::new(name, true, "The last line is terminated")
+        }
+        Some(_) => Check::new(name, false, "cron ignores the last line"),
+    }
+}
+
+/// cron turns an unescaped `%` into a newline and feeds the rest to stdin.
+pub fn check_percent_signs(command: &str) -> Check {
+    let name = "No unescaped % in the command";
+    let unescaped = command
+        .char_indices()
+        .any(|(i, c)| c == '%' && !command[..i].ends_with('\\'));
+    if unescaped {
+        Check::new(name, false, "Escape % as \\% (e.g. in date +\\%F)")
+    } else {
+        Check::new(name, true, "")
+    }
+}
+
+/// Returns the `PATH` cron runs jobs with: the last `PATH=` line of the crontab
+/// or cron's default.
+pub fn cron_path(content: &str) -> String {
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| line.trim().strip_prefix("PATH"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('='))
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .next()
+        .unwrap_or_else(|| DEFAULT_CRON_PATH.to_string())
+}
+
+/// The program a command starts, skipping leading `NAME=value` assignments.
+pub fn command_program(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .find(|word| {
+            !word
+                .split_once('=')
+                .is_some_and(|(name, _)| !name.is_empty() && !name.contains('/'))
+        })
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\'').to_string())
+}
+
+/// Resolves the program of the command in `path` and checks that `user` may
+/// execute it.
+pub fn check_command(command: &str, path: &str, user: &str) -> Vec<Check> {
+    let name = "Command resolves in cron's PATH";
+    let Some(program) = command_program(command) else {
+        return vec![Check::new(name, false, "The job has no command")];
+    };
+
+    if SHELL_BUILTINS.contains(&program.as_str()) {
+        return vec![Check::new(
+            name,
+            true,
+            format!("{} is a shell builtin", program),
+        )];
+    }
+
+    let resolved = if program.contains('/') {
+        let candidate = PathBuf::from(&program);
+        if !candidate.is_absolute() {
+            return vec![Check::new(
+                name,
+                false,
+                format!("{} is relative, cron starts in the home directory", program),
+            )];
+        }
+        candidate.exists().then_some(candidate)
+    } else {
+        path.split(':')
+            .map(|directory| Path::new(directory).join(&program))
+            .find(|candidate| candidate.is_file())
+    };
+
+    let Some(resolved) = resolved else {
+        return vec![Check::new(
+            name,
+            false,
+            format!("{} not found in PATH={}", program, path),
+        )];
+    };
+
+    vec![
+        Check::new(name, true, resolved.display().to_string()),
+        check_executable(&resolved, user),
+    ]
+}
+
+/// Checks the permission bits of `path` against the run-as user's uid and groups.
+pub fn check_executable(path: &Path, user: &str) -> Check {
+    let name = "Executable by the run-as user";
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) => return Check::new(name, false, format!("{}: {}", path.display(), err)),
+    };
+    let Some((uid, groups)) = lookup_user(user) else {
+        return Check::new(name, false, format!("Unknown user {}", user));
+    };
+
+    let mode = metadata.permissions().mode();
+    let allowed = if uid == 0 {
+        mode & 0o111 != 0
+    } else if metadata.uid() == uid {
+        mode & 0o100 != 0
+    } else if groups.contains(&metadata.gid()) {
+        mode & 0o010 != 0
+    } else {
+        mode & 0o001 != 0
+    };
+
+    let detail = format!(
+        "{} is {:o} owned by {}:{}",
+        path.display(),
+        mode & 0o7777,
+        metadata.uid(),
+        metadata.gid()
+    );
+    Check::new(name, allowed, detail)
+}
+
+/// Returns the uid and all group ids of a user from `/etc/passwd` and `/etc/group`.
+fn lookup_user(user: &str) -> Option<(u32, Vec<u32>)> {
+    let passwd = fs::read_to_string(PASSWD).ok()?;
+    let (uid, gid) = passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.first() == Some(&user)).then_some(())?;
+        Some((
+            fields.get(2)?.parse::<u32>().ok()?,
+            fields.get(3)?.parse().ok()?,
+        ))
+    })?;
+
+    let mut groups = vec![gid];
+    if let Ok(group) = fs::read_to_string(GROUP) {
+        groups.extend(group.lines().filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            let is_member = fields.get(3)?.split(',').any(|member| member == user);
+            if is_member {
+                fields.get(2)?.parse::<u32>().ok()
+            } else {
+                None
+            }
+        }));
+    }
+    Some((uid, groups))
+}
+
+/// Popup listing the diagnostic checks of a job.
+pub struct DiagnosticsPopup {
+    styles: EditWindowStyles,
+    title: String,
+    checks: Vec<Check>,
+}
+
+impl DiagnosticsPopup {
+    pub fn new(cron_job: &CronJob, backend: &dyn CrontabBackend) -> Self {
+        let title = if cron_job.job_description.is_empty() {
+            cron_job.job.clone()
+        } else {
+            cron_job.job_description.clone()
+        };
+        Self {
+            styles: EditWindowStyles::new(),
+            title,
+            checks: run_diagnostics(cron_job, backend),
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let height = self.checks.len() as u16 * 2 + 5;
+        let vertical = Layout::vertical([Constraint::Max(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Widget::render(Clear, area, buf);
+
+        let failed = self.checks.iter().filter(|check| !check.passed).count();
+        let block = Block::default()
+            .title(format!(" Diagnostics: {} ", self.title))
+            .title_bottom(Line::from(INFO_TEXT).centered())
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+
+        let mut lines = vec![Line::styled(
+            match failed {
+                0 => "All checks passed".to_string(),
+                failed => format!("{} of {} checks failed", failed, self.checks.len()),
+            },
+            self.styles.title_style,
+        )];
+        for check in &self.checks {
+            let (mark, style) = if check.passed {
+                ("✔ PASS", self.styles.valid_input_style)
+            } else {
+                ("✘ FAIL", self.styles.invalid_input_style)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}  ", mark), style),
+                Span::raw(check.name),
+            ]));
+            if !check.detail.is_empty() {
+                lines.push(Line::styled(
+                    format!("        {}", check.detail),
+                    self.styles.unselected_input_border_style,
+                ));
+            }
+        }
+
+        let report = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .block(block);
+        Widget::render(report, area, buf);
+    }
+}
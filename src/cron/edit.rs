@@ -1,26 +1,29 @@
-use crate::cron::utils::{get_human_readable_cron, get_next_execution, save_to_crontab};
-use crate::cron::{CronJob, EditWindowStyles};
+use crate::cron::syntax::{analyze_cron_notation, normalize_cron_notation, validate_cron_notation};
+use crate::cron::utils::{
+    first_line, get_human_readable_cron, get_next_execution, parse_tags, save_to_crontab,
+};
+use crate::cron::{CronJob, CrontabBackend, EditWindowStyles};
 use arboard::Clipboard;
 use ratatui::{
     crossterm::event::{self, KeyCode},
     layout::{Constraint, Flex, Layout, Rect},
     prelude::{Buffer, Widget},
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, Paragraph, TableState},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 use tui_textarea::{CursorMove, TextArea};
 
-const INFO_TEXT: [&str; 3] = [
-    "",
-    "(Esc) Close without saving | (Tab) Move to the next field | (Enter) Save and close",
-    "",
-];
+const INFO_TEXT: &str = "(Esc) Close without saving | (Tab) Move to the next field | ";
+const SAVE_TEXT: &str = "(Enter) Save and close";
 
+#[derive(PartialEq)]
 pub enum ActiveInput {
     CronNotation,
     Job,
     JobDescription,
+    Tags,
+    Owner,
 }
 
 impl ActiveInput {
@@ -28,7 +31,9 @@ impl ActiveInput {
         match self {
             ActiveInput::CronNotation => ActiveInput::Job,
             ActiveInput::Job => ActiveInput::JobDescription,
-            ActiveInput::JobDescription => ActiveInput::CronNotation,
+            ActiveInput::JobDescription => ActiveInput::Tags,
+            ActiveInput::Tags => ActiveInput::Owner,
+            ActiveInput::Owner => ActiveInput::CronNotation,
         }
     }
 }
@@ -38,11 +43,16 @@ pub struct Inputs {
     pub cron_notation: TextArea<'static>,
     pub job: TextArea<'static>,
     pub job_description: TextArea<'static>,
+    pub tags: TextArea<'static>,
+    pub owner: TextArea<'static>,
     pub current_input: ActiveInput,
     pub cron_notation_value: String,
     pub job_value: String,
     pub job_description_value: String,
+    pub tags_value: String,
+    pub owner_value: String,
     pub is_new: bool,
+    editing: Option<usize>,
 }
 
 impl Default for Inputs {
@@ -52,22 +62,30 @@ impl Default for Inputs {
             cron_notation: TextArea::default(),
             job: TextArea::default(),
             job_description: TextArea::default(),
+            tags: TextArea::default(),
+            owner: TextArea::default(),
             current_input: ActiveInput::CronNotation,
             cron_notation_value: String::new(),
             job_value: String::new(),
             job_description_value: String::new(),
+            tags_value: String::new(),
+            owner_value: String::new(),
             is_new: true,
+            editing: None,
         }
     }
 }
 
 impl Inputs {
+    /// Handles a key press while the popup is open. When the job is saved,
+    /// `selected` is set to the index of the new or updated job.
     pub fn handle_inputs(
         &mut self,
         key: event::KeyEvent,
         show_popup: &mut bool,
         cron_jobs: &mut Vec<CronJob>,
-        table_state: &mut TableState,
+        selected: &mut Option<usize>,
+        backend: &dyn CrontabBackend,
     ) {
         let ctrl_pressed = key.modifiers.contains(event::KeyModifiers::CONTROL);
         match key.code {
@@ -80,15 +98,19 @@ impl Inputs {
                 self.flash_values();
                 self.current_input = ActiveInput::CronNotation;
             }
-            KeyCode::Enter => match validate(&mut self.cron_notation) {
+            KeyCode::Enter => match validate(first_line(&self.cron_notation)) {
                 Ok(_) => {
-                    if self.is_new {
-                        cron_jobs.push(self.create_new_cron());
-                        table_state.select(Some(cron_jobs.len() - 1));
-                    } else {
-                        self.update_selected_cron(&mut cron_jobs[table_state.selected().unwrap()]);
+                    match self.editing.filter(|_| !self.is_new) {
+                        Some(index) => {
+                            self.update_selected_cron(&mut cron_jobs[index]);
+                            *selected = Some(index);
+                        }
+                        None => {
+                            cron_jobs.push(self.create_new_cron());
+                            *selected = Some(cron_jobs.len() - 1);
+                        }
                     }
-                    save_to_crontab(cron_jobs).unwrap_or_else(|err| {
+                    save_to_crontab(backend, cron_jobs).unwrap_or_else(|err| {
                         eprint!("Error saving to crontab: {}", err);
                     });
 
@@ -99,125 +121,130 @@ impl Inputs {
             KeyCode::Char('v') if ctrl_pressed => {
                 self.handle_paste();
             }
-            _ => match self.current_input {
-                ActiveInput::CronNotation => {
-                    let cron_input = &mut self.cron_notation;
-                    let cron_value = &mut self.cron_notation_value;
-                    if cron_input.input(key) {
-                        cron_value.clear();
-                        if let Some(first_line) = cron_input.lines().get(0) {
-                            cron_value.push_str(first_line);
-                        }
-                    }
-                }
-                ActiveInput::Job => {
-                    let job_input = &mut self.job;
-                    let job_value = &mut self.job_value;
-                    if job_input.input(key) {
-                        job_value.clear();
-                        if let Some(first_line) = job_input.lines().get(0) {
-                            job_value.push_str(first_line);
-                        }
+            _ => {
+                let (input, value) = self.active_field();
+                if input.input(key) {
+                    value.clear();
+                    if let Some(first_line) = input.lines().first() {
+                        value.push_str(first_line);
                     }
                 }
-                ActiveInput::JobDescription => {
-                    let job_description_input = &mut self.job_description;
-                    let job_description_value = &mut self.job_description_value;
-                    if job_description_input.input(key) {
-                        job_description_value.clear();
-                        if let Some(first_line) = job_description_input.lines().get(0) {
-                            job_description_value.push_str(first_line);
-                        }
-                    }
-                }
-            },
+            }
+        }
+    }
+
+    fn active_field(&mut self) -> (&mut TextArea<'static>, &mut String) {
+        match self.current_input {
+            ActiveInput::CronNotation => (&mut self.cron_notation, &mut self.cron_notation_value),
+            ActiveInput::Job => (&mut self.job, &mut self.job_value),
+            ActiveInput::JobDescription => {
+                (&mut self.job_description, &mut self.job_description_value)
+            }
+            ActiveInput::Tags => (&mut self.tags, &mut self.tags_value),
+            ActiveInput::Owner => (&mut self.owner, &mut self.owner_value),
         }
     }
 
     fn flash_inputs(&mut self) {
-        self.cron_notation.delete_line_by_head();
-        self.cron_notation.delete_line_by_end();
-        self.job.delete_line_by_head();
-        self.job.delete_line_by_end();
-        self.job_description.delete_line_by_head();
-        self.job_description.delete_line_by_end();
+        for input in [
+            &mut self.cron_notation,
+            &mut self.job,
+            &mut self.job_description,
+            &mut self.tags,
+            &mut self.owner,
+        ] {
+            input.delete_line_by_head();
+            input.delete_line_by_end();
+        }
     }
 
     fn flash_values(&mut self) {
         self.cron_notation_value.clear();
         self.job_value.clear();
         self.job_description_value.clear();
+        self.tags_value.clear();
+        self.owner_value.clear();
     }
 
     pub fn init_empty(&mut self) {
         self.is_new = true;
+        self.editing = None;
         self.flash_inputs();
         self.flash_values();
         self.current_input = ActiveInput::CronNotation;
         self.initial_render();
     }
 
-    pub fn init(&mut self, cron_jobs: &mut Vec<CronJob>, table_state: &mut TableState) {
+    pub fn init(&mut self, cron_jobs: &[CronJob], index: usize) {
         self.flash_inputs();
         self.flash_values();
         self.current_input = ActiveInput::CronNotation;
         self.initial_render();
+        self.editing = Some(index);
 
         if !self.is_new {
-            let selected_cron = &mut cron_jobs[table_state.selected().unwrap()];
-            self.cron_notation.insert_str(&selected_cron.cron_notation);
-            self.job.insert_str(&selected_cron.job);
-            self.job_description
-                .insert_str(&selected_cron.job_description);
-
-            self.cron_notation_value = selected_cron.cron_notation.to_string();
-            self.job_value = selected_cron.job.to_string();
-            self.job_description_value = selected_cron.job_description.to_string();
+            self.fill(&cron_jobs[index]);
         }
     }
 
+    /// Opens the popup for a new job prefilled from `cron_job`, e.g. a clone of
+    /// the selected job or a job created from a template.
+    pub fn init_prefilled(&mut self, cron_job: &CronJob) {
+        self.init_empty();
+        self.fill(cron_job);
+    }
+
+    fn fill(&mut self, cron_job: &CronJob) {
+        self.cron_notation_value = cron_job.cron_notation.to_string();
+        self.job_value = cron_job.job.to_string();
+        self.job_description_value = cron_job.job_description.to_string();
+        self.tags_value = cron_job.tags.join(", ");
+        self.owner_value = cron_job.owner.to_string();
+
+        self.cron_notation.insert_str(&self.cron_notation_value);
+        self.job.insert_str(&self.job_value);
+        self.job_description.insert_str(&self.job_description_value);
+        self.tags.insert_str(&self.tags_value);
+        self.owner.insert_str(&self.owner_value);
+    }
+
     fn create_new_cron(&mut self) -> CronJob {
         CronJob::new({
             CronJob {
-                cron_notation: format!("{}", self.cron_notation_value),
-                job: format!("{}", self.job_value),
-                job_description: format!("{}", self.job_description_value),
+                cron_notation: self.cron_notation_value.to_string(),
+                job: self.job_value.to_string(),
+                job_description: self.job_description_value.to_string(),
                 next_execution: get_next_execution(&self.cron_notation_value),
+                tags: parse_tags(&self.tags_value),
+                owner: self.owner_value.trim().to_string(),
+                ..Default::default()
             }
         })
     }
 
     fn update_selected_cron(&mut self, selected_cron: &mut CronJob) {
-        selected_cron.cron_notation = format!("{}", self.cron_notation_value);
-        selected_cron.job = format!("{}", self.job_value);
-        selected_cron.job_description = format!("{}", self.job_description_value);
+        selected_cron.cron_notation = self.cron_notation_value.to_string();
+        selected_cron.job = self.job_value.to_string();
+        selected_cron.job_description = self.job_description_value.to_string();
         selected_cron.next_execution = get_next_execution(&self.cron_notation_value);
+        selected_cron.tags = parse_tags(&self.tags_value);
+        selected_cron.owner = self.owner_value.trim().to_string();
     }
 
     fn handle_paste(&mut self) {
         let mut clipboard = Clipboard::new().unwrap();
+        let (input, value) = self.active_field();
 
-        match self.current_input {
-            ActiveInput::CronNotation => {
-                self.cron_notation.move_cursor(CursorMove::End);
-                self.cron_notation.insert_str(clipboard.get_text().unwrap());
-            }
-            ActiveInput::Job => {
-                self.job.move_cursor(CursorMove::End);
-                self.job.insert_str(clipboard.get_text().unwrap());
-            }
-            ActiveInput::JobDescription => {
-                self.job_description.move_cursor(CursorMove::End);
-                self.job_description
-                    .insert_str(clipboard.get_text().unwrap());
-            }
+        input.move_cursor(CursorMove::End);
+        input.insert_str(clipboard.get_text().unwrap());
+        value.clear();
+        if let Some(first_line) = input.lines().first() {
+            value.push_str(first_line);
         }
     }
 
     fn initial_render(&mut self) {
         let cron_input = &mut self.cron_notation;
-        let job_input = &mut self.job;
-        let description_input = &mut self.job_description;
 
         cron_input.set_placeholder_text("Enter a cron notation");
         cron_input.set_cursor_line_style(Style::default());
@@ -227,18 +254,22 @@ impl Inputs {
                 .border_style(self.styles.selected_input_border_style),
         );
 
-        job_input.set_placeholder_text("Enter a job");
-        job_input.set_cursor_line_style(Style::default());
-
-        description_input.set_placeholder_text("Enter a description");
-        description_input.set_cursor_line_style(Style::default());
+        for (input, placeholder) in [
+            (&mut self.job, "Enter a job"),
+            (&mut self.job_description, "Enter a description"),
+            (&mut self.tags, "Comma separated, e.g. backup,db"),
+            (&mut self.owner, "Enter an owner"),
+        ] {
+            input.set_placeholder_text(placeholder);
+            input.set_cursor_line_style(Style::default());
+        }
     }
 
     pub fn render_inputs(&mut self, area: Rect, buf: &mut Buffer) {
         let area = popup_area(area, 70);
         Widget::render(Clear, area, buf);
 
-        let layout = Layout::vertical([Constraint::Length(17), Constraint::Length(3)])
+        let layout = Layout::vertical([Constraint::Length(20), Constraint::Length(3)])
             .flex(Flex::SpaceBetween);
 
         let [main_area, footer_area] = layout.areas(area);
@@ -256,16 +287,21 @@ impl Inputs {
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
+            Constraint::Length(3),
         ])
         .margin(2)
         .flex(Flex::Start);
-        let [title_area, cron_notation_area, job_area, description_area] = main.areas(main_area);
+        let [title_area, cron_notation_area, job_area, description_area, metadata_area] =
+            main.areas(main_area);
+        let [tags_area, owner_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(metadata_area);
 
         let footer = Layout::vertical([Constraint::Length(3)]);
         let [info_area] = footer.areas(footer_area);
 
         let selected_cron_notation = get_human_readable_cron(self.cron_notation_value.as_str())
-            .unwrap_or_else(|e| format!("{}", e));
+            .unwrap_or_else(|e| e.to_string());
 
         let wrapped_text: Vec<Line> = selected_cron_notation
             .chars()
@@ -290,122 +326,116 @@ impl Inputs {
             );
         Widget::render(title, title_area, buf);
 
-        let info = Paragraph::new(Text::from_iter(INFO_TEXT))
-            .style(self.styles.footer_style)
-            .centered()
-            .block(Block::default());
+        let validation = validate(first_line(&self.cron_notation));
+        let save_style = match validation {
+            Ok(_) => self.styles.footer_style,
+            Err(_) => self.styles.disabled_hint_style,
+        };
+        let info = Paragraph::new(Text::from(vec![
+            Line::from(""),
+            Line::from(vec![
+                Span::raw(INFO_TEXT),
+                Span::styled(SAVE_TEXT, save_style),
+            ]),
+            Line::from(""),
+        ]))
+        .style(self.styles.footer_style)
+        .centered()
+        .block(Block::default());
         Widget::render(info, info_area, buf);
 
-        let cron_input: &mut TextArea<'_> = &mut self.cron_notation;
-        let job_input = &mut self.job;
-        let description_input = &mut self.job_description;
+        let cron_input = &mut self.cron_notation;
+        let focused = self.current_input == ActiveInput::CronNotation;
+        let (border_style, cursor_style, title) = match (&validation, focused) {
+            (Ok(_), true) => (
+                self.styles.valid_input_style,
+                self.styles.valid_cursor_style,
+                "Cron notation* (OK)".to_string(),
+            ),
+            (Err(ValidationError::InvalidCronExpression(message)), true) => (
+                self.styles.invalid_input_style,
+                self.styles.invalid_cursor_style,
+                format!("Cron notation* ({})", message),
+            ),
+            (Ok(_), false) => (
+                self.styles.unselected_input_border_style,
+                Style::default(),
+                "Cron notation*".to_string(),
+            ),
+            (Err(ValidationError::InvalidCronExpression(message)), false) => (
+                self.styles.invalid_input_style,
+                Style::default(),
+                format!("Cron notation* ({})", message),
+            ),
+        };
+        cron_input.set_cursor_style(cursor_style);
+        cron_input.set_style(Style::default());
+        cron_input.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(title),
+        );
+        cron_input.render(cron_notation_area, buf);
+        self.highlight_fields(cron_notation_area, buf);
+
+        for (input, kind, title, input_area) in [
+            (&mut self.job, ActiveInput::Job, "Job", job_area),
+            (
+                &mut self.job_description,
+                ActiveInput::JobDescription,
+                "Description",
+                description_area,
+            ),
+            (&mut self.tags, ActiveInput::Tags, "Tags", tags_area),
+            (&mut self.owner, ActiveInput::Owner, "Owner", owner_area),
+        ] {
+            let (border_style, cursor_style) = if self.current_input == kind {
+                (
+                    self.styles.selected_input_border_style,
+                    self.styles.cursor_style,
+                )
+            } else {
+                (self.styles.unselected_input_border_style, Style::default())
+            };
+            input.set_cursor_style(cursor_style);
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(title),
+            );
+            input.render(input_area, buf);
+        }
+    }
+
+    /// Colours each field of the rendered cron notation separately and marks
+    /// invalid fields. Skipped when the text is wider than the input, since the
+    /// textarea then scrolls horizontally.
+    fn highlight_fields(&self, area: Rect, buf: &mut Buffer) {
+        let value = first_line(&self.cron_notation);
+        let inner_width = area.width.saturating_sub(2) as usize;
+        if value.chars().count() >= inner_width {
+            return;
+        }
 
-        match self.current_input {
-            ActiveInput::CronNotation => {
-                match validate(cron_input) {
-                    Ok(_) => {
-                        cron_input.set_block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .border_style(self.styles.valid_input_style)
-                                .title("Cron notation* (OK)"),
-                        );
-                        cron_input.set_cursor_style(self.styles.valid_cursor_style);
-                    }
-                    Err(ValidationError::InvalidCronExpression(message)) => {
-                        cron_input.set_block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .border_style(self.styles.invalid_input_style)
-                                .title(format!("Cron notation* ({})", message)),
-                        );
-                        cron_input.set_cursor_style(self.styles.invalid_cursor_style);
-                    }
+        for span in analyze_cron_notation(value) {
+            let style = match (&span.error, span.field) {
+                (None, Some(field)) => self.styles.field_styles[field.index()],
+                _ => self.styles.invalid_field_style,
+            };
+            for column in span.start..span.end {
+                let position = (area.x + 1 + column as u16, area.y + 1);
+                if let Some(cell) = buf.cell_mut(position) {
+                    cell.set_style(style);
                 }
-                cron_input.render(cron_notation_area, buf);
-
-                job_input.set_cursor_style(Style::default());
-                job_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Job"),
-                );
-                job_input.render(job_area, buf);
-
-                description_input.set_cursor_style(Style::default());
-                description_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Description"),
-                );
-                description_input.render(description_area, buf);
-            }
-            ActiveInput::Job => {
-                job_input.set_cursor_style(self.styles.cursor_style);
-                job_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.selected_input_border_style)
-                        .title("Job"),
-                );
-                job_input.render(job_area, buf);
-
-                cron_input.set_cursor_style(Style::default());
-                cron_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Cron notation*"),
-                );
-                cron_input.set_style(Style::default());
-                cron_input.render(cron_notation_area, buf);
-
-                description_input.set_cursor_style(Style::default());
-                description_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Description"),
-                );
-                description_input.render(description_area, buf);
-            }
-            ActiveInput::JobDescription => {
-                description_input.set_cursor_style(self.styles.cursor_style);
-                description_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.selected_input_border_style)
-                        .title("Description"),
-                );
-                description_input.render(description_area, buf);
-
-                cron_input.set_cursor_style(Style::default());
-                cron_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Cron notation*"),
-                );
-                cron_input.set_style(Style::default());
-                cron_input.render(cron_notation_area, buf);
-
-                job_input.set_cursor_style(Style::default());
-                job_input.set_block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(self.styles.unselected_input_border_style)
-                        .title("Job"),
-                );
-                job_input.render(job_area, buf);
             }
         }
     }
 }
 
 fn popup_area(area: Rect, percent_x: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Max(20)]).flex(Flex::Center);
+    let vertical = Layout::vertical([Constraint::Max(23)]).flex(Flex::Center);
     let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
     let [area] = vertical.areas(area);
     let [area] = horizontal.areas(area);
@@ -417,29 +447,19 @@ pub enum ValidationError {
     InvalidCronExpression(String),
 }
 
-fn validate(textarea: &mut TextArea) -> Result<(), ValidationError> {
+/// Validates the cron notation field by field, then makes sure the schedule
+/// parser used for the next execution accepts it as well.
+fn validate(input: &str) -> Result<(), ValidationError> {
     use chrono::Utc;
     use cron_parser::parse;
 
-    let input = textarea
-        .lines()
-        .get(0)
-        .map(|s| s.as_str())
-        .unwrap_or("")
-        .trim();
+    validate_cron_notation(input).map_err(ValidationError::InvalidCronExpression)?;
 
-    let now = Utc::now();
-
-    match parse(input, &now) {
-        Ok(_) => {
-            textarea.set_style(Style::default().fg(Color::LightGreen));
-            Ok(())
-        }
-        Err(_) => {
-            textarea.set_style(Style::default().fg(Color::LightRed));
-            Err(ValidationError::InvalidCronExpression(
-                "Invalid cron expression".to_string(),
-            ))
-        }
+    match parse(&normalize_cron_notation(input.trim()), &Utc::now()) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(ValidationError::InvalidCronExpression(format!(
+            "unsupported schedule: {}",
+            err
+        ))),
     }
 }
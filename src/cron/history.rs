@@ -0,0 +1,381 @@
+use crate::log::{get_data_dir, PROJECT_NAME};
+use chrono::{DateTime, Utc};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// First argument that makes the binary run a job and record it instead of
+/// starting the interface.
+pub const RECORD_COMMAND: &str = "record";
+const HISTORY_DIRECTORY: &str = "history";
+const MAX_RUNS: usize = 50;
+const OUTPUT_TAIL_LINES: usize = 20;
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A single recorded run of a job.
+pub struct RunRecord {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub exit_code: i32,
+    pub output: Vec<String>,
+}
+
+impl RunRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    pub fn duration_seconds(&self) -> i64 {
+        (self.end - self.start).num_seconds().max(0)
+    }
+}
+
+/// Formats seconds as `45s`, `3m05s` or `2h10m`.
+pub fn format_duration(seconds: i64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+/// Draws the durations of `runs` (oldest first) as a row of block characters.
+pub fn sparkline(runs: &[RunRecord]) -> String {
+    let longest = runs
+        .iter()
+        .map(RunRecord::duration_seconds)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    runs.iter()
+        .map(|run| {
+            let level = run.duration_seconds() * (SPARKLINE_BARS.len() as i64 - 1) / longest;
+            SPARKLINE_BARS[level as usize]
+        })
+        .collect()
+}
+
+/// Creates an id for a newly recorded job.
+pub fn new_history_id() -> String {
+    format!("job-{:x}", Utc::now().timestamp_millis())
+}
+
+fn history_path(id: &str) -> PathBuf {
+    get_data_dir().join(HISTORY_DIRECTORY).join(id)
+}
+
+/// Escapes backslashes and newlines so the output fits on the record line.
+fn escape_output(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.replace('\\', "\\\\"))
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
+fn unescape_output(value: &str) -> Vec<String> {
+    if value.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                lines.push(String::new());
+            }
+            ('\\', Some('\\')) => {
+                chars.next();
+                lines.last_mut().unwrap().push('\\');
+            }
+            (c, _) => lines.last_mut().unwrap().push(c),
+        }
+    }
+    lines
+}
+
+/// Parses a history file. Every run is a line
+/// `run <start> <end> <exit code> <output>`, the output lines joined by `\n`.
+/// Lines that do not parse, such as one cut short by a crash, are skipped.
+pub fn parse_history(content: &str) -> Vec<RunRecord> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(5, ' ').collect();
+            let ["run", start, end, exit_code, output] = fields[..] else {
+                return None;
+            };
+            Some(RunRecord {
+                start: DateTime::parse_from_rfc3339(start)
+                    .ok()?
+                    .with_timezone(&Utc),
+                end: DateTime::parse_from_rfc3339(end).ok()?.with_timezone(&Utc),
+                exit_code: exit_code.parse().ok()?,
+                output: unescape_output(output),
+            })
+        })
+        .collect()
+}
+
+fn format_run(run: &RunRecord) -> String {
+    format!(
+        "run {} {} {} {}\n",
+        run.start.to_rfc3339(),
+        run.end.to_rfc3339(),
+        run.exit_code,
+        escape_output(&run.output)
+    )
+}
+
+/// Loads the last `MAX_RUNS` recorded runs of a job, oldest first.
+pub fn load_history(id: &str) -> io::Result<Vec<RunRecord>> {
+    match fs::read_to_string(history_path(id)) {
+        Ok(content) => {
+            let mut runs = parse_history(&content);
+            runs.drain(..runs.len().saturating_sub(MAX_RUNS));
+            Ok(runs)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Appends a run to the history of a job as a single write, so runs that end
+/// at the same time do not overwrite each other. Once the file holds twice
+/// `MAX_RUNS` runs it is rewritten with the last `MAX_RUNS`; a lock file keeps
+/// appends out while that happens.
+pub fn append_run(id: &str, run: RunRecord) -> io::Result<()> {
+    append_run_to(&history_path(id), &run)
+}
+
+fn append_run_to(path: &Path, run: &RunRecord) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_file_name(format!(".{}.lock", name)))?;
+    lock.lock()?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(format_run(run).as_bytes())?;
+
+    let runs = parse_history(&fs::read_to_string(path)?);
+    if runs.len() >= 2 * MAX_RUNS {
+        let kept: String = runs[runs.len() - MAX_RUNS..]
+            .iter()
+            .map(format_run)
+            .collect();
+        let temporary = path.with_file_name(format!(".{}.tuixel-tmp", name));
+        fs::write(&temporary, kept)?;
+        fs::rename(&temporary, path)?;
+    }
+    Ok(())
+}
+
+/// Runs `command` with `sh -c`, passes its output through and records the run.
+/// Returns the exit code to exit with.
+pub fn record(args: &[String]) -> i32 {
+    let (id, command) = match args {
+        [id, separator, command] if separator == "--" => (id, command),
+        _ => {
+            eprintln!("usage: tuixel {} <id> -- <command>", RECORD_COMMAND);
+            return 2;
+        }
+    };
+
+    let start = Utc::now();
+    let output = Command::new("sh").arg("-c").arg(command).output();
+    let end = Utc::now();
+
+    let (exit_code, text) = match output {
+        Ok(output) => {
+            // Pass the output on so cron still mails it.
+            let _ = io::stdout().write_all(&output.stdout);
+            let _ = io::stderr().write_all(&output.stderr);
+            let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            (output.status.code().unwrap_or(-1), text)
+        }
+        Err(err) => {
+            eprintln!("Failed to run {}: {}", command, err);
+            (127, err.to_string())
+        }
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    let run = RunRecord {
+        start,
+        end,
+        exit_code,
+        output: tail,
+    };
+    if let Err(err) = append_run(id, run) {
+        eprintln!("Failed to record run history: {}", err);
+    }
+
+    exit_code
+}
+
+/// Quotes a value for `sh`, e.g. `it's` becomes `'it'\''s'`.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Splits a command line the way `sh` splits words, supporting single quotes,
+/// double quotes and backslash escapes. Returns `None` for unbalanced quotes.
+fn shell_words(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => word.push(chars.next()?),
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                word.push(chars.next()?);
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Some(words)
+}
+
+/// Builds the crontab command that records the runs of `command` under `id`.
+pub fn wrap_command(id: &str, command: &str) -> String {
+    let executable = std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| env!("CARGO_PKG_NAME").to_string());
+    format!(
+        "{}_DATA={} {} {} {} -- {}",
+        PROJECT_NAME.clone(),
+        shell_quote(&get_data_dir().to_string_lossy()),
+        shell_quote(&executable),
+        RECORD_COMMAND,
+        id,
+        shell_quote(command)
+    )
+}
+
+/// Recognises a command built by `wrap_command` and returns the history id and
+/// the wrapped command.
+pub fn unwrap_command(job: &str) -> Option<(String, String)> {
+    let words = shell_words(job)?;
+    let [data, executable, record, id, separator, command] = &words[..] else {
+        return None;
+    };
+    let is_wrapper = data.starts_with(&format!("{}_DATA=", PROJECT_NAME.clone()))
+        && executable.ends_with(env!("CARGO_PKG_NAME"))
+        && record == RECORD_COMMAND
+        && separator == "--";
+    is_wrapper.then(|| (id.clone(), command.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn run(exit_code: i32, output: &[&str]) -> RunRecord {
+        RunRecord {
+            start: DateTime::parse_from_rfc3339("2026-10-18T02:00:00+00:00")
+                .unwrap()
+                .with_timezone(&Utc),
+            end: DateTime::parse_from_rfc3339("2026-10-18T02:01:05+00:00")
+                .unwrap()
+                .with_timezone(&Utc),
+            exit_code,
+            output: output.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn history_lines_keep_the_output() {
+        let output = ["copied 3 files", r"C:\backup\new", ""];
+        let content = format_run(&run(1, &output)) + "run cut short";
+
+        let runs = parse_history(&content);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].exit_code, 1);
+        assert_eq!(runs[0].duration_seconds(), 65);
+        assert_eq!(runs[0].output, output);
+    }
+
+    #[test]
+    fn concurrent_appends_keep_every_run() {
+        let directory = std::env::temp_dir().join(format!("tuixel-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let path = directory.join("job-1");
+
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        append_run_to(&path, &run(writer, &["done"])).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(parse_history(&fs::read_to_string(&path).unwrap()).len(), 40);
+
+        // The file is cut back to the last runs once it holds twice as many.
+        for _ in 0..60 {
+            append_run_to(&path, &run(0, &[])).unwrap();
+        }
+        let runs = parse_history(&fs::read_to_string(&path).unwrap());
+        assert!(runs.len() < 2 * MAX_RUNS);
+        assert!(runs.len() >= MAX_RUNS);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
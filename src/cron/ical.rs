@@ -0,0 +1,238 @@
+use crate::cron::utils::{expand_cron_macro, runs_between};
+use crate::cron::CronJob;
+use crate::log::{get_data_dir, PROJECT_NAME};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetName, Tz};
+use lazy_static::lazy_static;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const ICAL_DATE_FORMAT: &str = "%Y%m%dT%H%M%S";
+const DEFAULT_WEEKS: i64 = 4;
+pub const MAX_RUNS_PER_JOB: usize = 5_000;
+/// Lines longer than this many bytes are folded, as RFC 5545 asks.
+const MAX_LINE_LENGTH: usize = 75;
+
+lazy_static! {
+    pub static ref WEEKS_ENV: String = format!("{}_ICAL_WEEKS", PROJECT_NAME.clone());
+}
+
+/// 64 bit FNV-1a hash. Unlike the std hashers its value never changes, so
+/// the event UIDs stay the same from one release to the next.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Number of weeks the export prompt starts with, from `TUIXEL_ICAL_WEEKS`.
+pub fn export_weeks() -> i64 {
+    std::env::var(WEEKS_ENV.clone())
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|weeks| *weeks > 0)
+        .unwrap_or(DEFAULT_WEEKS)
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and newlines.
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line into lines of at most 75 bytes, continuation lines
+/// starting with a space, and terminates it with CRLF.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{}{:02}{:02}", sign, seconds / 3600, seconds % 3600 / 60)
+}
+
+fn utc_offset(timezone: &Tz, time: &DateTime<Utc>) -> (i32, String) {
+    let offset = timezone.offset_from_utc_datetime(&time.naive_utc());
+    let name = offset.abbreviation().unwrap_or("").to_string();
+    (offset.fix().local_minus_utc(), name)
+}
+
+/// Builds a VTIMEZONE for the years of the export from the offset changes of
+/// `timezone`, so calendar tools do not have to know the TZID.
+fn vtimezone(timezone: &Tz, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", timezone.name()),
+    ];
+
+    // Start a year early so the observance in effect at `start` is included.
+    let first_day = NaiveDate::from_ymd_opt(start.year() - 1, 1, 1)
+        .unwrap_or_default()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let mut previous = utc_offset(timezone, &first_day);
+    let mut transitions = Vec::new();
+    let mut hour = first_day;
+    while hour <= *end + Duration::days(1) {
+        let current = utc_offset(timezone, &hour);
+        if current.0 != previous.0 {
+            transitions.push((hour, previous.0, current.clone()));
+        }
+        previous = current;
+        hour += Duration::hours(1);
+    }
+
+    if transitions.is_empty() {
+        let (offset, name) = utc_offset(timezone, start);
+        lines.extend([
+            "BEGIN:STANDARD".to_string(),
+            "DTSTART:19700101T000000".to_string(),
+            format!("TZOFFSETFROM:{}", format_offset(offset)),
+            format!("TZOFFSETTO:{}", format_offset(offset)),
+            format!("TZNAME:{}", name),
+            "END:STANDARD".to_string(),
+        ]);
+    }
+
+    for (time, offset_from, (offset_to, name)) in transitions {
+        // DTSTART of a transition is the local time before the change.
+        let local_start = time.naive_utc() + Duration::seconds(offset_from as i64);
+        let kind = if offset_to > offset_from {
+            "DAYLIGHT"
+        } else {
+            "STANDARD"
+        };
+        lines.extend([
+            format!("BEGIN:{}", kind),
+            format!("DTSTART:{}", local_start.format(ICAL_DATE_FORMAT)),
+            format!("TZOFFSETFROM:{}", format_offset(offset_from)),
+            format!("TZOFFSETTO:{}", format_offset(offset_to)),
+            format!("TZNAME:{}", name),
+            format!("END:{}", kind),
+        ]);
+    }
+
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+/// Writes every run of `jobs` between `start` and `end` as a VEVENT, in the
+/// timezone of `start`. Disabled jobs and `@reboot` jobs are left out. Also
+/// returns the number of jobs whose runs stopped at `MAX_RUNS_PER_JOB`.
+pub fn jobs_to_ics(jobs: &[&CronJob], start: &DateTime<Tz>, end: &DateTime<Tz>) -> (String, usize) {
+    let timezone = start.timezone();
+    let now = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//{}//Cron schedule//EN", env!("CARGO_PKG_NAME")),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-TIMEZONE:{}", timezone.name()),
+    ];
+    lines.extend(vtimezone(
+        &timezone,
+        &start.with_timezone(&Utc),
+        &end.with_timezone(&Utc),
+    ));
+
+    let mut truncated = 0;
+    for job in jobs {
+        if job.disabled || job.job.is_empty() {
+            continue;
+        }
+        let Some(cron_notation) = expand_cron_macro(job.cron_notation.trim()) else {
+            continue;
+        };
+        let summary = if job.job_description.is_empty() {
+            job.job.clone()
+        } else {
+            job.job_description.clone()
+        };
+        let description = format!("Command: {}\nSchedule: {}", job.job, job.cron_notation);
+        let job_id = fnv1a(format!("{}\n{}", job.cron_notation, job.job).as_bytes());
+
+        let (runs, cut) = runs_between(&cron_notation, start, end, MAX_RUNS_PER_JOB);
+        if cut {
+            truncated += 1;
+        }
+        for run in runs {
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!(
+                    "UID:{}-{:x}@{}",
+                    run.format("%Y%m%dT%H%M"),
+                    job_id,
+                    env!("CARGO_PKG_NAME")
+                ),
+                format!("DTSTAMP:{}", now),
+                format!(
+                    "DTSTART;TZID={}:{}",
+                    timezone.name(),
+                    run.format(ICAL_DATE_FORMAT)
+                ),
+                format!(
+                    "DTEND;TZID={}:{}",
+                    timezone.name(),
+                    (run + Duration::minutes(1)).format(ICAL_DATE_FORMAT)
+                ),
+                format!("SUMMARY:{}", escape_text(&summary)),
+                format!("DESCRIPTION:{}", escape_text(&description)),
+            ]);
+            if !job.tags.is_empty() {
+                let tags: Vec<String> = job.tags.iter().map(|tag| escape_text(tag)).collect();
+                lines.push(format!("CATEGORIES:{}", tags.join(",")));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    (
+        lines.iter().map(|line| fold_line(line)).collect(),
+        truncated,
+    )
+}
+
+/// Exports the runs of `jobs` for the next `weeks` weeks into the data
+/// directory. Returns the path of the file and the number of jobs whose runs
+/// were cut short.
+pub fn export_ics(jobs: &[&CronJob], timezone: &Tz, weeks: i64) -> io::Result<(PathBuf, usize)> {
+    let start = Utc::now().with_timezone(timezone);
+    let end = start + Duration::weeks(weeks);
+
+    let directory = get_data_dir();
+    fs::create_dir_all(&directory)?;
+    let path = directory.join(format!("cron-schedule-{}.ics", start.format("%Y%m%d%H%M")));
+    let (calendar, truncated) = jobs_to_ics(jobs, &start, &end);
+    fs::write(&path, calendar)?;
+    Ok((path, truncated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -1,11 +1,39 @@
+pub mod access;
+pub use access::CronAccess;
+
+pub mod anacron;
+pub use anacron::AnacronTable;
+
+pub mod at;
+pub use at::AtQueue;
+
+pub mod backend;
+pub use backend::CrontabBackend;
+
 pub mod cron;
 pub use cron::CronJob;
 pub use cron::CronTable;
 
+pub mod diagnostics;
+
 pub mod edit;
 pub use edit::Inputs;
 
+pub mod history;
+
+pub mod ical;
+
+pub mod query;
+pub use query::CronQuery;
+
+pub mod raw;
+pub use raw::CronRaw;
+
 pub mod styles;
-pub use styles::{EditWindowStyles, TableStyles};
+pub use styles::{EditWindowStyles, RawViewStyles, TableStyles};
+
+pub mod syntax;
+
+pub mod templates;
 
 pub mod utils;
@@ -0,0 +1,562 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::config::CONFIG;
+use crate::cron::backend::backend_from_env;
+use crate::cron::utils::{expand_cron_macro, first_line, from_crontab, runs_between};
+use crate::cron::{CronJob, CronTable, EditWindowStyles, TableStyles};
+use crate::log::get_data_dir;
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyModifiers, MouseEvent},
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    widgets::{
+        Block, Borders, Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState,
+    },
+};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tui_textarea::TextArea;
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+const SYSTEM_CRONTAB: &str = "/etc/crontab";
+const SYSTEM_CRON_DIR: &str = "/etc/cron.d";
+const MAX_RUNS_PER_JOB: usize = 10_000;
+
+/// A scheduled job from any of the loaded crontabs.
+pub struct ScheduledJob {
+    pub source: String,
+    pub cron_notation: String,
+    pub job: String,
+}
+
+/// A single execution of a job that falls inside the queried window.
+pub struct JobRun {
+    pub time: DateTime<Tz>,
+    pub source: String,
+    pub cron_notation: String,
+    pub job: String,
+}
+
+impl JobRun {
+    fn ref_array(&self) -> [String; 4] {
+        [
+            self.time.format(DATE_FORMAT).to_string(),
+            self.source.clone(),
+            self.cron_notation.clone(),
+            self.job.clone(),
+        ]
+    }
+}
+
+enum ActiveInput {
+    Start,
+    End,
+}
+
+pub struct CronQuery {
+    state: TableState,
+    items: Vec<JobRun>,
+    jobs: Vec<ScheduledJob>,
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    input_styles: EditWindowStyles,
+    start: TextArea<'static>,
+    end: TextArea<'static>,
+    current_input: ActiveInput,
+    message: Option<String>,
+}
+
+impl ScreenTrait for CronQuery {
+    fn new() -> Self {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        let now = Utc::now().with_timezone(&timezone);
+
+        let mut start = TextArea::default();
+        start.insert_str((now - Duration::days(1)).format(DATE_FORMAT).to_string());
+        let mut end = TextArea::default();
+        end.insert_str(now.format(DATE_FORMAT).to_string());
+
+        let (jobs, message) = match load_scheduled_jobs() {
+            Ok(jobs) => (jobs, None),
+            Err(err) => {
+                tracing::error!("Error loading crontabs: {}", err);
+                (Vec::new(), Some(format!("Error loading crontabs: {}", err)))
+            }
+        };
+
+        let mut query = Self {
+            state: TableState::default().with_selected(0),
+            items: Vec::new(),
+            jobs,
+            scroll_state: ScrollbarState::new(0),
+            styles: TableStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            start,
+            end,
+            current_input: ActiveInput::Start,
+            message,
+        };
+        if query.message.is_none() {
+            query.run_query();
+        }
+        query
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+
+        self.render_inputs(rects[0], buf);
+        self.render_table(rects[1], buf);
+        self.render_scrollbar(rects[1], buf);
+        self.render_message(rects[2], buf);
+        self.render_footer(
+            rects[3],
+            buf,
+            vec![
+                ("<Esc>", "Return to cron jobs"),
+                ("<Tab>", "Switch between start and end"),
+                ("<Enter>", "Run query"),
+                ("<↓↑>", "Move up and down"),
+                ("<Ctrl+t>", "Export as text"),
+                ("<Ctrl+x>", "Export as CSV"),
+            ],
+        );
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if key.code == KeyCode::Esc {
+            Some(Screen::CronTable(CronTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl CronQuery {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        let ctrl_pressed = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Tab => {
+                self.current_input = match self.current_input {
+                    ActiveInput::Start => ActiveInput::End,
+                    ActiveInput::End => ActiveInput::Start,
+                };
+            }
+            KeyCode::Enter => {
+                self.run_query();
+            }
+            KeyCode::Down => {
+                self.next_row();
+            }
+            KeyCode::Up => {
+                self.previous_row();
+            }
+            KeyCode::Char('t') if ctrl_pressed => {
+                self.export(ExportFormat::Text);
+            }
+            KeyCode::Char('x') if ctrl_pressed => {
+                self.export(ExportFormat::Csv);
+            }
+            _ => {
+                match self.current_input {
+                    ActiveInput::Start => self.start.input(key),
+                    ActiveInput::End => self.end.input(key),
+                };
+            }
+        }
+    }
+
+    fn run_query(&mut self) {
+        let timezone = CONFIG.read().unwrap().get_timezone();
+        let start = parse_datetime(first_line(&self.start), &timezone);
+        let end = parse_datetime(first_line(&self.end), &timezone);
+
+        match (start, end) {
+            (Ok(start), Ok(end)) if start <= end => {
+                let (runs, truncated) = runs_in_window(&self.jobs, &start, &end);
+                self.items = runs;
+                let mut message = format!(
+                    "{} runs of {} jobs between {} and {} ({})",
+                    self.items.len(),
+                    self.jobs.len(),
+                    start.format(DATE_FORMAT),
+                    end.format(DATE_FORMAT),
+                    timezone
+                );
+                if truncated > 0 {
+                    message.push_str(&format!(
+                        ", truncated: {} jobs stopped at {} runs",
+                        truncated, MAX_RUNS_PER_JOB
+                    ));
+                }
+                self.message = Some(message);
+            }
+            (Ok(_), Ok(_)) => {
+                self.message = Some("The start must not be after the end".to_string());
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                self.message = Some(err);
+            }
+        }
+
+        self.state.select(Some(0));
+        self.scroll_state = ScrollbarState::new(self.items.len().saturating_sub(1));
+    }
+
+    fn export(&mut self, format: ExportFormat) {
+        let directory = get_data_dir();
+        let extension = match format {
+            ExportFormat::Text => "txt",
+            ExportFormat::Csv => "csv",
+        };
+        let name = format!(
+            "cron-runs-{}-{}.{}",
+            first_line(&self.start).replace([' ', ':'], ""),
+            first_line(&self.end).replace([' ', ':'], ""),
+            extension
+        );
+        let path = directory.join(name);
+
+        let content = match format {
+            ExportFormat::Text => runs_to_text(&self.items),
+            ExportFormat::Csv => runs_to_csv(&self.items),
+        };
+
+        self.message = Some(
+            match fs::create_dir_all(&directory).and_then(|_| fs::write(&path, content)) {
+                Ok(_) => format!("Exported {} runs to {}", self.items.len(), path.display()),
+                Err(err) => format!("Export failed: {}", err),
+            },
+        );
+    }
+
+    fn next_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i < self.items.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i);
+    }
+
+    fn previous_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.items.len() - 1,
+        };
+        self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i);
+    }
+
+    fn render_inputs(&mut self, area: Rect, buf: &mut Buffer) {
+        let [start_area, end_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+
+        let (start_border, end_border) = match self.current_input {
+            ActiveInput::Start => (
+                self.input_styles.selected_input_border_style,
+                self.input_styles.unselected_input_border_style,
+            ),
+            ActiveInput::End => (
+                self.input_styles.unselected_input_border_style,
+                self.input_styles.selected_input_border_style,
+            ),
+        };
+        let (start_cursor, end_cursor) = match self.current_input {
+            ActiveInput::Start => (self.input_styles.cursor_style, Style::default()),
+            ActiveInput::End => (Style::default(), self.input_styles.cursor_style),
+        };
+
+        self.start.set_cursor_line_style(Style::default());
+        self.start.set_cursor_style(start_cursor);
+        self.start.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(start_border)
+                .title("From (YYYY-MM-DD HH:MM)"),
+        );
+        self.start.render(start_area, buf);
+
+        self.end.set_cursor_line_style(Style::default());
+        self.end.set_cursor_style(end_cursor);
+        self.end.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(end_border)
+                .title("To (YYYY-MM-DD HH:MM)"),
+        );
+        self.end.render(end_area, buf);
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let header = ["Time", "Source", "Cron Notation", "Job"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(1);
+        let rows = self.items.iter().enumerate().map(|(i, data)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            data.ref_array()
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(self.styles.row_style.bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Percentage(25),
+                Constraint::Percentage(20),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.styles.selected_row_style)
+        .style(self.styles.row_style.bg(self.styles.normal_row_color))
+        .highlight_symbol(" ▌ ")
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+
+    fn render_message(&mut self, area: Rect, buf: &mut Buffer) {
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, area, buf);
+    }
+}
+
+enum ExportFormat {
+    Text,
+    Csv,
+}
+
+/// Parses a `YYYY-MM-DD HH:MM` timestamp as local time in `timezone`.
+pub fn parse_datetime(value: &str, timezone: &Tz) -> Result<DateTime<Tz>, String> {
+    let naive = NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| format!("Invalid time '{}', expected YYYY-MM-DD HH:MM", value))?;
+    timezone
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("'{}' does not exist in {}", value, timezone))
+}
+
+/// Lists every run of `jobs` between `start` and `end`, ordered by time,
+/// and the number of jobs whose runs stopped at `MAX_RUNS_PER_JOB`.
+pub fn runs_in_window(
+    jobs: &[ScheduledJob],
+    start: &DateTime<Tz>,
+    end: &DateTime<Tz>,
+) -> (Vec<JobRun>, usize) {
+    let mut runs = Vec::new();
+    let mut truncated = 0;
+    for job in jobs {
+        let (times, cut) = runs_between(&job.cron_notation, start, end, MAX_RUNS_PER_JOB);
+        if cut {
+            truncated += 1;
+        }
+        runs.extend(times.into_iter().map(|time| JobRun {
+            time,
+            source: job.source.clone(),
+            cron_notation: job.cron_notation.clone(),
+            job: job.job.clone(),
+        }));
+    }
+    runs.sort_by_key(|run| run.time);
+    (runs, truncated)
+}
+
+/// Parses a system crontab (`/etc/crontab` or a file in `/etc/cron.d`), where
+/// every job line has a user field between the schedule and the command.
+pub fn parse_system_crontab(source: &str, content: &str) -> Vec<ScheduledJob> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts[0].contains('=') {
+                return None;
+            }
+            let (cron_notation, rest) = if parts[0].starts_with('@') {
+                (expand_cron_macro(parts[0])?, &parts[1..])
+            } else if parts.len() >= 5 {
+                (parts[..5].join(" "), &parts[5..])
+            } else {
+                return None;
+            };
+            if rest.len() < 2 {
+                return None;
+            }
+            Some(ScheduledJob {
+                source: format!("{} ({})", source, rest[0]),
+                cron_notation,
+                job: rest[1..].join(" "),
+            })
+        })
+        .collect()
+}
+
+/// The jobs of the user crontab, including the `@daily` style entries the
+/// crontab parser keeps verbatim.
+fn user_crontab_jobs(cron_jobs: Vec<CronJob>) -> Vec<ScheduledJob> {
+    let mut jobs = Vec::new();
+    for cron_job in cron_jobs {
+        for line in cron_job.verbatim.iter().map(|line| line.trim()) {
+            let mut parts = line.split_whitespace();
+            let Some(cron_notation) = parts
+                .next()
+                .filter(|first| first.starts_with('@'))
+                .and_then(expand_cron_macro)
+            else {
+                continue;
+            };
+            let job = parts.collect::<Vec<_>>().join(" ");
+            if !job.is_empty() {
+                jobs.push(ScheduledJob {
+                    source: "crontab".to_string(),
+                    cron_notation,
+                    job,
+                });
+            }
+        }
+        if !cron_job.job.is_empty() && !cron_job.disabled {
+            jobs.push(ScheduledJob {
+                source: "crontab".to_string(),
+                cron_notation: cron_job.cron_notation,
+                job: cron_job.job,
+            });
+        }
+    }
+    jobs
+}
+
+/// Loads the jobs of the user crontab, `/etc/crontab` and `/etc/cron.d`.
+pub fn load_scheduled_jobs() -> io::Result<Vec<ScheduledJob>> {
+    let mut jobs = user_crontab_jobs(from_crontab(backend_from_env().as_ref())?);
+
+    let mut files = vec![PathBuf::from(SYSTEM_CRONTAB)];
+    if let Ok(entries) = fs::read_dir(SYSTEM_CRON_DIR) {
+        let mut cron_d: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        cron_d.sort();
+        files.extend(cron_d);
+    }
+
+    for file in files {
+        if let Ok(content) = fs::read_to_string(&file) {
+            jobs.extend(parse_system_crontab(&file.to_string_lossy(), &content));
+        }
+    }
+
+    Ok(jobs)
+}
+
+pub fn runs_to_text(runs: &[JobRun]) -> String {
+    runs.iter()
+        .map(|run| {
+            format!(
+                "{}  {}  {}  {}\n",
+                run.time.format("%Y-%m-%d %H:%M %Z"),
+                run.source,
+                run.cron_notation,
+                run.job
+            )
+        })
+        .collect()
+}
+
+pub fn runs_to_csv(runs: &[JobRun]) -> String {
+    let mut csv = String::from("time,source,cron_notation,job\n");
+    for run in runs {
+        let fields = [
+            run.time.to_rfc3339(),
+            run.source.clone(),
+            run.cron_notation.clone(),
+            run.job.clone(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cron::utils::parse_crontab;
+
+    #[test]
+    fn user_crontab_jobs_expand_macros() {
+        let jobs = user_crontab_jobs(parse_crontab(
+            "MAILTO=root\n# nightly\n@daily /usr/bin/backup --all\n@reboot /usr/bin/start\n*/5 * * * * /usr/bin/poll\n",
+        ));
+        let found: Vec<(&str, &str)> = jobs
+            .iter()
+            .map(|job| (job.cron_notation.as_str(), job.job.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("0 0 * * *", "/usr/bin/backup --all"),
+                ("*/5 * * * *", "/usr/bin/poll"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::cron::backend::backend_from_env;
+use crate::cron::syntax::{analyze_crontab_line, validate_crontab, CrontabToken};
+use crate::cron::{CronTable, CrontabBackend, EditWindowStyles, RawViewStyles, TableStyles};
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyModifiers, MouseEvent},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use tui_textarea::TextArea;
+
+const MAX_ERROR_LINES: usize = 6;
+const DISCARD_INFO_TEXT: &str = " (Esc) Keep editing | (y) Discard ";
+
+/// What discarding the edited text leads to, once confirmed.
+#[derive(Clone, Copy)]
+enum Discard {
+    Leave,
+    Reload,
+}
+
+/// Shows the crontab as plain text in a multi-line editor. The text is
+/// validated line by line before it is installed.
+pub struct CronRaw {
+    editor: TextArea<'static>,
+    backend: Box<dyn CrontabBackend>,
+    styles: RawViewStyles,
+    table_styles: TableStyles,
+    input_styles: EditWindowStyles,
+    /// First visible row and column of the editor, mirroring the scrolling of
+    /// the textarea so the highlighting lines up with the rendered text.
+    top_row: u16,
+    top_col: u16,
+    errors: Vec<(usize, String)>,
+    modified: bool,
+    message: Option<String>,
+    discard: Option<Discard>,
+}
+
+impl ScreenTrait for CronRaw {
+    fn new() -> Self {
+        let mut raw = Self {
+            editor: TextArea::default(),
+            backend: backend_from_env(),
+            styles: RawViewStyles::new(),
+            table_styles: TableStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            top_row: 0,
+            top_col: 0,
+            errors: Vec::new(),
+            modified: false,
+            message: None,
+            discard: None,
+        };
+        raw.reload();
+        raw
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let error_height = match self.errors.len() {
+            0 => 0,
+            count => count.min(MAX_ERROR_LINES) as u16 + 2,
+        };
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(error_height),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+
+        self.render_editor(rects[0], buf);
+        self.render_errors(rects[1], buf);
+        self.render_message(rects[2], buf);
+        self.render_footer(
+            rects[3],
+            buf,
+            vec![
+                ("<Esc>", "Return to cron jobs"),
+                ("<Ctrl+s>", "Validate and install"),
+                ("<Ctrl+r>", "Reload from crontab"),
+            ],
+        );
+
+        if let Some(discard) = self.discard {
+            self.render_discard(discard, area, buf);
+        }
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if let Some(discard) = self.discard {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.discard = None;
+                    match discard {
+                        Discard::Leave => return Some(Screen::CronTable(CronTable::new())),
+                        Discard::Reload => self.reload(),
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('n') => self.discard = None,
+                _ => {}
+            }
+            None
+        } else if key.code == KeyCode::Esc {
+            if self.modified {
+                self.discard = Some(Discard::Leave);
+                None
+            } else {
+                Some(Screen::CronTable(CronTable::new()))
+            }
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl CronRaw {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        let ctrl_pressed = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('s') if ctrl_pressed => {
+                self.install();
+            }
+            KeyCode::Char('r') if ctrl_pressed => {
+                if self.modified {
+                    self.discard = Some(Discard::Reload);
+                } else {
+                    self.reload();
+                }
+            }
+            _ => {
+                if self.editor.input(key) {
+                    self.modified = true;
+                }
+            }
+        }
+    }
+
+    fn reload(&mut self) {
+        let content = match self.backend.read() {
+            Ok(content) => {
+                self.message = Some(format!("Loaded from {}", self.backend.describe()));
+                content.unwrap_or_default()
+            }
+            Err(err) => {
+                tracing::error!("Error reading crontab: {}", err);
+                self.message = Some(format!("Error reading crontab: {}", err));
+                String::new()
+            }
+        };
+
+        self.editor = TextArea::from(content.lines());
+        self.editor.set_style(self.styles.editor_style);
+        self.editor.set_cursor_line_style(Style::default());
+        self.editor.set_cursor_style(self.styles.cursor_style);
+        self.top_row = 0;
+        self.top_col = 0;
+        self.errors.clear();
+        self.modified = false;
+    }
+
+    /// Installs the text if every line is valid, otherwise lists the invalid
+    /// lines and leaves the crontab untouched.
+    fn install(&mut self) {
+        let mut content = self.editor.lines().join("\n");
+        // cron ignores a last line without a trailing newline.
+        content.push('\n');
+
+        self.errors = validate_crontab(&content);
+        if !self.errors.is_empty() {
+            self.message = Some(format!(
+                "Not installed: {} invalid lines",
+                self.errors.len()
+            ));
+            return;
+        }
+
+        match self.backend.write(&content) {
+            Ok(_) => {
+                self.modified = false;
+                self.message = Some(format!("Installed to {}", self.backend.describe()));
+            }
+            Err(err) => {
+                tracing::error!("Error installing crontab: {}", err);
+                self.message = Some(format!("Error installing crontab: {}", err));
+            }
+        }
+    }
+
+    fn render_editor(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = if self.modified {
+            format!(" {} (modified) ", self.backend.describe())
+        } else {
+            format!(" {} ", self.backend.describe())
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.styles.editor_border_style)
+            .style(self.styles.editor_style);
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let gutter_width = self.editor.lines().len().to_string().len() as u16 + 3;
+        let [gutter_area, text_area] =
+            Layout::horizontal([Constraint::Length(gutter_width), Constraint::Min(1)]).areas(inner);
+
+        let (cursor_row, cursor_col) = self.editor.cursor();
+        self.top_row = next_scroll_top(self.top_row, cursor_row as u16, text_area.height);
+        self.top_col = next_scroll_top(self.top_col, cursor_col as u16, text_area.width);
+
+        self.editor.render(text_area, buf);
+        self.render_gutter(gutter_area, buf);
+        self.highlight(text_area, buf);
+    }
+
+    fn render_gutter(&self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.saturating_sub(3) as usize;
+        let lines: Vec<Line> = self
+            .visible_rows(area.height)
+            .map(|row| {
+                let (_, error) = analyze_crontab_line(&self.editor.lines()[row]);
+                match error {
+                    Some(_) => Line::styled(
+                        format!("{:>width$} ! ", row + 1),
+                        self.styles.gutter_error_style,
+                    ),
+                    None => {
+                        Line::styled(format!("{:>width$}   ", row + 1), self.styles.gutter_style)
+                    }
+                }
+            })
+            .collect();
+        Widget::render(
+            Paragraph::new(Text::from(lines)).style(self.styles.gutter_style),
+            area,
+            buf,
+        );
+    }
+
+    /// Colours the rendered text by the kind of each part of a line.
+    fn highlight(&self, area: Rect, buf: &mut Buffer) {
+        let cursor = self.editor.cursor();
+        for (screen_row, row) in self.visible_rows(area.height).enumerate() {
+            let (spans, _) = analyze_crontab_line(&self.editor.lines()[row]);
+            for span in spans {
+                let style = self.token_style(span.token);
+                for column in span.start..span.end {
+                    if (row, column) == cursor || column < self.top_col as usize {
+                        continue;
+                    }
+                    let x = column - self.top_col as usize;
+                    if x >= area.width as usize {
+                        break;
+                    }
+                    let position = (area.x + x as u16, area.y + screen_row as u16);
+                    if let Some(cell) = buf.cell_mut(position) {
+                        cell.set_style(style);
+                    }
+                }
+            }
+        }
+    }
+
+    fn token_style(&self, token: CrontabToken) -> Style {
+        match token {
+            CrontabToken::Comment => self.styles.comment_style,
+            CrontabToken::Metadata => self.styles.metadata_style,
+            CrontabToken::Variable => self.styles.variable_style,
+            CrontabToken::Value => self.styles.value_style,
+            CrontabToken::Schedule(Some(field)) => self.styles.field_styles[field.index()],
+            CrontabToken::Schedule(None) => self.styles.macro_style,
+            CrontabToken::Command => self.styles.command_style,
+            CrontabToken::Invalid => self.styles.invalid_style,
+        }
+    }
+
+    fn visible_rows(&self, height: u16) -> std::ops::Range<usize> {
+        let top = self.top_row as usize;
+        top..(top + height as usize).min(self.editor.lines().len())
+    }
+
+    fn render_errors(&self, area: Rect, buf: &mut Buffer) {
+        if self.errors.is_empty() {
+            return;
+        }
+        let mut lines: Vec<Line> = self
+            .errors
+            .iter()
+            .take(MAX_ERROR_LINES)
+            .map(|(line, error)| {
+                Line::from(vec![
+                    Span::styled(format!("line {}: ", line), self.styles.gutter_error_style),
+                    Span::raw(format!(" {}", error)),
+                ])
+            })
+            .collect();
+        if self.errors.len() > MAX_ERROR_LINES {
+            if let Some(last) = lines.last_mut() {
+                last.push_span(Span::raw(format!(
+                    " (and {} more)",
+                    self.errors.len() - MAX_ERROR_LINES
+                )));
+            }
+        }
+
+        let errors = Paragraph::new(Text::from(lines))
+            .style(self.styles.error_style)
+            .block(
+                Block::default()
+                    .title(" Invalid lines ")
+                    .borders(Borders::ALL),
+            );
+        Widget::render(errors, area, buf);
+    }
+
+    fn render_discard(&self, discard: Discard, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Max(5)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Widget::render(Clear, area, buf);
+
+        let title = match discard {
+            Discard::Leave => " Leave without installing ",
+            Discard::Reload => " Reload from crontab ",
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(DISCARD_INFO_TEXT).centered())
+            .style(self.input_styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.input_styles.window_border_style);
+        let text = Paragraph::new(Line::styled(
+            format!(
+                "The edited text has not been installed to {} and will be lost.",
+                self.backend.describe()
+            ),
+            self.input_styles.title_style,
+        ))
+        .wrap(Wrap { trim: false })
+        .block(block);
+        Widget::render(text, area, buf);
+    }
+
+    fn render_message(&mut self, area: Rect, buf: &mut Buffer) {
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.table_styles.message_style)
+            .centered();
+        Widget::render(message, area, buf);
+    }
+}
+
+/// Scrolls the viewport the same way the textarea does: only as far as
+/// needed to keep the cursor visible.
+fn next_scroll_top(previous_top: u16, cursor: u16, length: u16) -> u16 {
+    if cursor < previous_top {
+        cursor
+    } else if previous_top + length <= cursor {
+        cursor + 1 - length
+    } else {
+        previous_top
+    }
+}
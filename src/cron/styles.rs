@@ -8,6 +8,14 @@ pub struct TableStyles {
     pub normal_row_color: Color,
     pub alt_row_color: Color,
     pub scrollbar_style: Style,
+    pub skipped_row_style: Style,
+    pub message_style: Style,
+    pub group_row_style: Style,
+    pub disabled_row_style: Style,
+    pub maintenance_header_style: Style,
+    pub success_style: Style,
+    pub failure_style: Style,
+    pub sparkline_style: Style,
 }
 
 impl TableStyles {
@@ -25,6 +33,28 @@ impl TableStyles {
                 .fg(tailwind::GRAY.c300)
                 .bg(Color::DarkGray)
                 .add_modifier(Modifier::REVERSED),
+            skipped_row_style: Style::new().fg(tailwind::AMBER.c300),
+            message_style: Style::new()
+                .fg(tailwind::GRAY.c300)
+                .bg(tailwind::SLATE.c900),
+            group_row_style: Style::new()
+                .fg(tailwind::SKY.c200)
+                .bg(tailwind::SLATE.c800)
+                .add_modifier(Modifier::BOLD),
+            disabled_row_style: Style::new()
+                .fg(tailwind::GRAY.c400)
+                .add_modifier(Modifier::DIM),
+            maintenance_header_style: Style::new()
+                .fg(tailwind::AMBER.c100)
+                .bg(tailwind::AMBER.c800)
+                .add_modifier(Modifier::BOLD),
+            success_style: Style::new()
+                .fg(tailwind::GREEN.c400)
+                .add_modifier(Modifier::BOLD),
+            failure_style: Style::new()
+                .fg(tailwind::RED.c400)
+                .add_modifier(Modifier::BOLD),
+            sparkline_style: Style::new().fg(tailwind::SKY.c300),
         }
     }
 }
@@ -42,6 +72,9 @@ pub struct EditWindowStyles {
     pub invalid_input_style: Style,
     pub invalid_cursor_style: Style,
     pub cursor_style: Style,
+    pub field_styles: [Style; 5],
+    pub invalid_field_style: Style,
+    pub disabled_hint_style: Style,
 }
 
 impl EditWindowStyles {
@@ -61,6 +94,75 @@ impl EditWindowStyles {
             invalid_input_style: Style::new().fg(Color::LightRed),
             invalid_cursor_style: Style::new().bg(Color::LightRed),
             cursor_style: Style::new().bg(Color::White),
+            field_styles: [
+                Style::new().fg(Color::LightCyan),
+                Style::new().fg(Color::LightGreen),
+                Style::new().fg(Color::LightYellow),
+                Style::new().fg(Color::LightMagenta),
+                Style::new().fg(Color::LightBlue),
+            ],
+            invalid_field_style: Style::new()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+            disabled_hint_style: Style::new()
+                .fg(tailwind::SLATE.c500)
+                .bg(tailwind::SLATE.c800)
+                .add_modifier(Modifier::CROSSED_OUT),
+        }
+    }
+}
+
+pub struct RawViewStyles {
+    pub editor_style: Style,
+    pub editor_border_style: Style,
+    pub cursor_style: Style,
+    pub comment_style: Style,
+    pub metadata_style: Style,
+    pub variable_style: Style,
+    pub value_style: Style,
+    pub macro_style: Style,
+    pub field_styles: [Style; 5],
+    pub command_style: Style,
+    pub invalid_style: Style,
+    pub gutter_style: Style,
+    pub gutter_error_style: Style,
+    pub error_style: Style,
+}
+
+impl RawViewStyles {
+    pub const fn new() -> Self {
+        Self {
+            editor_style: Style::new()
+                .fg(tailwind::GRAY.c200)
+                .bg(tailwind::SLATE.c900),
+            editor_border_style: Style::new().fg(Color::LightCyan),
+            cursor_style: Style::new().bg(Color::White),
+            comment_style: Style::new()
+                .fg(tailwind::SLATE.c400)
+                .add_modifier(Modifier::ITALIC),
+            metadata_style: Style::new().fg(tailwind::AMBER.c300),
+            variable_style: Style::new()
+                .fg(tailwind::VIOLET.c300)
+                .add_modifier(Modifier::BOLD),
+            value_style: Style::new().fg(tailwind::VIOLET.c100),
+            macro_style: Style::new()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            field_styles: EditWindowStyles::new().field_styles,
+            command_style: Style::new().fg(tailwind::GRAY.c100),
+            invalid_style: Style::new()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+            gutter_style: Style::new()
+                .fg(tailwind::SLATE.c500)
+                .bg(tailwind::SLATE.c800),
+            gutter_error_style: Style::new()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+            error_style: Style::new().fg(Color::LightRed).bg(tailwind::SLATE.c900),
         }
     }
 }
@@ -0,0 +1,370 @@
+use crate::cron::utils::parse_metadata;
+
+/// The five fields of a cron notation, in order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CronField {
+    Minute,
+    Hour,
+    DayOfMonth,
+    Month,
+    DayOfWeek,
+}
+
+const FIELDS: [CronField; 5] = [
+    CronField::Minute,
+    CronField::Hour,
+    CronField::DayOfMonth,
+    CronField::Month,
+    CronField::DayOfWeek,
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CronField::Minute => "minute",
+            CronField::Hour => "hour",
+            CronField::DayOfMonth => "day-of-month",
+            CronField::Month => "month",
+            CronField::DayOfWeek => "day-of-week",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn range(&self) -> (u32, u32) {
+        match self {
+            CronField::Minute => (0, 59),
+            CronField::Hour => (0, 23),
+            CronField::DayOfMonth => (1, 31),
+            CronField::Month => (1, 12),
+            CronField::DayOfWeek => (0, 7),
+        }
+    }
+
+    /// Resolves a single value, accepting month and day names where cron does.
+    fn value(&self, value: &str) -> Result<u32, String> {
+        let (min, max) = self.range();
+        let lower = value.to_lowercase();
+        let named = match self {
+            CronField::Month => MONTH_NAMES.iter().position(|name| *name == lower),
+            CronField::DayOfWeek => DAY_NAMES.iter().position(|name| *name == lower),
+            _ => None,
+        };
+        if let Some(position) = named {
+            return Ok(position as u32 + min);
+        }
+
+        let number: u32 = value
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", value))?;
+        if number < min || number > max {
+            return Err(format!("{} out of range {}-{}", value, min, max));
+        }
+        Ok(number)
+    }
+
+    /// Checks a whole field such as `1-5,*/10` and describes the first problem.
+    pub fn validate(&self, field: &str) -> Result<(), String> {
+        for item in field.split(',') {
+            if item.is_empty() {
+                return Err("empty list entry".to_string());
+            }
+
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+
+            if let Some(step) = step {
+                let (_, max) = self.range();
+                match step.parse::<u32>() {
+                    Ok(0) => return Err("step must be greater than 0".to_string()),
+                    Ok(step) if step > max => {
+                        return Err(format!("step {} larger than {}", step, max));
+                    }
+                    Ok(_) => {}
+                    Err(_) => return Err(format!("step '{}' is not a number", step)),
+                }
+            }
+
+            if range == "*" {
+                continue;
+            }
+
+            match range.split_once('-') {
+                Some((start, end)) => {
+                    let start_value = self.value(start)?;
+                    let end_value = self.value(end)?;
+                    if start_value > end_value {
+                        return Err(format!("range {} is reversed", range));
+                    }
+                }
+                None => {
+                    self.value(range)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A whitespace separated token of a cron notation and its position,
+/// counted in characters.
+pub struct FieldSpan {
+    pub start: usize,
+    pub end: usize,
+    pub field: Option<CronField>,
+    pub error: Option<String>,
+}
+
+/// Splits a cron notation into its fields and validates each one.
+/// Tokens past the fifth field are reported with `field: None`.
+pub fn analyze_cron_notation(cron_notation: &str) -> Vec<FieldSpan> {
+    let mut spans = Vec::new();
+    let mut start = None;
+
+    for (position, character) in cron_notation.chars().chain([' ']).enumerate() {
+        match (character.is_whitespace(), start) {
+            (false, None) => start = Some(position),
+            (true, Some(token_start)) => {
+                let token: String = cron_notation
+                    .chars()
+                    .skip(token_start)
+                    .take(position - token_start)
+                    .collect();
+                let field = FIELDS.get(spans.len()).copied();
+                let error = match field {
+                    Some(field) => field.validate(&token).err(),
+                    None => Some(format!("unexpected '{}', expected 5 fields", token)),
+                };
+                spans.push(FieldSpan {
+                    start: token_start,
+                    end: position,
+                    field,
+                    error,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+/// Validates a five field cron notation, naming the field at fault.
+pub fn validate_cron_notation(cron_notation: &str) -> Result<(), String> {
+    let trimmed = cron_notation.trim();
+    if trimmed.is_empty() {
+        return Err("Cron notation is empty".to_string());
+    }
+    if trimmed.starts_with('@') {
+        return Err(format!(
+            "{} is not supported, use the five field notation",
+            trimmed
+        ));
+    }
+
+    let spans = analyze_cron_notation(trimmed);
+    for span in &spans {
+        if let Some(error) = &span.error {
+            return Err(match span.field {
+                Some(field) => format!("{}: {}", field.name(), error),
+                None => error.to_string(),
+            });
+        }
+    }
+    if let Some(missing) = FIELDS.get(spans.len()) {
+        return Err(format!("{}: missing", missing.name()));
+    }
+    Ok(())
+}
+
+/// Rewrites the parts of a valid notation that the schedule parser does not
+/// understand: month names and `7` for Sunday.
+pub fn normalize_cron_notation(cron_notation: &str) -> String {
+    cron_notation
+        .split_whitespace()
+        .enumerate()
+        .map(|(index, token)| match FIELDS.get(index) {
+            Some(CronField::Month) => normalize_items(token, false, |value| {
+                MONTH_NAMES
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(value))
+                    .map(|position| (position + 1).to_string())
+            }),
+            Some(CronField::DayOfWeek) => {
+                normalize_items(token, true, |value| (value == "7").then(|| "0".to_string()))
+            }
+            _ => token.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_items(
+    token: &str,
+    wrap_sunday: bool,
+    replace: impl Fn(&str) -> Option<String>,
+) -> String {
+    token
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+            let range = match range.split_once('-') {
+                // A day-of-week range ending on Sunday (`5-7`) becomes `5-6,0`.
+                Some((start, "7")) if wrap_sunday && step.is_none() && start != "7" => {
+                    format!("{}-6,0", replace(start).unwrap_or(start.to_string()))
+                }
+                Some((start, end)) => format!(
+                    "{}-{}",
+                    replace(start).unwrap_or(start.to_string()),
+                    replace(end).unwrap_or(end.to_string())
+                ),
+                None => replace(range).unwrap_or(range.to_string()),
+            };
+            match step {
+                Some(step) => format!("{}/{}", range, step),
+                None => range,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+const CRON_MACROS: [&str; 8] = [
+    "@reboot",
+    "@hourly",
+    "@daily",
+    "@midnight",
+    "@weekly",
+    "@monthly",
+    "@yearly",
+    "@annually",
+];
+
+/// The kind of text a part of a crontab line holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrontabToken {
+    Comment,
+    Metadata,
+    Variable,
+    Value,
+    Schedule(Option<CronField>),
+    Command,
+    Invalid,
+}
+
+/// A part of a crontab line, positions counted in characters.
+pub struct LineSpan {
+    pub start: usize,
+    pub end: usize,
+    pub token: CrontabToken,
+}
+
+/// Classifies the parts of a single crontab line for highlighting and
+/// describes what is wrong with it, if anything.
+pub fn analyze_crontab_line(line: &str) -> (Vec<LineSpan>, Option<String>) {
+    let length = line.chars().count();
+    let trimmed = line.trim_start();
+    let indent = length - trimmed.chars().count();
+    let span = |start, end, token| LineSpan { start, end, token };
+
+    if trimmed.is_empty() {
+        return (Vec::new(), None);
+    }
+
+    if let Some(comment) = trimmed.strip_prefix('#') {
+        let token = match parse_metadata(comment.trim()) {
+            Some(_) => CrontabToken::Metadata,
+            None => CrontabToken::Comment,
+        };
+        return (vec![span(indent, length, token)], None);
+    }
+
+    if let Some((name, _)) = trimmed.split_once('=') {
+        let name = name.trim_end();
+        let is_variable = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_variable {
+            let name_end = indent + name.chars().count();
+            return (
+                vec![
+                    span(indent, name_end, CrontabToken::Variable),
+                    span(name_end, length, CrontabToken::Value),
+                ],
+                None,
+            );
+        }
+    }
+
+    let fields = analyze_cron_notation(line);
+    let command_start = if trimmed.starts_with('@') {
+        1
+    } else {
+        FIELDS.len()
+    };
+    let mut spans = Vec::new();
+    let mut error = None;
+
+    for field_span in fields.iter().take(command_start) {
+        let token = if trimmed.starts_with('@') {
+            let name: String = line
+                .chars()
+                .skip(field_span.start)
+                .take(field_span.end - field_span.start)
+                .collect();
+            if CRON_MACROS.contains(&name.as_str()) {
+                CrontabToken::Schedule(None)
+            } else {
+                error.get_or_insert(format!("unknown schedule '{}'", name));
+                CrontabToken::Invalid
+            }
+        } else {
+            match (&field_span.field, &field_span.error) {
+                (Some(field), Some(field_error)) => {
+                    error.get_or_insert(format!("{}: {}", field.name(), field_error));
+                    CrontabToken::Invalid
+                }
+                (field, _) => CrontabToken::Schedule(*field),
+            }
+        };
+        spans.push(span(field_span.start, field_span.end, token));
+    }
+
+    match fields.get(command_start) {
+        Some(command) => spans.push(span(command.start, length, CrontabToken::Command)),
+        None => {
+            let missing = FIELDS
+                .get(fields.len())
+                .filter(|_| !trimmed.starts_with('@'))
+                .map(|field| format!("{}: missing", field.name()));
+            error.get_or_insert(missing.unwrap_or_else(|| "missing command".to_string()));
+        }
+    }
+
+    (spans, error)
+}
+
+/// Validates every line of a crontab, returning the 1-based line number and
+/// problem of each invalid line.
+pub fn validate_crontab(content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| analyze_crontab_line(line).1.map(|error| (index + 1, error)))
+        .collect()
+}
@@ -0,0 +1,369 @@
+use crate::cron::utils::{get_next_execution, parse_crontab, parse_tags, render_crontab};
+use crate::cron::{CronJob, EditWindowStyles};
+use crate::log::get_data_dir;
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, HighlightSpacing, List, ListState, Paragraph},
+};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tui_textarea::TextArea;
+
+const TEMPLATES_FILE: &str = "cron-templates";
+
+const INFO_TEXT: &str = "(Esc) Cancel | (↓↑) Move | (Enter) Use template";
+const PLACEHOLDER_INFO_TEXT: &str = "(Esc) Cancel | (Enter) Next value";
+
+/// Templates shipped with tuixel: (name, cron notation, job, tags).
+/// `{name}` marks a placeholder that is asked for when the template is used.
+const BUILTIN_TEMPLATES: [(&str, &str, &str, &str); 7] = [
+    (
+        "Renew certificates",
+        "0 3 * * *",
+        "certbot renew --quiet",
+        "web",
+    ),
+    (
+        "Rotate logs",
+        "0 0 * * *",
+        "/usr/sbin/logrotate /etc/logrotate.conf",
+        "logs",
+    ),
+    (
+        "Dump MySQL database {database}",
+        "30 2 * * *",
+        "mysqldump --single-transaction {database} | gzip > {backup_dir}/{database}.sql.gz",
+        "backup,db",
+    ),
+    (
+        "Dump PostgreSQL database {database}",
+        "45 2 * * *",
+        "pg_dump -Fc {database} > {backup_dir}/{database}.dump",
+        "backup,db",
+    ),
+    (
+        "Clean up temporary files",
+        "15 4 * * 0",
+        "find /tmp -type f -mtime +{days} -delete",
+        "cleanup",
+    ),
+    (
+        "Health ping",
+        "*/5 * * * *",
+        "curl -fsS -m 10 --retry 3 -o /dev/null {url}",
+        "monitoring",
+    ),
+    (
+        "Run script",
+        "0 * * * *",
+        "{script} >> /var/log/{name}.log 2>&1",
+        "",
+    ),
+];
+
+pub struct CronTemplate {
+    pub name: String,
+    pub cron_notation: String,
+    pub job: String,
+    pub tags: Vec<String>,
+    pub owner: String,
+    pub builtin: bool,
+}
+
+impl CronTemplate {
+    /// Placeholders of the template in order of first appearance, without
+    /// duplicates.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut placeholders = Vec::new();
+        for text in [&self.name, &self.cron_notation, &self.job] {
+            for placeholder in find_placeholders(text) {
+                if !placeholders.contains(&placeholder) {
+                    placeholders.push(placeholder);
+                }
+            }
+        }
+        placeholders
+    }
+
+    /// Creates a job from the template, replacing every placeholder with its
+    /// value.
+    pub fn to_cron_job(&self, values: &[(String, String)]) -> CronJob {
+        let fill = |text: &str| {
+            values.iter().fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+        };
+        let cron_notation = fill(&self.cron_notation);
+        CronJob {
+            next_execution: get_next_execution(&cron_notation),
+            cron_notation,
+            job: fill(&self.job),
+            job_description: fill(&self.name),
+            tags: self.tags.clone(),
+            owner: self.owner.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Finds `{name}` placeholders. Shell expansions such as `${HOME}` and the
+/// `{}` of `find -exec` are not placeholders.
+fn find_placeholders(text: &str) -> Vec<String> {
+    text.match_indices('{')
+        .filter(|(start, _)| !text[..*start].ends_with('$'))
+        .filter_map(|(start, _)| {
+            let after = &text[start + 1..];
+            let name = &after[..after.find('}')?];
+            let valid =
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then(|| name.to_string())
+        })
+        .collect()
+}
+
+pub fn builtin_templates() -> Vec<CronTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, cron_notation, job, tags)| CronTemplate {
+            name: name.to_string(),
+            cron_notation: cron_notation.to_string(),
+            job: job.to_string(),
+            tags: parse_tags(tags),
+            owner: String::new(),
+            builtin: true,
+        })
+        .collect()
+}
+
+pub fn user_templates_path() -> PathBuf {
+    get_data_dir().join(TEMPLATES_FILE)
+}
+
+/// Reads the user templates. The file uses the crontab format tuixel writes:
+/// the comment above a job is the template name, followed by the optional
+/// `# tuixel:` metadata line.
+pub fn load_user_templates() -> io::Result<Vec<CronTemplate>> {
+    let content = match fs::read_to_string(user_templates_path()) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    Ok(parse_crontab(&content)
+        .into_iter()
+        .filter(|job| !job.job.is_empty())
+        .map(|job| CronTemplate {
+            name: if job.job_description.is_empty() {
+                job.job.clone()
+            } else {
+                job.job_description
+            },
+            cron_notation: job.cron_notation,
+            job: job.job,
+            tags: job.tags,
+            owner: job.owner,
+            builtin: false,
+        })
+        .collect())
+}
+
+/// Appends a job to the user templates file.
+pub fn save_user_template(cron_job: &CronJob) -> io::Result<()> {
+    let path = user_templates_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let template = CronJob {
+        cron_notation: cron_job.cron_notation.clone(),
+        job: cron_job.job.clone(),
+        job_description: cron_job.job_description.clone(),
+        tags: cron_job.tags.clone(),
+        owner: cron_job.owner.clone(),
+        ..Default::default()
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() > 0 {
+        writeln!(file)?;
+    }
+    file.write_all(render_crontab(&[template]).as_bytes())
+}
+
+pub enum TemplateAction {
+    None,
+    Cancel,
+    Use(CronJob),
+}
+
+/// Popup listing the templates. After a template is picked, its placeholders
+/// are asked for one after another.
+pub struct TemplatePicker {
+    styles: EditWindowStyles,
+    templates: Vec<CronTemplate>,
+    state: ListState,
+    placeholders: Vec<String>,
+    values: Vec<(String, String)>,
+    input: TextArea<'static>,
+    error: Option<String>,
+}
+
+impl TemplatePicker {
+    pub fn new() -> Self {
+        let mut templates = builtin_templates();
+        let error = match load_user_templates() {
+            Ok(user_templates) => {
+                templates.extend(user_templates);
+                None
+            }
+            Err(err) => {
+                tracing::error!("Error reading cron templates: {}", err);
+                Some(format!("Error reading user templates: {}", err))
+            }
+        };
+
+        Self {
+            styles: EditWindowStyles::new(),
+            templates,
+            state: ListState::default().with_selected(Some(0)),
+            placeholders: Vec::new(),
+            values: Vec::new(),
+            input: TextArea::default(),
+            error,
+        }
+    }
+
+    fn selected_template(&self) -> Option<&CronTemplate> {
+        self.state.selected().and_then(|i| self.templates.get(i))
+    }
+
+    /// The placeholder currently asked for, if a template was picked.
+    fn current_placeholder(&self) -> Option<&String> {
+        self.placeholders.get(self.values.len())
+    }
+
+    pub fn handle_key(&mut self, key: event::KeyEvent) -> TemplateAction {
+        if key.code == KeyCode::Esc {
+            return TemplateAction::Cancel;
+        }
+
+        if let Some(placeholder) = self.current_placeholder().cloned() {
+            if key.code == KeyCode::Enter {
+                let value = self.input.lines().join("").trim().to_string();
+                if value.is_empty() {
+                    self.error = Some(format!("{} must not be empty", placeholder));
+                    return TemplateAction::None;
+                }
+                self.values.push((placeholder, value));
+                self.error = None;
+                self.input = TextArea::default();
+                return self.finish_if_complete();
+            }
+            self.input.input(key);
+            return TemplateAction::None;
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select_previous(),
+            KeyCode::Enter => {
+                if let Some(template) = self.selected_template() {
+                    self.placeholders = template.placeholders();
+                    self.values.clear();
+                    return self.finish_if_complete();
+                }
+            }
+            _ => {}
+        }
+        TemplateAction::None
+    }
+
+    fn finish_if_complete(&mut self) -> TemplateAction {
+        if self.current_placeholder().is_some() {
+            return TemplateAction::None;
+        }
+        match self.selected_template() {
+            Some(template) => TemplateAction::Use(template.to_cron_job(&self.values)),
+            None => TemplateAction::Cancel,
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Max(20)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Widget::render(Clear, area, buf);
+
+        let block = Block::default()
+            .title(" Cron templates ")
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [list_area, prompt_area, info_area] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .margin(1)
+        .areas(inner);
+
+        let items = self.templates.iter().map(|template| {
+            let origin = if template.builtin { "built-in" } else { "user" };
+            Line::from(vec![
+                Span::styled(format!("{:<40}", template.name), self.styles.title_style),
+                Span::raw(format!(" {:<14}", template.cron_notation)),
+                Span::raw(format!(" {:<9}", origin)),
+                Span::raw(format!(" {}", template.job)),
+            ])
+        });
+        let list = List::new(items)
+            .highlight_style(
+                self.styles
+                    .selected_input_border_style
+                    .add_modifier(Modifier::REVERSED),
+            )
+            .highlight_symbol("▌ ")
+            .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(list, list_area, buf, &mut self.state);
+
+        if let Some(placeholder) = self.current_placeholder().cloned() {
+            let title = match &self.error {
+                Some(error) => format!("Value for {{{}}} ({})", placeholder, error),
+                None => format!("Value for {{{}}}", placeholder),
+            };
+            self.input.set_cursor_line_style(Style::default());
+            self.input.set_cursor_style(self.styles.cursor_style);
+            self.input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.styles.selected_input_border_style)
+                    .title(title),
+            );
+            self.input.render(prompt_area, buf);
+        } else if let Some(error) = &self.error {
+            let message = Paragraph::new(Text::from(error.as_str()))
+                .style(self.styles.invalid_input_style)
+                .block(Block::default().borders(Borders::ALL));
+            Widget::render(message, prompt_area, buf);
+        }
+
+        let info_text = if self.current_placeholder().is_some() {
+            PLACEHOLDER_INFO_TEXT
+        } else {
+            INFO_TEXT
+        };
+        let info = Paragraph::new(info_text)
+            .style(self.styles.footer_style)
+            .centered();
+        Widget::render(info, info_area, buf);
+    }
+}
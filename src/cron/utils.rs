@@ -1,12 +1,35 @@
 use crate::config::CONFIG;
+use crate::cron::backend::CrontabBackend;
+use crate::cron::history::{unwrap_command, wrap_command};
+use crate::cron::syntax::{normalize_cron_notation, validate_cron_notation};
 use crate::cron::CronJob;
-use chrono::Utc;
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use cron_descriptor::cronparser::cron_expression_descriptor;
 use cron_descriptor::cronparser::Options;
 use cron_parser::parse;
-use std::io::{self, BufRead, Write};
+use std::io;
 use std::panic;
-use std::process::{Command, Stdio};
+use tui_textarea::TextArea;
+
+const METADATA_PREFIX: &str = "tuixel:";
+
+/// Cron runs a job on the days matching the day of month or the day of week
+/// when neither field starts with `*`, while the schedule parser wants both
+/// to match. Such a notation is split into one schedule per day field.
+fn day_schedules(cron_expr: &str) -> Vec<String> {
+    let cron_expr = normalize_cron_notation(cron_expr);
+    match cron_expr.split_whitespace().collect::<Vec<_>>()[..] {
+        [minute, hour, day, month, weekday]
+            if !day.starts_with('*') && !weekday.starts_with('*') =>
+        {
+            vec![
+                format!("{} {} {} {} *", minute, hour, day, month),
+                format!("{} {} * {} {}", minute, hour, month, weekday),
+            ]
+        }
+        _ => vec![cron_expr],
+    }
+}
 
 pub fn get_next_execution(cron_expr: &str) -> String {
     let settings = CONFIG.read().unwrap();
@@ -14,10 +37,60 @@ pub fn get_next_execution(cron_expr: &str) -> String {
 
     let now = Utc::now().with_timezone(&timezone);
 
-    match parse(cron_expr, &now) {
-        Ok(next) => format!("{}", next),
-        Err(_) => "Invalid cron expression".to_string(),
+    match day_schedules(cron_expr)
+        .iter()
+        .filter_map(|schedule| parse(schedule, &now).ok())
+        .min()
+    {
+        Some(next) => format!("{}", next),
+        None => "Invalid cron expression".to_string(),
+    }
+}
+
+/// Returns every execution of `cron_expr` between `start` and `end` (both inclusive),
+/// stopping after `limit` runs, and whether later runs were left out.
+pub fn runs_between<Tz: TimeZone>(
+    cron_expr: &str,
+    start: &DateTime<Tz>,
+    end: &DateTime<Tz>,
+    limit: usize,
+) -> (Vec<DateTime<Tz>>, bool) {
+    let mut runs = Vec::new();
+
+    for schedule in day_schedules(cron_expr) {
+        let mut current = start.clone() - Duration::minutes(1);
+        // One run past the limit tells whether the list is complete.
+        for _ in 0..=limit {
+            match parse(&schedule, &current) {
+                Ok(next) if next <= *end => {
+                    runs.push(next.clone());
+                    current = next;
+                }
+                _ => break,
+            }
+        }
     }
+
+    runs.sort();
+    runs.dedup();
+    let truncated = runs.len() > limit;
+    runs.truncate(limit);
+    (runs, truncated)
+}
+
+/// Translates the `@hourly`-style shortcuts into the equivalent five field notation.
+/// `@reboot` has no schedule and yields `None`.
+pub fn expand_cron_macro(cron_expr: &str) -> Option<String> {
+    let expanded = match cron_expr {
+        "@hourly" => "0 * * * *",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@weekly" => "0 0 * * 0",
+        "@monthly" => "0 0 1 * *",
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@reboot" => return None,
+        other => other,
+    };
+    Some(expanded.to_string())
 }
 
 pub fn get_human_readable_cron(cron_expr: &str) -> Result<String, String> {
@@ -46,92 +119,238 @@ pub fn get_human_readable_cron(cron_expr: &str) -> Result<String, String> {
     }
 }
 
-pub fn from_crontab() -> Result<Vec<CronJob>, io::Error> {
-    let output = Command::new("crontab")
-        .arg("-l")
-        .stdout(Stdio::piped())
-        .output()?;
-
-    if !output.status.success() {
-        let stderr_output = String::from_utf8_lossy(&output.stderr);
+/// Parses a structured comment such as `tuixel: tags=backup,db owner=ops` into
+/// its key and value pairs. Returns `None` for ordinary comments.
+pub fn parse_metadata(comment: &str) -> Option<Vec<(String, String)>> {
+    let body = comment.strip_prefix(METADATA_PREFIX)?;
+    Some(
+        body.split_whitespace()
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (pair.to_string(), String::new()),
+            })
+            .collect(),
+    )
+}
 
-        if stderr_output.contains("no crontab for") {
-            return Ok(vec![CronJob::new({
-                CronJob {
-                    cron_notation: "User has no crontab".to_string(),
-                    job: String::new(),
-                    job_description: String::new(),
-                    next_execution: String::new(),
-                }
-            })]);
+/// Copies the known metadata keys onto the job.
+pub fn apply_metadata(cron_job: &mut CronJob, metadata: &[(String, String)]) {
+    for (key, value) in metadata {
+        match key.as_str() {
+            "tags" => cron_job.tags = parse_tags(value),
+            "owner" => cron_job.owner = value.to_string(),
+            "disabled" => cron_job.disabled = true,
+            "maintenance" => {
+                cron_job.disabled = true;
+                cron_job.suspended = true;
+            }
+            _ => {}
         }
+    }
+}
 
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to read crontab",
-        ));
+/// Renders the structured comment for a job, or `None` if it has no metadata.
+pub fn format_metadata(cron_job: &CronJob) -> Option<String> {
+    let mut pairs = Vec::new();
+    if !cron_job.tags.is_empty() {
+        pairs.push(format!("tags={}", cron_job.tags.join(",")));
     }
+    if !cron_job.owner.is_empty() {
+        pairs.push(format!("owner={}", cron_job.owner));
+    }
+    if cron_job.suspended {
+        pairs.push("maintenance".to_string());
+    } else if cron_job.disabled {
+        pairs.push("disabled".to_string());
+    }
+
+    if pairs.is_empty() {
+        None
+    } else {
+        Some(format!("{} {}", METADATA_PREFIX, pairs.join(" ")))
+    }
+}
+
+/// Whether the metadata marks the following commented-out line as a disabled job.
+fn is_disabled(metadata: &[(String, String)]) -> bool {
+    metadata
+        .iter()
+        .any(|(key, _)| key == "disabled" || key == "maintenance")
+}
+
+/// Splits a crontab job line into its cron notation and command. Macro
+/// schedules and environment settings are not job lines.
+fn split_job_line(line: &str) -> Option<(String, String)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    if parts.len() < 6 || parts[0].starts_with('@') || parts[0].contains('=') {
+        return None;
+    }
+
+    Some((parts[..5].join(" "), parts[5..].join(" ")))
+}
+
+/// Splits a comma separated tag list, dropping empty entries and inner whitespace.
+pub fn parse_tags(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("-"))
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
 
-    let reader = io::BufReader::new(&output.stdout[..]);
+pub fn from_crontab(backend: &dyn CrontabBackend) -> Result<Vec<CronJob>, io::Error> {
+    match backend.read()? {
+        Some(content) => Ok(parse_crontab(&content)),
+        None => Ok(vec![CronJob::new({
+            CronJob {
+                cron_notation: "User has no crontab".to_string(),
+                ..Default::default()
+            }
+        })]),
+    }
+}
+
+/// Parses crontab text into jobs, attaching the preceding comment as the
+/// description and applying any structured metadata comment. Lines that are
+/// not jobs are kept verbatim on the next job, or on a trailing entry without
+/// a job.
+pub fn parse_crontab(content: &str) -> Vec<CronJob> {
     let mut cron_jobs = Vec::new();
-    let mut comment: Option<String> = None;
+    let mut comment: Option<&str> = None;
+    let mut metadata: Vec<(String, String)> = Vec::new();
+    let mut verbatim: Vec<String> = Vec::new();
 
-    for line in reader.lines() {
-        let line = line?;
-        let line = line.trim();
+    for raw in content.lines() {
+        let line = raw.trim();
 
         if line.is_empty() {
             continue;
         }
 
-        if line.starts_with('#') {
-            comment = Some(line.trim_start_matches('#').trim().to_string());
-        } else {
-            let parts: Vec<&str> = line.split_whitespace().collect();
+        let (text, commented) = match line.strip_prefix('#') {
+            Some(text) => (text.trim_start_matches('#').trim(), true),
+            None => (line, false),
+        };
 
-            if parts.len() < 6 {
+        if commented {
+            if let Some(parsed) = parse_metadata(text) {
+                metadata = parsed;
+                continue;
+            }
+            let is_job_line = split_job_line(text)
+                .is_some_and(|(cron_notation, _)| validate_cron_notation(&cron_notation).is_ok());
+            if !is_disabled(&metadata) || !is_job_line {
+                verbatim.extend(comment.replace(raw).map(str::to_string));
                 continue;
             }
+        }
+
+        let Some((cron_notation, job)) = split_job_line(text) else {
+            verbatim.extend(comment.take().map(str::to_string));
+            verbatim.push(raw.to_string());
+            continue;
+        };
 
-            let cron_notation = parts[..5].join(" ");
-            let job = parts[5..].join(" ");
-            let modified_next_execution = get_next_execution(&cron_notation);
+        let modified_next_execution = get_next_execution(&cron_notation);
+        let (history, job) = match unwrap_command(&job) {
+            Some((id, command)) => (Some(id), command),
+            None => (None, job),
+        };
 
-            cron_jobs.push(CronJob {
-                cron_notation,
-                job,
-                job_description: comment.take().unwrap_or_else(|| String::new()),
-                next_execution: modified_next_execution,
-            });
-        }
+        let mut cron_job = CronJob {
+            cron_notation,
+            job,
+            job_description: comment
+                .take()
+                .map(|raw| raw.trim().trim_start_matches('#').trim().to_string())
+                .unwrap_or_default(),
+            next_execution: modified_next_execution,
+            history,
+            verbatim: std::mem::take(&mut verbatim),
+            ..Default::default()
+        };
+        apply_metadata(&mut cron_job, &std::mem::take(&mut metadata));
+        cron_jobs.push(cron_job);
+    }
+
+    verbatim.extend(comment.map(str::to_string));
+    if !verbatim.is_empty() {
+        cron_jobs.push(CronJob {
+            verbatim,
+            ..Default::default()
+        });
     }
 
-    Ok(cron_jobs)
+    cron_jobs
 }
 
-pub fn save_to_crontab(cron_jobs: &[CronJob]) -> io::Result<()> {
+pub fn render_crontab(cron_jobs: &[CronJob]) -> String {
     let mut new_crontab = String::new();
 
     for job in cron_jobs {
-        if !job.job.is_empty() {
-            if !new_crontab.is_empty() {
-                new_crontab.push('\n');
-            }
-            if !job.job_description.is_empty() {
-                new_crontab.push_str(&format!("# {}\n", job.job_description));
-            }
-            new_crontab.push_str(&format!("{} {}\n", job.cron_notation, job.job));
+        if job.job.is_empty() && job.verbatim.is_empty() {
+            continue;
+        }
+        if !new_crontab.is_empty() {
+            new_crontab.push('\n');
+        }
+        for line in &job.verbatim {
+            new_crontab.push_str(&format!("{}\n", line));
+        }
+        if job.job.is_empty() {
+            continue;
         }
+        if !job.job_description.is_empty() {
+            new_crontab.push_str(&format!("# {}\n", job.job_description));
+        }
+        if let Some(metadata) = format_metadata(job) {
+            new_crontab.push_str(&format!("# {}\n", metadata));
+        }
+        if job.disabled {
+            new_crontab.push('#');
+        }
+        let command = match &job.history {
+            Some(id) => wrap_command(id, &job.job),
+            None => job.job.clone(),
+        };
+        new_crontab.push_str(&format!("{} {}\n", job.cron_notation, command));
     }
 
-    let mut process = Command::new("crontab").stdin(Stdio::piped()).spawn()?;
+    new_crontab
+}
 
-    if let Some(stdin) = process.stdin.as_mut() {
-        stdin.write_all(new_crontab.as_bytes())?;
+/// Removes a job, handing its verbatim lines on to the next entry so that
+/// environment settings and macro lines survive the deletion.
+pub fn remove_job(cron_jobs: &mut Vec<CronJob>, index: usize) {
+    let removed = cron_jobs.remove(index);
+    if removed.verbatim.is_empty() {
+        return;
+    }
+    match cron_jobs.get_mut(index) {
+        Some(next) => {
+            let mut verbatim = removed.verbatim;
+            verbatim.append(&mut next.verbatim);
+            next.verbatim = verbatim;
+        }
+        None => cron_jobs.push(CronJob {
+            verbatim: removed.verbatim,
+            ..Default::default()
+        }),
     }
+}
+
+/// The trimmed first line of a single line input.
+pub fn first_line<'a>(textarea: &'a TextArea) -> &'a str {
+    textarea
+        .lines()
+        .first()
+        .map(|line| line.trim())
+        .unwrap_or("")
+}
 
-    process.wait()?;
-    Ok(())
+pub fn save_to_crontab(backend: &dyn CrontabBackend, cron_jobs: &[CronJob]) -> io::Result<()> {
+    backend.write(&render_crontab(cron_jobs))
 }
 
 // #[cfg(test)]
@@ -154,3 +373,106 @@ pub fn save_to_crontab(cron_jobs: &[CronJob]) -> io::Result<()> {
 //         );
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    const CRONTAB: &str = "\
+SHELL=/bin/bash
+MAILTO=ops@example.com
+# Nightly backup
+# tuixel: tags=backup owner=ops
+0 2 * * * /usr/local/bin/backup.sh
+
+@reboot /usr/local/bin/warmup a b c d e
+# a note about the cache
+*/15 * * * * /usr/bin/flush-cache
+
+# Disabled report
+# tuixel: disabled
+#30 6 * * 1 /usr/bin/report
+
+@daily /usr/bin/rotate
+# trailing note
+";
+
+    #[test]
+    fn render_keeps_unmodeled_lines_in_order() {
+        let jobs = parse_crontab(CRONTAB);
+        let commands: Vec<&str> = jobs.iter().map(|job| job.job.as_str()).collect();
+        assert_eq!(
+            commands,
+            [
+                "/usr/local/bin/backup.sh",
+                "/usr/bin/flush-cache",
+                "/usr/bin/report",
+                ""
+            ]
+        );
+        assert_eq!(
+            jobs[0].verbatim,
+            ["SHELL=/bin/bash", "MAILTO=ops@example.com"]
+        );
+        assert_eq!(
+            jobs[1].verbatim,
+            ["@reboot /usr/local/bin/warmup a b c d e"]
+        );
+        assert_eq!(jobs[1].job_description, "a note about the cache");
+        assert!(jobs[2].disabled);
+
+        assert_eq!(render_crontab(&jobs), CRONTAB);
+    }
+
+    fn october() -> (DateTime<chrono_tz::Tz>, DateTime<chrono_tz::Tz>) {
+        let timezone = chrono_tz::UTC;
+        (
+            timezone.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+            timezone.with_ymd_and_hms(2026, 10, 31, 23, 59, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_day() {
+        let (start, end) = october();
+
+        let (runs, truncated) = runs_between("0 0 1 * 1", &start, &end, 100);
+        let days: Vec<u32> = runs.iter().map(|run| run.day()).collect();
+        assert_eq!(days, [1, 5, 12, 19, 26]);
+        assert!(!truncated);
+
+        // With a day field starting with `*` cron wants both to match.
+        let (runs, _) = runs_between("0 0 */10 * 3", &start, &end, 100);
+        let days: Vec<u32> = runs.iter().map(|run| run.day()).collect();
+        assert_eq!(days, [21]);
+    }
+
+    #[test]
+    fn runs_between_reports_the_limit() {
+        let (start, _) = october();
+        let end = start + Duration::days(1);
+
+        let (runs, truncated) = runs_between("* * * * *", &start, &end, 10);
+        assert_eq!(runs.len(), 10);
+        assert!(truncated);
+
+        let (runs, truncated) = runs_between("* * * * *", &start, &end, 2_000);
+        assert_eq!(runs.len(), 1_441);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn remove_job_keeps_its_verbatim_lines() {
+        let mut jobs = parse_crontab(CRONTAB);
+        remove_job(&mut jobs, 0);
+        remove_job(&mut jobs, 0);
+
+        let rendered = render_crontab(&jobs);
+        assert!(rendered.starts_with(
+            "SHELL=/bin/bash\nMAILTO=ops@example.com\n@reboot /usr/local/bin/warmup a b c d e\n"
+        ));
+        assert!(!rendered.contains("backup.sh"));
+        assert!(!rendered.contains("flush-cache"));
+    }
+}
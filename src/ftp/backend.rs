@@ -0,0 +1,629 @@
+use crate::ftp::{
+    FtpUser, ProftpdBackend, Protocol, PureftpdBackend, SftpBackend, TlsConfig, VsftpdBackend,
+};
+use crate::log::PROJECT_NAME;
+use chrono::{DateTime, Days, NaiveDate};
+use lazy_static::lazy_static;
+use pwhash::sha512_crypt;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io::{self, Write};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PASSWD: &str = "/etc/passwd";
+const GROUP: &str = "/etc/group";
+const SHADOW: &str = "/etc/shadow";
+/// Field of `/etc/shadow` with the expiry date, in days since 1970-01-01.
+const SHADOW_EXPIRE: usize = 7;
+/// Account the files of virtual users belong to when nothing else is known.
+const FTP_SYSTEM_USER: &str = "ftp";
+const NOBODY_ID: u32 = 65534;
+/// Backend names with the server binary that shows the server is installed.
+const SERVERS: [(&str, &str); 3] = [
+    ("vsftpd", "vsftpd"),
+    ("proftpd", "proftpd"),
+    ("pureftpd", "pure-ftpd"),
+];
+/// Entries visited by `directory_size` before it stops counting.
+const MAX_SIZE_ENTRIES: usize = 50_000;
+/// Daemons are often outside the `PATH` of ordinary users.
+const SBIN_DIRS: [&str; 3] = ["/usr/sbin", "/usr/local/sbin", "/sbin"];
+
+lazy_static! {
+    pub static ref BACKEND_ENV: String = format!("{}_FTP_BACKEND", PROJECT_NAME.clone());
+}
+
+/// A per-user limit of `FtpLimits`, for asking a backend whether it can
+/// enforce it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    QuotaSize,
+    UploadRate,
+    DownloadRate,
+    MaxSessions,
+}
+
+/// The account storage of an FTP server.
+pub trait FtpBackend {
+    /// Returns the FTP accounts.
+    fn load_users(&self) -> io::Result<Vec<FtpUser>>;
+
+    /// Creates `user`, setting `password` when given.
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()>;
+
+    /// Writes the changes to the user previously called `old_username`. The
+    /// password is only changed when `password` is given.
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()>;
+
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()>;
+
+    /// Locks or unlocks `user` without touching its password, so a
+    /// suspended account can log in again with it.
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()>;
+
+    /// A short label for the UI, e.g. `vsftpd (/etc/vsftpd.conf)`.
+    fn describe(&self) -> String;
+
+    /// Problems the server will have with the document root of `user` when
+    /// it is owned by `owner`:`group` with `mode`.
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+        let _ = (user, owner, group, mode);
+        Vec::new()
+    }
+
+    /// Whether the server can enforce `limit` per user. Limits it cannot are
+    /// not offered in the editor.
+    fn supports_limit(&self, limit: Limit) -> bool {
+        let _ = limit;
+        false
+    }
+
+    /// Whether the server can make accounts of `protocol` expire on
+    /// `FtpUser::expires`.
+    fn supports_expiry(&self, protocol: Protocol) -> bool {
+        let _ = protocol;
+        false
+    }
+
+    /// The bytes stored in the document root of `user` and whether the count
+    /// is complete.
+    fn used_space(&self, user: &FtpUser) -> Option<(u64, bool)> {
+        directory_size(Path::new(&user.doc_root))
+    }
+
+    /// The protocols new users can be created with.
+    fn protocols(&self) -> Vec<Protocol> {
+        vec![Protocol::Ftp]
+    }
+
+    /// Where the server reads its FTPS certificate and key from.
+    fn tls_config(&self) -> io::Result<TlsConfig> {
+        Err(io::Error::other(format!(
+            "{} has no TLS settings",
+            self.describe()
+        )))
+    }
+}
+
+/// The FTP backend together with the SFTP-only accounts of `sshd_config`,
+/// when the file exists. Calls about a user go to the backend of its
+/// protocol.
+pub struct ProtocolBackends {
+    ftp: Box<dyn FtpBackend>,
+    sftp: Option<SftpBackend>,
+}
+
+impl ProtocolBackends {
+    pub fn from_env() -> Self {
+        Self {
+            ftp: backend_from_env(),
+            sftp: SftpBackend::from_env(),
+        }
+    }
+
+    fn backend(&self, protocol: Protocol) -> io::Result<&dyn FtpBackend> {
+        match (protocol, &self.sftp) {
+            (Protocol::Ftp, _) => Ok(self.ftp.as_ref()),
+            (Protocol::Sftp, Some(sftp)) => Ok(sftp),
+            (Protocol::Sftp, None) => Err(io::Error::other("no sshd_config found for SFTP")),
+        }
+    }
+}
+
+impl FtpBackend for ProtocolBackends {
+    fn load_users(&self) -> io::Result<Vec<FtpUser>> {
+        let mut users = self.ftp.load_users()?;
+        if let Some(sftp) = &self.sftp {
+            // Unreadable SFTP accounts should not hide the FTP ones.
+            match sftp.load_users() {
+                Ok(sftp_users) => users.extend(sftp_users),
+                Err(err) => tracing::error!("Error reading SFTP users: {}", err),
+            }
+        }
+        Ok(users)
+    }
+
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()> {
+        self.backend(user.protocol)?.add_user(user, password)
+    }
+
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        self.backend(user.protocol)?
+            .update_user(old_username, user, password)
+    }
+
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()> {
+        self.backend(user.protocol)?.delete_user(user)
+    }
+
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()> {
+        self.backend(user.protocol)?.set_locked(user, locked)
+    }
+
+    fn describe(&self) -> String {
+        match &self.sftp {
+            Some(sftp) => format!("{}, {}", self.ftp.describe(), sftp.describe()),
+            None => self.ftp.describe(),
+        }
+    }
+
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+        self.backend(user.protocol)
+            .map(|backend| backend.doc_root_warnings(user, owner, group, mode))
+            .unwrap_or_default()
+    }
+
+    /// The limits of the FTP server; SFTP accounts have none.
+    fn supports_limit(&self, limit: Limit) -> bool {
+        self.ftp.supports_limit(limit)
+    }
+
+    fn supports_expiry(&self, protocol: Protocol) -> bool {
+        self.backend(protocol)
+            .is_ok_and(|backend| backend.supports_expiry(protocol))
+    }
+
+    fn used_space(&self, user: &FtpUser) -> Option<(u64, bool)> {
+        self.backend(user.protocol).ok()?.used_space(user)
+    }
+
+    fn protocols(&self) -> Vec<Protocol> {
+        let mut protocols = self.ftp.protocols();
+        if self.sftp.is_some() {
+            protocols.push(Protocol::Sftp);
+        }
+        protocols
+    }
+
+    /// SFTP runs over SSH, so only the FTP server has a certificate.
+    fn tls_config(&self) -> io::Result<TlsConfig> {
+        self.ftp.tls_config()
+    }
+}
+
+/// Picks the backend from the `TUIXEL_FTP_BACKEND` environment variable:
+/// `vsftpd`, `proftpd` or `pureftpd`. Without it the installed server is
+/// detected, falling back to vsftpd.
+pub fn backend_from_env() -> Box<dyn FtpBackend> {
+    let server = std::env::var(BACKEND_ENV.clone())
+        .ok()
+        .or_else(|| detect_server().map(str::to_string));
+
+    match server.as_deref() {
+        Some("proftpd") => Box::new(ProftpdBackend::from_env()),
+        Some("pureftpd") | Some("pure-ftpd") => Box::new(PureftpdBackend::from_env()),
+        _ => Box::new(VsftpdBackend::from_env()),
+    }
+}
+
+/// The backend name of the first FTP server binary found in `PATH` or the
+/// usual `sbin` directories.
+pub fn detect_server() -> Option<&'static str> {
+    SERVERS
+        .iter()
+        .find(|(_, binary)| find_binary(binary).is_some())
+        .map(|(name, _)| *name)
+}
+
+/// The path of `binary` in `PATH` or the usual `sbin` directories.
+pub fn find_binary(binary: &str) -> Option<PathBuf> {
+    let path = std::env::var("PATH").unwrap_or_default();
+    let found = path
+        .split(':')
+        .chain(SBIN_DIRS)
+        .filter(|directory| !directory.is_empty())
+        .map(|directory| Path::new(directory).join(binary))
+        .find(|candidate| candidate.is_file());
+    found
+}
+
+/// A line of `/etc/passwd`, or of a file in the same format such as a
+/// ProFTPD `AuthUserFile`.
+#[derive(Clone)]
+pub struct PasswdEntry {
+    pub name: String,
+    pub password: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub gecos: String,
+    pub home: String,
+    pub shell: String,
+}
+
+impl PasswdEntry {
+    pub fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split(':').collect();
+        let [name, password, uid, gid, gecos, home, shell] = fields[..] else {
+            return None;
+        };
+        Some(Self {
+            name: name.to_string(),
+            password: password.to_string(),
+            uid: uid.parse().ok()?,
+            gid: gid.parse().ok()?,
+            gecos: gecos.to_string(),
+            home: home.to_string(),
+            shell: shell.to_string(),
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}",
+            self.name, self.password, self.uid, self.gid, self.gecos, self.home, self.shell
+        )
+    }
+}
+
+/// The `/etc/passwd` entry of `user`.
+pub fn system_user(user: &str) -> Option<PasswdEntry> {
+    let passwd = fs::read_to_string(PASSWD).ok()?;
+    passwd
+        .lines()
+        .filter_map(PasswdEntry::parse)
+        .find(|entry| entry.name == user)
+}
+
+/// Names of the users in `/etc/passwd` by uid.
+pub fn user_names() -> HashMap<u32, String> {
+    fs::read_to_string(PASSWD)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(PasswdEntry::parse)
+        .map(|entry| (entry.uid, entry.name))
+        .collect()
+}
+
+/// Names of the groups in `/etc/group` by gid.
+pub fn group_names() -> HashMap<u32, String> {
+    fs::read_to_string(GROUP)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.get(2)?.parse().ok()?, fields.first()?.to_string()))
+        })
+        .collect()
+}
+
+/// The id of a group from `/etc/group`.
+pub fn group_id(name: &str) -> Option<u32> {
+    let group = fs::read_to_string(GROUP).ok()?;
+    group.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.first() == Some(&name)).then(|| fields.get(2)?.parse().ok())?
+    })
+}
+
+/// The primary group of `user` and every group listing it as a member.
+pub fn user_groups(user: &str) -> Vec<u32> {
+    let mut groups: Vec<u32> = system_user(user)
+        .map(|entry| entry.gid)
+        .into_iter()
+        .collect();
+    if let Ok(group) = fs::read_to_string(GROUP) {
+        groups.extend(group.lines().filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            let is_member = fields.get(3)?.split(',').any(|member| member == user);
+            if is_member {
+                fields.get(2)?.parse::<u32>().ok()
+            } else {
+                None
+            }
+        }));
+    }
+    groups
+}
+
+/// The users of the group called `name`: the members listed in
+/// `/etc/group` and the users having it as primary group.
+pub fn group_members(name: &str) -> Vec<String> {
+    let Some(gid) = group_id(name) else {
+        return Vec::new();
+    };
+    let mut members: Vec<String> = fs::read_to_string(GROUP)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            (fields.first() == Some(&name)).then(|| fields.get(3).copied())?
+        })
+        .flat_map(|list| list.split(','))
+        .filter(|member| !member.is_empty())
+        .map(str::to_string)
+        .collect();
+    for entry in fs::read_to_string(PASSWD)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(PasswdEntry::parse)
+    {
+        if entry.gid == gid && !members.contains(&entry.name) {
+            members.push(entry.name);
+        }
+    }
+    members
+}
+
+/// Whether `uid` with `groups` gets `access` (`4` read, `2` write, `1`
+/// execute) on a file owned by `owner`:`group` with `mode`. Only the owner,
+/// group or other class that applies is checked, as the kernel does; root
+/// gets every access. ACLs are not considered.
+pub fn has_access(
+    uid: u32,
+    groups: &[u32],
+    owner: u32,
+    group: u32,
+    mode: u32,
+    access: u32,
+) -> bool {
+    let shift = if uid == owner {
+        6
+    } else if groups.contains(&group) {
+        3
+    } else {
+        0
+    };
+    uid == 0 || (mode >> shift) & access == access
+}
+
+/// Whether `uid` with `groups` may write to a directory owned by
+/// `owner`:`group` with `mode`. Root may always write.
+pub fn is_writable(uid: u32, groups: &[u32], owner: u32, group: u32, mode: u32) -> bool {
+    has_access(uid, groups, owner, group, mode, 0o2)
+}
+
+/// Warns when the system account of a virtual user cannot write to its
+/// document root, since every upload then fails with `550`.
+pub fn upload_warnings(user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+    let (uid, gid) = match (user.uid, user.gid) {
+        (Some(uid), Some(gid)) => (uid, gid),
+        _ => return Vec::new(),
+    };
+    if is_writable(uid, &[gid], owner, group, mode) {
+        Vec::new()
+    } else {
+        vec![format!(
+            "uid {} cannot write to the document root, so uploads will fail",
+            uid
+        )]
+    }
+}
+
+/// Adds up the sizes of the files below `path`, giving up after
+/// `MAX_SIZE_ENTRIES` entries so huge trees do not stall the screen. Returns
+/// `None` when `path` is not a directory.
+pub fn directory_size(path: &Path) -> Option<(u64, bool)> {
+    if !path.is_dir() {
+        return None;
+    }
+    let mut total = 0;
+    let mut entries = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(directory) = pending.pop() {
+        let Ok(read_dir) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(Result::ok) {
+            entries += 1;
+            if entries > MAX_SIZE_ENTRIES {
+                return Some((total, false));
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+    Some((total, true))
+}
+
+/// Whether the password of a system account is locked, and the day it
+/// expires on, from `/etc/shadow`. Only root can read it, so others see
+/// every account as unlocked and without expiry.
+pub fn shadow_status(user: &str) -> (bool, Option<NaiveDate>) {
+    let Ok(content) = fs::read_to_string(SHADOW) else {
+        return (false, None);
+    };
+    let Some(fields) = content
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields[0] == user)
+    else {
+        return (false, None);
+    };
+    let locked = fields.get(1).is_some_and(|hash| hash.starts_with('!'));
+    let expires = fields
+        .get(SHADOW_EXPIRE)
+        .and_then(|days| days.parse::<u64>().ok())
+        .and_then(|days| {
+            DateTime::UNIX_EPOCH
+                .date_naive()
+                .checked_add_days(Days::new(days))
+        });
+    (locked, expires)
+}
+
+/// Runs an account tool such as `useradd`, turning a failure into an error
+/// with its output.
+pub fn run_tool(program: &str, args: &[&str]) -> io::Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| io::Error::other(format!("{} failed: {}", program, err)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Locks the password of a system account with `usermod`, which puts a `!`
+/// in front of the hash and takes it away again on unlocking.
+pub fn set_system_locked(user: &str, locked: bool) -> io::Result<()> {
+    let flag = if locked { "--lock" } else { "--unlock" };
+    run_tool("usermod", &[flag, user])
+}
+
+/// Sets the day a system account expires on with `chage`, which PAM and sshd
+/// enforce. Nothing runs when the date is already set.
+pub fn set_system_expiry(user: &str, expires: Option<NaiveDate>) -> io::Result<()> {
+    if shadow_status(user).1 == expires {
+        return Ok(());
+    }
+    let date = match expires {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "-1".to_string(),
+    };
+    run_tool("chage", &["--expiredate", &date, user])
+}
+
+/// Whether a hash of a password file is locked. A lone `!` stands for a
+/// password that was never set.
+pub fn is_locked_hash(hash: &str) -> bool {
+    hash.strip_prefix('!')
+        .is_some_and(|unlocked| !unlocked.is_empty())
+}
+
+/// Puts a `!` in front of a password hash or takes it away, the way
+/// `ftpasswd --lock` does. A user without a password has nothing to lock,
+/// and unlocking the lone `!` would accept an empty password.
+pub fn lock_hash(hash: &str, locked: bool) -> io::Result<String> {
+    match (hash.strip_prefix('!'), locked) {
+        (Some(""), _) => Err(io::Error::other("no password has been set")),
+        (Some(_), true) | (None, false) => Ok(hash.to_string()),
+        (None, true) => Ok(format!("!{}", hash)),
+        (Some(unlocked), false) => Ok(unlocked.to_string()),
+    }
+}
+
+/// Hashes `password` with SHA-512 crypt (`$6$...`), which ProFTPD and
+/// Pure-FTPd both verify through `crypt(3)`.
+pub fn hash_password(password: &str) -> io::Result<String> {
+    sha512_crypt::hash(password).map_err(|err| io::Error::other(err.to_string()))
+}
+
+/// The uid and gid most virtual users share. Falls back to the `ftp` user,
+/// then to `nobody`.
+pub fn shared_ids(ids: impl Iterator<Item = (u32, u32)>) -> (u32, u32) {
+    let mut counts: HashMap<(u32, u32), usize> = HashMap::new();
+    for ids in ids {
+        *counts.entry(ids).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(ids, count)| (*count, std::cmp::Reverse(*ids)))
+        .map(|(ids, _)| ids)
+        .or_else(|| system_user(FTP_SYSTEM_USER).map(|entry| (entry.uid, entry.gid)))
+        .unwrap_or((NOBODY_ID, NOBODY_ID))
+}
+
+/// Reads the lines of `path`; a missing file has none.
+pub fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content.lines().map(str::to_string).collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes a file holding password hashes through a temporary file renamed
+/// into place. FTP servers refuse world readable password files, so a new
+/// file gets `0600` and an existing one keeps its owner and group bits
+/// without those for others. Ownership of an existing file is kept.
+pub fn write_private_lines(path: &Path, lines: &[String]) -> io::Result<()> {
+    let metadata = fs::metadata(path).ok();
+    let mode = metadata
+        .as_ref()
+        .map(|metadata| metadata.mode() & 0o660)
+        .unwrap_or(0o600);
+    let temporary = temporary_path(path);
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    let write = || -> io::Result<()> {
+        // Private from the start; a looser mode is only set once written.
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&temporary)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        if let Some(metadata) = &metadata {
+            std::os::unix::fs::chown(&temporary, Some(metadata.uid()), Some(metadata.gid()))?;
+        }
+        if mode != 0o600 {
+            fs::set_permissions(&temporary, fs::Permissions::from_mode(mode))?;
+        }
+        fs::rename(&temporary, path)
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&temporary);
+    })
+}
+
+/// A file next to `path` for writing it atomically, named after the whole
+/// file name and a random suffix, so dotted names, concurrent runs and
+/// leftovers of a crashed run do not collide and the name cannot be guessed.
+pub fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tuixel-{:016x}", name, random_suffix()))
+}
+
+/// A random number from the per process random keys of the std hasher,
+/// mixed with the clock so that calls within one process differ as well.
+fn random_suffix() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    std::process::id().hash(&mut hasher);
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,450 @@
+use crate::ftp::backend::{group_names, has_access, system_user, user_groups, user_names};
+use crate::ftp::ftp::format_size;
+use crate::ftp::provision::account_ids;
+use crate::ftp::{BrowserStyles, FtpUser, TableStyles};
+use chrono::{DateTime, Local};
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    text::{Line, Span},
+    widgets::{
+        Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState,
+    },
+};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+const READ: u32 = 0o4;
+const WRITE: u32 = 0o2;
+const EXECUTE: u32 = 0o1;
+
+/// A file or directory shown in the tree.
+struct TreeEntry {
+    path: PathBuf,
+    name: String,
+    depth: usize,
+    is_dir: bool,
+    /// Target of a symbolic link.
+    link: Option<String>,
+    size: u64,
+    owner: u32,
+    group: u32,
+    mode: u32,
+    modified: Option<DateTime<Local>>,
+    /// Whether the FTP user can read and write the entry, including the
+    /// search permission on every directory above it.
+    readable: bool,
+    writable: bool,
+}
+
+/// A tree of the document root of an FTP user that marks what the user's
+/// account may not read or write.
+pub struct FileBrowser {
+    state: TableState,
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    browser_styles: BrowserStyles,
+    root: PathBuf,
+    username: String,
+    uid: u32,
+    groups: Vec<u32>,
+    user_names: HashMap<u32, String>,
+    group_names: HashMap<u32, String>,
+    expanded: HashSet<PathBuf>,
+    entries: Vec<TreeEntry>,
+    message: Option<String>,
+}
+
+impl FileBrowser {
+    pub fn new(user: &FtpUser) -> Self {
+        let (uid, gid) = account_ids(user);
+        // System accounts also get their supplementary groups.
+        let groups = match system_user(&user.username) {
+            Some(entry) if entry.uid == uid => user_groups(&user.username),
+            _ => vec![gid],
+        };
+        let root = PathBuf::from(&user.doc_root);
+        let mut browser = Self {
+            state: TableState::default().with_selected(0),
+            scroll_state: ScrollbarState::new(0),
+            styles: TableStyles::new(),
+            browser_styles: BrowserStyles::new(),
+            expanded: HashSet::from([root.clone()]),
+            root,
+            username: user.username.clone(),
+            uid,
+            groups,
+            user_names: user_names(),
+            group_names: group_names(),
+            entries: Vec::new(),
+            message: None,
+        };
+        browser.reload();
+        browser
+    }
+
+    /// Handles a key press. Returns `false` once the browser is closed.
+    pub fn handle_key(&mut self, key: event::KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Char('j') | KeyCode::Down => {
+                let i = self.state.selected().map_or(0, |i| i + 1);
+                self.select(if i < self.entries.len() { i } else { 0 });
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let i = match self.state.selected() {
+                    Some(i) if i > 0 => i - 1,
+                    _ => self.entries.len().saturating_sub(1),
+                };
+                self.select(i);
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => {
+                self.select(self.entries.len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+                if let Some(entry) = self.selected().filter(|entry| entry.is_dir) {
+                    let path = entry.path.clone();
+                    if !self.expanded.remove(&path) {
+                        self.expanded.insert(path);
+                    }
+                    self.reload();
+                }
+            }
+            KeyCode::Char('h') | KeyCode::Left => self.collapse(),
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Collapses the selected directory, or moves to the directory above.
+    fn collapse(&mut self) {
+        let Some(entry) = self.selected() else {
+            return;
+        };
+        let path = entry.path.clone();
+        if entry.is_dir && self.expanded.remove(&path) {
+            self.reload();
+        } else if let Some(parent) = path.parent() {
+            if let Some(index) = self.entries.iter().position(|entry| entry.path == parent) {
+                self.select(index);
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<&TreeEntry> {
+        self.state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.state.select(Some(index));
+        self.scroll_state =
+            ScrollbarState::new(self.entries.len().saturating_sub(1)).position(index);
+    }
+
+    /// Reads the root and the expanded directories again.
+    fn reload(&mut self) {
+        let selected = self.selected().map(|entry| entry.path.clone());
+        self.entries.clear();
+        let reachable = self.can_reach_root();
+        match self.entry(&self.root, 0, reachable) {
+            Some(root) => {
+                let (readable, writable) = (root.readable, root.writable);
+                self.entries.push(root);
+                self.add_children(&self.root.clone(), 1, reachable && readable);
+                let unreadable = self.entries.iter().filter(|entry| !entry.readable).count();
+                self.message = Some(if !reachable {
+                    format!(
+                        "{} cannot reach {}: a directory above it lacks search (x) permission",
+                        self.username,
+                        self.root.display()
+                    )
+                } else if !writable {
+                    format!(
+                        "{} cannot write to its document root, unreadable entries: {}",
+                        self.username, unreadable
+                    )
+                } else {
+                    format!(
+                        "{} entries shown, unreadable by {}: {}",
+                        self.entries.len(),
+                        self.username,
+                        unreadable
+                    )
+                });
+            }
+            None => {
+                self.message = Some(format!("{} does not exist", self.root.display()));
+            }
+        }
+        let index = selected
+            .and_then(|path| self.entries.iter().position(|entry| entry.path == path))
+            .unwrap_or(0);
+        self.select(index);
+    }
+
+    /// Whether the user may search every directory above the root. A
+    /// chrooted server enters the root as root, but other servers do not.
+    fn can_reach_root(&self) -> bool {
+        self.root.ancestors().skip(1).all(|directory| {
+            fs::metadata(directory).is_ok_and(|metadata| self.permits(&metadata, EXECUTE))
+        })
+    }
+
+    fn permits(&self, metadata: &fs::Metadata, access: u32) -> bool {
+        has_access(
+            self.uid,
+            &self.groups,
+            metadata.uid(),
+            metadata.gid(),
+            metadata.mode(),
+            access,
+        )
+    }
+
+    /// Describes `path`. `reachable` tells whether the user can search every
+    /// directory leading to it.
+    fn entry(&self, path: &Path, depth: usize, reachable: bool) -> Option<TreeEntry> {
+        let link_metadata = fs::symlink_metadata(path).ok()?;
+        let link = link_metadata
+            .file_type()
+            .is_symlink()
+            .then(|| fs::read_link(path).ok())
+            .flatten()
+            .map(|target| target.display().to_string());
+        // Permissions of a link are those of its target.
+        let metadata = fs::metadata(path).unwrap_or_else(|_| link_metadata.clone());
+        let is_dir = metadata.is_dir();
+        let (readable, writable) = if is_dir {
+            (
+                self.permits(&metadata, READ | EXECUTE),
+                self.permits(&metadata, WRITE | EXECUTE),
+            )
+        } else {
+            (
+                self.permits(&metadata, READ),
+                self.permits(&metadata, WRITE),
+            )
+        };
+        let name = if depth == 0 {
+            path.display().to_string()
+        } else {
+            path.file_name()?.to_string_lossy().to_string()
+        };
+        Some(TreeEntry {
+            path: path.to_path_buf(),
+            name,
+            depth,
+            is_dir,
+            link,
+            size: metadata.len(),
+            owner: link_metadata.uid(),
+            group: link_metadata.gid(),
+            mode: link_metadata.mode(),
+            modified: link_metadata.modified().ok().map(DateTime::<Local>::from),
+            readable: reachable && readable,
+            writable: reachable && writable,
+        })
+    }
+
+    /// Adds the entries of `directory`, directories first, descending into
+    /// the expanded ones.
+    fn add_children(&mut self, directory: &Path, depth: usize, reachable: bool) {
+        if !self.expanded.contains(directory) {
+            return;
+        }
+        let Ok(read_dir) = fs::read_dir(directory) else {
+            return;
+        };
+        let mut children: Vec<TreeEntry> = read_dir
+            .filter_map(Result::ok)
+            .filter_map(|child| self.entry(&child.path(), depth, reachable))
+            .collect();
+        children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        for child in children {
+            let path = child.path.clone();
+            // Following links could loop, so only real directories open.
+            let descend = child.is_dir && child.link.is_none();
+            let child_reachable = reachable && child.readable;
+            self.entries.push(child);
+            if descend {
+                self.add_children(&path, depth + 1, child_reachable);
+            }
+        }
+    }
+
+    fn owner_name(&self, entry: &TreeEntry) -> String {
+        let user = self
+            .user_names
+            .get(&entry.owner)
+            .cloned()
+            .unwrap_or_else(|| entry.owner.to_string());
+        let group = self
+            .group_names
+            .get(&entry.group)
+            .cloned()
+            .unwrap_or_else(|| entry.group.to_string());
+        format!("{}:{}", user, group)
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        Widget::render(Clear, area, buf);
+        let [title_area, table_area, message_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let title = Line::from(vec![
+            Span::styled(
+                format!(" {} as {} ", self.root.display(), self.username),
+                self.styles.header_style,
+            ),
+            Span::styled(
+                "  (Esc) Close | (Enter) Open/close | (←) Up | (r) Reload",
+                self.browser_styles.detail_style,
+            ),
+        ]);
+        Widget::render(
+            Paragraph::new(title).style(self.styles.message_style),
+            title_area,
+            buf,
+        );
+        self.render_table(table_area, buf);
+        self.render_scrollbar(table_area, buf);
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, message_area, buf);
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let header = ["Name", "Size", "Owner", "Mode", "Modified", "FTP access"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(1);
+        let rows = self.entries.iter().enumerate().map(|(i, entry)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            let marker = match (entry.is_dir, self.expanded.contains(&entry.path)) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                _ => "  ",
+            };
+            let mut name = format!("{}{}{}", "  ".repeat(entry.depth), marker, entry.name);
+            if let Some(target) = &entry.link {
+                name.push_str(&format!(" -> {}", target));
+            }
+            let name_style = if entry.is_dir {
+                self.browser_styles.directory_style
+            } else {
+                Style::default()
+            };
+            let (access, access_style) = match (entry.readable, entry.writable) {
+                (true, true) => ("read, write".to_string(), Style::default()),
+                (true, false) => (
+                    "⚠ read only".to_string(),
+                    self.browser_styles.unwritable_style,
+                ),
+                (false, true) => (
+                    "⚠ write only".to_string(),
+                    self.browser_styles.unreadable_style,
+                ),
+                (false, false) => (
+                    "⚠ no access".to_string(),
+                    self.browser_styles.unreadable_style,
+                ),
+            };
+            Row::new([
+                Cell::from(Span::styled(name, name_style)),
+                Cell::from(if entry.is_dir {
+                    "-".to_string()
+                } else {
+                    format_size(entry.size)
+                }),
+                Cell::from(self.owner_name(entry)),
+                Cell::from(mode_string(entry.mode)),
+                Cell::from(
+                    entry
+                        .modified
+                        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default(),
+                ),
+                Cell::from(Span::styled(access, access_style)),
+            ])
+            .style(self.styles.row_style.bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(20),
+                Constraint::Length(10),
+                Constraint::Length(16),
+                Constraint::Length(13),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.styles.selected_row_style)
+        .style(self.styles.row_style.bg(self.styles.normal_row_color))
+        .highlight_symbol(" ▌ ")
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+}
+
+/// `ls -l` style permissions, e.g. `drwxr-x---`.
+pub fn mode_string(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        _ => '-',
+    };
+    let mut text = String::from(kind);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & READ != 0 { 'r' } else { '-' });
+        text.push(if bits & WRITE != 0 { 'w' } else { '-' });
+        // setuid, setgid and sticky replace the execute flag.
+        let special = mode & (0o4000 >> (2 - shift / 3)) != 0;
+        text.push(match (bits & EXECUTE != 0, special, shift) {
+            (true, true, 0) => 't',
+            (false, true, 0) => 'T',
+            (true, true, _) => 's',
+            (false, true, _) => 'S',
+            (true, false, _) => 'x',
+            (false, false, _) => '-',
+        });
+    }
+    text
+}
@@ -0,0 +1,777 @@
+use crate::ftp::backend::Limit;
+use crate::ftp::ftp::{format_size, parse_size};
+use crate::ftp::{EditWindowStyles, FtpBackend, FtpLimits, FtpUser, Protocol};
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use tui_textarea::{CursorMove, TextArea};
+
+const INFO_TEXT: &str = "(Esc) Close | (Tab) Next field / complete path | ";
+const SAVE_TEXT: &str = "(Enter) Save and close";
+const MASK_CHAR: char = '•';
+const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Completion candidates listed in the document root title.
+const MAX_CANDIDATES: usize = 5;
+
+lazy_static! {
+    /// The portable user name rule of `useradd`, which the FTP servers share.
+    static ref USERNAME_REGEX: Regex = Regex::new(r"^[a-z_][a-z0-9_.-]*$").unwrap();
+}
+const MAX_USERNAME_LENGTH: usize = 32;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ActiveInput {
+    Username,
+    Protocol,
+    Expires,
+    DocRoot,
+    Password,
+    ConfirmPassword,
+    Quota,
+    UploadRate,
+    DownloadRate,
+    MaxSessions,
+}
+
+impl ActiveInput {
+    pub fn next(&self) -> Self {
+        match self {
+            ActiveInput::Username => ActiveInput::Protocol,
+            ActiveInput::Protocol => ActiveInput::Expires,
+            ActiveInput::Expires => ActiveInput::DocRoot,
+            ActiveInput::DocRoot => ActiveInput::Password,
+            ActiveInput::Password => ActiveInput::ConfirmPassword,
+            ActiveInput::ConfirmPassword => ActiveInput::Quota,
+            ActiveInput::Quota => ActiveInput::UploadRate,
+            ActiveInput::UploadRate => ActiveInput::DownloadRate,
+            ActiveInput::DownloadRate => ActiveInput::MaxSessions,
+            ActiveInput::MaxSessions => ActiveInput::Username,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            ActiveInput::Username => ActiveInput::MaxSessions,
+            ActiveInput::Protocol => ActiveInput::Username,
+            ActiveInput::Expires => ActiveInput::Protocol,
+            ActiveInput::DocRoot => ActiveInput::Expires,
+            ActiveInput::Password => ActiveInput::DocRoot,
+            ActiveInput::ConfirmPassword => ActiveInput::Password,
+            ActiveInput::Quota => ActiveInput::ConfirmPassword,
+            ActiveInput::UploadRate => ActiveInput::Quota,
+            ActiveInput::DownloadRate => ActiveInput::UploadRate,
+            ActiveInput::MaxSessions => ActiveInput::DownloadRate,
+        }
+    }
+
+    /// The limit edited in this field.
+    fn limit(&self) -> Option<Limit> {
+        match self {
+            ActiveInput::Quota => Some(Limit::QuotaSize),
+            ActiveInput::UploadRate => Some(Limit::UploadRate),
+            ActiveInput::DownloadRate => Some(Limit::DownloadRate),
+            ActiveInput::MaxSessions => Some(Limit::MaxSessions),
+            _ => None,
+        }
+    }
+}
+
+/// The add and edit popup of an FTP account.
+pub struct Inputs {
+    styles: EditWindowStyles,
+    pub username: TextArea<'static>,
+    pub expires: TextArea<'static>,
+    pub doc_root: TextArea<'static>,
+    pub password: TextArea<'static>,
+    pub confirm_password: TextArea<'static>,
+    pub quota: TextArea<'static>,
+    pub upload_rate: TextArea<'static>,
+    pub download_rate: TextArea<'static>,
+    pub max_sessions: TextArea<'static>,
+    pub current_input: ActiveInput,
+    pub protocol: Protocol,
+    /// Protocols the backend can create users with.
+    protocols: Vec<Protocol>,
+    /// Limits the backend can enforce; the other limit fields are skipped.
+    supported: Vec<Limit>,
+    /// Whether the backend can make the account expire.
+    expiry_supported: bool,
+    /// The user being edited, `None` for a new user.
+    editing: Option<FtpUser>,
+    /// Directories matching the document root after the last completion.
+    candidates: Vec<String>,
+    /// Error reported by the backend on the last save.
+    error: Option<String>,
+    /// Set once saving was attempted, so empty fields show their errors too.
+    submitted: bool,
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Self {
+            styles: EditWindowStyles::new(),
+            username: TextArea::default(),
+            expires: TextArea::default(),
+            doc_root: TextArea::default(),
+            password: TextArea::default(),
+            confirm_password: TextArea::default(),
+            quota: TextArea::default(),
+            upload_rate: TextArea::default(),
+            download_rate: TextArea::default(),
+            max_sessions: TextArea::default(),
+            current_input: ActiveInput::Username,
+            protocol: Protocol::Ftp,
+            protocols: Vec::new(),
+            supported: Vec::new(),
+            expiry_supported: false,
+            editing: None,
+            candidates: Vec::new(),
+            error: None,
+            submitted: false,
+        }
+    }
+}
+
+impl Inputs {
+    /// Handles a key press while the popup is open. Returns the name of the
+    /// user once it has been saved through `backend`.
+    pub fn handle_inputs(
+        &mut self,
+        key: event::KeyEvent,
+        show_popup: &mut bool,
+        ftp_users: &[FtpUser],
+        backend: &dyn FtpBackend,
+    ) -> Option<String> {
+        match key.code {
+            KeyCode::Tab => {
+                if self.current_input != ActiveInput::DocRoot || !self.complete_doc_root() {
+                    self.current_input = self.current_input.next();
+                    while !self.is_enabled(self.current_input) {
+                        self.current_input = self.current_input.next();
+                    }
+                }
+            }
+            KeyCode::BackTab => {
+                self.current_input = self.current_input.previous();
+                while !self.is_enabled(self.current_input) {
+                    self.current_input = self.current_input.previous();
+                }
+            }
+            KeyCode::Esc => {
+                *show_popup = false;
+            }
+            KeyCode::Enter => {
+                self.submitted = true;
+                if self.validate(ftp_users).is_empty() {
+                    match self.save(backend) {
+                        Ok(username) => {
+                            *show_popup = false;
+                            return Some(username);
+                        }
+                        Err(err) => self.error = Some(err.to_string()),
+                    }
+                }
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                if self.current_input == ActiveInput::Protocol =>
+            {
+                let index = self
+                    .protocols
+                    .iter()
+                    .position(|protocol| *protocol == self.protocol)
+                    .map_or(0, |index| index + 1);
+                self.protocol = self.protocols[index % self.protocols.len()];
+                self.update_limits(backend);
+                self.error = None;
+            }
+            _ => {
+                if self.active_field().is_some_and(|field| field.input(key)) {
+                    self.error = None;
+                    if self.current_input == ActiveInput::DocRoot {
+                        self.candidates.clear();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The text field in focus; the protocol is chosen with the arrow keys.
+    fn active_field(&mut self) -> Option<&mut TextArea<'static>> {
+        match self.current_input {
+            ActiveInput::Username => Some(&mut self.username),
+            ActiveInput::Protocol => None,
+            ActiveInput::Expires => Some(&mut self.expires),
+            ActiveInput::DocRoot => Some(&mut self.doc_root),
+            ActiveInput::Password => Some(&mut self.password),
+            ActiveInput::ConfirmPassword => Some(&mut self.confirm_password),
+            ActiveInput::Quota => Some(&mut self.quota),
+            ActiveInput::UploadRate => Some(&mut self.upload_rate),
+            ActiveInput::DownloadRate => Some(&mut self.download_rate),
+            ActiveInput::MaxSessions => Some(&mut self.max_sessions),
+        }
+    }
+
+    fn is_enabled(&self, input: ActiveInput) -> bool {
+        match input {
+            // The protocol of an existing user cannot change.
+            ActiveInput::Protocol => self.editing.is_none() && self.protocols.len() > 1,
+            ActiveInput::Expires => self.expiry_supported,
+            _ => input
+                .limit()
+                .is_none_or(|limit| self.supported.contains(&limit)),
+        }
+    }
+
+    pub fn init_empty(&mut self, backend: &dyn FtpBackend) {
+        self.editing = None;
+        self.protocol = Protocol::Ftp;
+        self.reset(backend);
+    }
+
+    pub fn init(&mut self, ftp_user: &FtpUser, backend: &dyn FtpBackend) {
+        self.editing = Some(ftp_user.clone());
+        self.protocol = ftp_user.protocol;
+        self.reset(backend);
+        self.username.insert_str(&ftp_user.username);
+        self.doc_root.insert_str(&ftp_user.doc_root);
+        if let Some(expires) = ftp_user.expires {
+            self.expires
+                .insert_str(expires.format(DATE_FORMAT).to_string());
+        }
+
+        let limits = &ftp_user.limits;
+        for (input, value) in [
+            (&mut self.quota, limits.quota_size.map(format_size)),
+            (&mut self.upload_rate, limits.upload_rate.map(format_size)),
+            (
+                &mut self.download_rate,
+                limits.download_rate.map(format_size),
+            ),
+            (
+                &mut self.max_sessions,
+                limits.max_sessions.map(|count| count.to_string()),
+            ),
+        ] {
+            if let Some(value) = value {
+                input.insert_str(value);
+            }
+        }
+    }
+
+    fn reset(&mut self, backend: &dyn FtpBackend) {
+        self.username = TextArea::default();
+        self.expires = TextArea::default();
+        self.doc_root = TextArea::default();
+        self.password = TextArea::default();
+        self.confirm_password = TextArea::default();
+        self.quota = TextArea::default();
+        self.upload_rate = TextArea::default();
+        self.download_rate = TextArea::default();
+        self.max_sessions = TextArea::default();
+        self.protocols = backend.protocols();
+        self.current_input = ActiveInput::Username;
+        self.candidates.clear();
+        self.error = None;
+        self.submitted = false;
+
+        let password_placeholder = if self.editing.is_some() {
+            "Leave empty to keep the current password"
+        } else {
+            "Enter a password"
+        };
+        for (input, placeholder) in [
+            (&mut self.username, "Enter a user name"),
+            (&mut self.expires, ""),
+            (&mut self.doc_root, "Absolute path, e.g. /srv/ftp/alice"),
+            (&mut self.password, password_placeholder),
+            (&mut self.confirm_password, "Repeat the password"),
+            (&mut self.quota, ""),
+            (&mut self.upload_rate, ""),
+            (&mut self.download_rate, ""),
+            (&mut self.max_sessions, ""),
+        ] {
+            input.set_placeholder_text(placeholder);
+            input.set_cursor_line_style(Style::default());
+        }
+        self.update_limits(backend);
+        self.password.set_mask_char(MASK_CHAR);
+        self.confirm_password.set_mask_char(MASK_CHAR);
+    }
+
+    /// Enables the limit and expiry fields the backend supports for the
+    /// chosen protocol. SFTP accounts have no limits.
+    fn update_limits(&mut self, backend: &dyn FtpBackend) {
+        self.expiry_supported = backend.supports_expiry(self.protocol);
+        self.expires.set_placeholder_text(if self.expiry_supported {
+            "YYYY-MM-DD"
+        } else {
+            "not supported"
+        });
+        self.supported = [
+            Limit::QuotaSize,
+            Limit::UploadRate,
+            Limit::DownloadRate,
+            Limit::MaxSessions,
+        ]
+        .into_iter()
+        .filter(|limit| self.protocol == Protocol::Ftp && backend.supports_limit(*limit))
+        .collect();
+        for (kind, example) in [
+            (ActiveInput::Quota, "e.g. 5G"),
+            (ActiveInput::UploadRate, "e.g. 512K"),
+            (ActiveInput::DownloadRate, "e.g. 1M"),
+            (ActiveInput::MaxSessions, "e.g. 3"),
+        ] {
+            let placeholder = if self.is_enabled(kind) {
+                example
+            } else {
+                "not supported"
+            };
+            let input = match kind {
+                ActiveInput::Quota => &mut self.quota,
+                ActiveInput::UploadRate => &mut self.upload_rate,
+                ActiveInput::DownloadRate => &mut self.download_rate,
+                _ => &mut self.max_sessions,
+            };
+            input.set_placeholder_text(placeholder);
+        }
+    }
+
+    /// Completes the document root to the longest prefix shared by the
+    /// matching directories, or lists them when nothing can be added.
+    /// Returns `false` when there was nothing to add and nothing new to show.
+    fn complete_doc_root(&mut self) -> bool {
+        let value = input_value(&self.doc_root).to_string();
+        let (completed, candidates) = complete_path(&value);
+        let already_shown = self.candidates == candidates;
+        self.candidates = candidates;
+        if completed == value {
+            return self.candidates.len() > 1 && !already_shown;
+        }
+        self.doc_root = TextArea::from([completed]);
+        self.doc_root.set_cursor_line_style(Style::default());
+        self.doc_root.move_cursor(CursorMove::End);
+        true
+    }
+
+    /// Returns the problems of each field; an empty list means the user can
+    /// be saved.
+    fn validate(&self, ftp_users: &[FtpUser]) -> Vec<(ActiveInput, String)> {
+        let mut errors = Vec::new();
+        let username = input_value(&self.username).trim();
+        let old_username = self.editing.as_ref().map(|user| user.username.as_str());
+
+        if let Err(message) = validate_username(username) {
+            errors.push((ActiveInput::Username, message));
+        } else if old_username != Some(username)
+            && ftp_users.iter().any(|user| user.username == username)
+        {
+            errors.push((ActiveInput::Username, "already exists".to_string()));
+        }
+
+        if let Err(message) = self.expiry() {
+            errors.push((ActiveInput::Expires, message));
+        }
+
+        let doc_root = input_value(&self.doc_root).trim();
+        if doc_root.is_empty() {
+            errors.push((ActiveInput::DocRoot, "required".to_string()));
+        } else if !doc_root.starts_with('/') {
+            errors.push((ActiveInput::DocRoot, "must be an absolute path".to_string()));
+        } else if doc_root.contains(':') {
+            errors.push((ActiveInput::DocRoot, "must not contain ':'".to_string()));
+        }
+
+        let password = input_value(&self.password);
+        if password.is_empty() && self.editing.is_none() {
+            errors.push((ActiveInput::Password, "required".to_string()));
+        } else if password.contains(':') || password.contains('\n') {
+            errors.push((ActiveInput::Password, "must not contain ':'".to_string()));
+        }
+        if input_value(&self.confirm_password) != password {
+            errors.push((ActiveInput::ConfirmPassword, "does not match".to_string()));
+        }
+
+        if let Err(limit_errors) = self.limits() {
+            errors.extend(limit_errors);
+        }
+
+        errors
+    }
+
+    /// The limits entered; empty fields leave the server default.
+    fn limits(&self) -> Result<FtpLimits, Vec<(ActiveInput, String)>> {
+        let mut errors = Vec::new();
+        let mut size = |input: &TextArea, kind: ActiveInput| {
+            let value = input_value(input).trim();
+            if value.is_empty() || !self.is_enabled(kind) {
+                return None;
+            }
+            match parse_size(value) {
+                Ok(size) if size > 0 => Some(size),
+                Ok(_) => {
+                    errors.push((kind, "must be above 0".to_string()));
+                    None
+                }
+                Err(message) => {
+                    errors.push((kind, message));
+                    None
+                }
+            }
+        };
+        let quota_size = size(&self.quota, ActiveInput::Quota);
+        let upload_rate = size(&self.upload_rate, ActiveInput::UploadRate);
+        let download_rate = size(&self.download_rate, ActiveInput::DownloadRate);
+
+        let sessions = input_value(&self.max_sessions).trim();
+        let max_sessions = if sessions.is_empty() || !self.is_enabled(ActiveInput::MaxSessions) {
+            None
+        } else {
+            match sessions.parse::<u32>() {
+                Ok(count) if count > 0 => Some(count),
+                _ => {
+                    errors.push((ActiveInput::MaxSessions, "a number above 0".to_string()));
+                    None
+                }
+            }
+        };
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(FtpLimits {
+            quota_size,
+            upload_rate,
+            download_rate,
+            max_sessions,
+            // Not edited here; kept as the backend returned it.
+            quota_files: self
+                .editing
+                .as_ref()
+                .and_then(|user| user.limits.quota_files),
+        })
+    }
+
+    /// The expiry date entered; an empty field means the account does not
+    /// expire.
+    fn expiry(&self) -> Result<Option<NaiveDate>, String> {
+        let value = input_value(&self.expires).trim();
+        if value.is_empty() || !self.is_enabled(ActiveInput::Expires) {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map(Some)
+            .map_err(|_| "invalid".to_string())
+    }
+
+    fn save(&self, backend: &dyn FtpBackend) -> std::io::Result<String> {
+        let username = input_value(&self.username).trim();
+        let doc_root = input_value(&self.doc_root).trim();
+        let password = Some(input_value(&self.password)).filter(|password| !password.is_empty());
+        let limits = self.limits().unwrap_or_default();
+        let expires = self.expiry().unwrap_or_default();
+
+        match &self.editing {
+            Some(old_user) => {
+                let user = FtpUser {
+                    username: username.to_string(),
+                    doc_root: doc_root.to_string(),
+                    limits,
+                    expires,
+                    ..old_user.clone()
+                };
+                backend.update_user(&old_user.username, &user, password)?;
+            }
+            None => {
+                let user = FtpUser {
+                    protocol: self.protocol,
+                    limits,
+                    expires,
+                    ..FtpUser::new(username, doc_root)
+                };
+                backend.add_user(&user, password)?;
+            }
+        }
+        Ok(username.to_string())
+    }
+
+    pub fn render_inputs(&mut self, area: Rect, buf: &mut Buffer, ftp_users: &[FtpUser]) {
+        let area = popup_area(area, 70);
+        Widget::render(Clear, area, buf);
+
+        let layout = Layout::vertical([Constraint::Length(21), Constraint::Length(3)])
+            .flex(Flex::SpaceBetween);
+        let [main_area, footer_area] = layout.areas(area);
+
+        let main_block = Block::default()
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+        Widget::render(main_block, main_area, buf);
+
+        let main = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .margin(2)
+        .flex(Flex::Start);
+        let [title_area, username_area, doc_root_area, password_area, limits_area, error_area] =
+            main.areas(main_area);
+        let [username_area, protocol_area, expires_area] = Layout::horizontal([
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ])
+        .areas(username_area);
+        let [password_area, confirm_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(password_area);
+        let [quota_area, upload_area, download_area, sessions_area] =
+            Layout::horizontal([Constraint::Ratio(1, 4); 4]).areas(limits_area);
+
+        let title_text = match &self.editing {
+            Some(user) if user.locked => format!(
+                "Edit {} user {} (locked)",
+                self.protocol.label(),
+                user.username
+            ),
+            Some(user) => format!("Edit {} user {}", self.protocol.label(), user.username),
+            None => format!("New {} user", self.protocol.label()),
+        };
+        let title = Paragraph::new(title_text)
+            .style(self.styles.title_style)
+            .centered()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Double)
+                    .border_style(self.styles.title_border_style),
+            );
+        Widget::render(title, title_area, buf);
+
+        let errors = self.validate(ftp_users);
+        let save_style = if errors.is_empty() {
+            self.styles.footer_style
+        } else {
+            self.styles.disabled_hint_style
+        };
+        let info = Paragraph::new(Text::from(vec![
+            Line::from(""),
+            Line::from(vec![
+                Span::raw(INFO_TEXT),
+                Span::styled(SAVE_TEXT, save_style),
+            ]),
+            Line::from(""),
+        ]))
+        .style(self.styles.footer_style)
+        .centered()
+        .block(Block::default());
+        Widget::render(info, footer_area, buf);
+
+        let candidates = match self.candidates.len() {
+            0 | 1 => String::new(),
+            count => {
+                let shown = self.candidates[..count.min(MAX_CANDIDATES)].join(" ");
+                let more = if count > MAX_CANDIDATES { " …" } else { "" };
+                format!(" [{}{}]", shown, more)
+            }
+        };
+
+        let current_input = self.current_input;
+        for (input, kind, name, input_area) in [
+            (
+                &mut self.username,
+                ActiveInput::Username,
+                "User name*".to_string(),
+                username_area,
+            ),
+            (
+                &mut self.expires,
+                ActiveInput::Expires,
+                "Expires".to_string(),
+                expires_area,
+            ),
+            (
+                &mut self.doc_root,
+                ActiveInput::DocRoot,
+                format!("Document root*{}", candidates),
+                doc_root_area,
+            ),
+            (
+                &mut self.password,
+                ActiveInput::Password,
+                "Password".to_string(),
+                password_area,
+            ),
+            (
+                &mut self.confirm_password,
+                ActiveInput::ConfirmPassword,
+                "Confirm password".to_string(),
+                confirm_area,
+            ),
+            (
+                &mut self.quota,
+                ActiveInput::Quota,
+                "Quota".to_string(),
+                quota_area,
+            ),
+            (
+                &mut self.upload_rate,
+                ActiveInput::UploadRate,
+                "Upload/s".to_string(),
+                upload_area,
+            ),
+            (
+                &mut self.download_rate,
+                ActiveInput::DownloadRate,
+                "Download/s".to_string(),
+                download_area,
+            ),
+            (
+                &mut self.max_sessions,
+                ActiveInput::MaxSessions,
+                "Sessions".to_string(),
+                sessions_area,
+            ),
+        ] {
+            // Empty fields are only marked once focused or after a save attempt.
+            let error = errors
+                .iter()
+                .find(|(field, _)| *field == kind)
+                .filter(|_| {
+                    !input_value(input).is_empty() || kind == current_input || self.submitted
+                })
+                .map(|(_, message)| message);
+            let focused = current_input == kind;
+            let (border_style, cursor_style) = match (error, focused) {
+                (Some(_), true) => (
+                    self.styles.invalid_input_style,
+                    self.styles.invalid_cursor_style,
+                ),
+                (Some(_), false) => (self.styles.invalid_input_style, Style::default()),
+                (None, true) => (
+                    self.styles.selected_input_border_style,
+                    self.styles.cursor_style,
+                ),
+                (None, false) => (self.styles.unselected_input_border_style, Style::default()),
+            };
+            let title = match error {
+                Some(message) => format!("{} ({})", name, message),
+                None => name,
+            };
+            input.set_cursor_style(cursor_style);
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(title),
+            );
+            input.render(input_area, buf);
+        }
+
+        let protocol_text = if self.is_enabled(ActiveInput::Protocol) {
+            format!("◂ {} ▸", self.protocol.label())
+        } else {
+            self.protocol.label().to_string()
+        };
+        let border_style = if current_input == ActiveInput::Protocol {
+            self.styles.selected_input_border_style
+        } else {
+            self.styles.unselected_input_border_style
+        };
+        let protocol = Paragraph::new(protocol_text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title("Protocol"),
+        );
+        Widget::render(protocol, protocol_area, buf);
+
+        if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str())
+                .style(self.styles.error_style)
+                .centered();
+            Widget::render(error, error_area, buf);
+        }
+    }
+}
+
+fn input_value<'a>(textarea: &'a TextArea) -> &'a str {
+    textarea.lines().first().map(|s| s.as_str()).unwrap_or("")
+}
+
+/// Checks a user name against the rules all backends accept.
+pub fn validate_username(username: &str) -> Result<(), String> {
+    if username.is_empty() {
+        Err("required".to_string())
+    } else if username.len() > MAX_USERNAME_LENGTH {
+        Err(format!("at most {} characters", MAX_USERNAME_LENGTH))
+    } else if !USERNAME_REGEX.is_match(username) {
+        Err("lowercase letters, digits, '_', '.' and '-' only".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Completes the last component of `value` against the directories that
+/// exist. Returns the completed value and the names of the matching
+/// directories; a single match gets a trailing `/`.
+pub fn complete_path(value: &str) -> (String, Vec<String>) {
+    let (directory, partial) = match value.rfind('/') {
+        Some(index) => value.split_at(index + 1),
+        None => return (value.to_string(), Vec::new()),
+    };
+    let Ok(entries) = fs::read_dir(Path::new(directory)) else {
+        return (value.to_string(), Vec::new());
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(partial))
+        .filter(|name| !name.starts_with('.') || partial.starts_with('.'))
+        .collect();
+    candidates.sort();
+
+    let completed = match candidates.as_slice() {
+        [] => value.to_string(),
+        [only] => format!("{}{}/", directory, only),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.clone(), |common, name| {
+                common
+                    .chars()
+                    .zip(name.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            });
+            format!("{}{}", directory, common)
+        }
+    };
+    (completed, candidates)
+}
+
+fn popup_area(area: Rect, percent_x: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Max(24)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
@@ -1,6 +1,13 @@
 use crate::app::{Screen, ScreenTrait};
-use crate::ftp::TableStyles;
+use crate::ftp::backend::ProtocolBackends;
+use crate::ftp::import::{export_csv, ImportAction};
+use crate::ftp::provision::{ProvisionAction, RemovalAction};
+use crate::ftp::{
+    FileBrowser, FtpBackend, FtpStats, ImportPopup, Inputs, ProvisionPopup, RemovalPopup,
+    TableStyles, TlsPopup, VsftpdSettings,
+};
 use crate::menu::MainMenu;
+use chrono::{Local, NaiveDate};
 use ratatui::{
     crossterm::event::{self, KeyCode, MouseEvent},
     layout::{Constraint, Layout, Margin, Rect},
@@ -19,21 +26,138 @@ const INFO_TEXT: [&str; 3] = [
     "",
 ];
 const ITEM_HEIGHT: usize = 4;
+/// Wide enough for the "Protocol" header.
+const PROTOCOL_WIDTH: u16 = 9;
+
+/// Per-user limits. `None` leaves the server default in place.
+#[derive(Clone, Default)]
+pub struct FtpLimits {
+    /// Disk quota in bytes.
+    pub quota_size: Option<u64>,
+    pub quota_files: Option<u64>,
+    /// Transfer rates in bytes per second.
+    pub upload_rate: Option<u64>,
+    pub download_rate: Option<u64>,
+    pub max_sessions: Option<u32>,
+}
 
+const SIZE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// Formats a byte count with a binary unit, e.g. `1.5 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < SIZE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, SIZE_UNITS[unit])
+    }
+}
+
+/// Parses a size such as `1048576`, `512K`, `1.5G` or `10 MiB`. Units are
+/// binary, so `1K` is 1024 bytes.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("{} is not a size like 500M", value))?;
+    let exponent = match unit
+        .trim()
+        .to_uppercase()
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+    {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return Err(format!("unknown unit {}", unit.trim())),
+    };
+    Ok((number * 1024f64.powi(exponent)).round() as u64)
+}
+
+/// How an account transfers files: through the FTP server, or as an
+/// SFTP-only account of `sshd_config`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Protocol {
+    Ftp,
+    Sftp,
+}
+
+impl Protocol {
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Protocol::Ftp => "FTP",
+            Protocol::Sftp => "SFTP",
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct FtpUser {
     pub username: String,
     pub doc_root: String,
+    pub protocol: Protocol,
+    /// System account the files are accessed as, for backends with virtual users.
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub shell: Option<String>,
+    pub limits: FtpLimits,
+    /// Client addresses or networks the user may log in from, all when empty.
+    pub allowed_ips: Vec<String>,
+    pub denied_ips: Vec<String>,
+    /// The day the server starts refusing the account.
+    pub expires: Option<NaiveDate>,
+    /// Refused by the server while the password is kept.
+    pub locked: bool,
 }
 
 impl FtpUser {
-    const fn ref_array(&self) -> [&String; 2] {
-        [&self.username, &self.doc_root]
+    fn ref_array(&self) -> [&str; 3] {
+        [&self.username, self.protocol.label(), &self.doc_root]
     }
 
     pub fn new(username: &str, doc_root: &str) -> Self {
         Self {
             username: username.to_string(),
             doc_root: doc_root.to_string(),
+            protocol: Protocol::Ftp,
+            uid: None,
+            gid: None,
+            shell: None,
+            limits: FtpLimits::default(),
+            allowed_ips: Vec::new(),
+            denied_ips: Vec::new(),
+            expires: None,
+            locked: false,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= Local::now().date_naive())
+    }
+
+    /// The lock and expiry state shown under the user name.
+    fn status(&self) -> String {
+        let expiry = match self.expires {
+            Some(expires) if self.is_expired() => format!("expired {}", expires),
+            Some(expires) => format!("expires {}", expires),
+            None => String::new(),
+        };
+        match (self.locked, expiry.is_empty()) {
+            (true, true) => "locked".to_string(),
+            (true, false) => format!("locked, {}", expiry),
+            (false, _) => expiry,
         }
     }
 }
@@ -41,20 +165,33 @@ impl FtpUser {
 pub struct FtpTable {
     state: TableState,
     items: Vec<FtpUser>,
-    longest_item_lens: (u16, u16),
+    /// Quota use of each user, in the order of `items`.
+    usage: Vec<String>,
+    longest_item_lens: (u16, u16, u16),
     scroll_state: ScrollbarState,
     styles: TableStyles,
     show_popup: bool,
+    inputs: Inputs,
+    provision: Option<ProvisionPopup>,
+    removal: Option<RemovalPopup>,
+    browser: Option<FileBrowser>,
+    import: Option<ImportPopup>,
+    tls: Option<TlsPopup>,
+    backend: Box<dyn FtpBackend>,
+    message: Option<String>,
 }
 
 impl ScreenTrait for FtpTable {
     fn new() -> Self {
-        let ftp_users = vec![
-            FtpUser::new("alice", "/var/ftp/alice"),
-            FtpUser::new("bob", "/var/ftp/bob"),
-            FtpUser::new("charlie", "/var/ftp/charlie"),
-        ];
-        let longest_item_lens = constraint_len_calculator(&ftp_users);
+        let backend: Box<dyn FtpBackend> = Box::new(ProtocolBackends::from_env());
+        let mut message = Some(backend.describe());
+        let ftp_users = backend.load_users().unwrap_or_else(|err| {
+            tracing::error!("Error reading FTP users: {}", err);
+            message = Some(format!("Error reading FTP users: {}", err));
+            Vec::new()
+        });
+        let usage = quota_usage(backend.as_ref(), &ftp_users);
+        let longest_item_lens = constraint_len_calculator(&ftp_users, &usage);
         let scroll_position = if ftp_users.is_empty() {
             0
         } else {
@@ -63,21 +200,38 @@ impl ScreenTrait for FtpTable {
         Self {
             state: TableState::default().with_selected(0),
             items: ftp_users,
+            usage,
             longest_item_lens,
             scroll_state: ScrollbarState::new(scroll_position),
             styles: TableStyles::new(),
             show_popup: false,
+            inputs: Inputs::default(),
+            provision: None,
+            removal: None,
+            browser: None,
+            import: None,
+            tls: None,
+            backend,
+            message,
         }
     }
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let vertical = Layout::vertical([Constraint::Min(1), Constraint::Length(2)]);
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+        ]);
         let rects = vertical.split(area);
 
         self.render_table(rects[0], buf);
         self.render_scrollbar(rects[0], buf);
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, rects[1], buf);
         self.render_footer(
-            rects[1],
+            rects[2],
             buf,
             vec![
                 ("<Esc>", "Return to the main menu"),
@@ -85,11 +239,33 @@ impl ScreenTrait for FtpTable {
                 ("<↓↑>", "Move up and down"),
                 ("<d>", "Delete selected user"),
                 ("<n>", "Add new user"),
+                ("<l>", "Lock/unlock user"),
+                ("<b>", "Browse files"),
+                ("<i>", "Import CSV"),
+                ("<e>", "Export CSV"),
+                ("<t>", "FTPS certificate"),
+                ("<s>", "Transfer statistics"),
+                ("<c>", "vsftpd settings"),
             ],
         );
 
         if self.show_popup {
-            //self.render_popup(rects[0], buf);
+            self.inputs.render_inputs(rects[0], buf, &self.items);
+        }
+        if let Some(provision) = &mut self.provision {
+            provision.render(rects[0], buf, self.backend.as_ref());
+        }
+        if let Some(removal) = &self.removal {
+            removal.render(rects[0], buf);
+        }
+        if let Some(browser) = &mut self.browser {
+            browser.render(rects[0], buf);
+        }
+        if let Some(import) = &mut self.import {
+            import.render(rects[0], buf);
+        }
+        if let Some(tls) = &self.tls {
+            tls.render(rects[0], buf);
         }
     }
 
@@ -98,8 +274,12 @@ impl ScreenTrait for FtpTable {
         key: event::KeyEvent,
         _mouse: Option<MouseEvent>,
     ) -> Option<Screen> {
-        if key.code == KeyCode::Esc {
+        if key.code == KeyCode::Esc && !self.popup_open() {
             Some(Screen::MainMenu(MainMenu::new()))
+        } else if key.code == KeyCode::Char('s') && !self.popup_open() {
+            Some(Screen::FtpStats(FtpStats::new()))
+        } else if key.code == KeyCode::Char('c') && !self.popup_open() {
+            Some(Screen::VsftpdSettings(VsftpdSettings::new()))
         } else {
             self.handle_keys(key);
             None
@@ -108,10 +288,68 @@ impl ScreenTrait for FtpTable {
 }
 
 impl FtpTable {
+    fn popup_open(&self) -> bool {
+        self.show_popup
+            || self.provision.is_some()
+            || self.removal.is_some()
+            || self.browser.is_some()
+            || self.import.is_some()
+            || self.tls.is_some()
+    }
+
     fn handle_keys(&mut self, key: event::KeyEvent) {
-        if self.show_popup == true {
-            // self.inputs
-            //     .handle_inputs(key, &mut self.show_popup, &mut self.items, &mut self.state);
+        if let Some(browser) = &mut self.browser {
+            if !browser.handle_key(key) {
+                self.browser = None;
+            }
+        } else if let Some(tls) = &mut self.tls {
+            if !tls.handle_key(key, self.backend.as_ref()) {
+                self.tls = None;
+            }
+        } else if let Some(import) = &mut self.import {
+            if let ImportAction::Close(message) =
+                import.handle_key(key, &self.items, self.backend.as_ref())
+            {
+                self.message = Some(message);
+                self.import = None;
+                self.reload();
+            }
+        } else if let Some(provision) = &mut self.provision {
+            if let ProvisionAction::Close(message) = provision.handle_key(key) {
+                self.message = Some(message);
+                self.provision = None;
+            }
+        } else if let Some(removal) = &self.removal {
+            match removal.handle_key(key) {
+                RemovalAction::None => {}
+                RemovalAction::Cancel => self.removal = None,
+                RemovalAction::Delete(fate) => {
+                    let username = removal.user().username.clone();
+                    self.message = Some(match self.backend.delete_user(removal.user()) {
+                        Ok(_) => removal.finish(fate),
+                        Err(err) => format!("Error deleting {}: {}", username, err),
+                    });
+                    self.removal = None;
+                    self.reload();
+                }
+            }
+        } else if self.show_popup {
+            if let Some(username) = self.inputs.handle_inputs(
+                key,
+                &mut self.show_popup,
+                &self.items,
+                self.backend.as_ref(),
+            ) {
+                self.message = Some(format!("Saved {}", username));
+                self.reload();
+                if let Some(index) = self.items.iter().position(|user| user.username == username) {
+                    self.state.select(Some(index));
+                    let user = &self.items[index];
+                    if !std::path::Path::new(&user.doc_root).exists() {
+                        self.provision = Some(ProvisionPopup::new(user));
+                    }
+                }
+            }
         } else {
             match key.code {
                 KeyCode::Char('j') | KeyCode::Down => {
@@ -128,44 +366,103 @@ impl FtpTable {
                 }
                 KeyCode::Char('n') => {
                     self.show_popup = true;
-                    // self.inputs.init_empty();
-                }
-                KeyCode::Esc => {
-                    self.show_popup = false;
+                    self.inputs.init_empty(self.backend.as_ref());
                 }
                 KeyCode::Char('d') => {
-                    let index = self.state.selected().unwrap();
-                    self.items.remove(index);
-                    // save_to_crontab(&self.items).unwrap_or_else(|err| {
-                    //     eprint!("Error saving to crontab: {}", err);
-                    // });
+                    if let Some(user) = self.state.selected().and_then(|i| self.items.get(i)) {
+                        self.removal = Some(RemovalPopup::new(user, &self.items));
+                    }
+                }
+                KeyCode::Char('l') => {
+                    self.toggle_lock();
+                }
+                KeyCode::Char('b') => {
+                    if let Some(user) = self.state.selected().and_then(|i| self.items.get(i)) {
+                        self.browser = Some(FileBrowser::new(user));
+                    }
+                }
+                KeyCode::Char('i') => {
+                    self.import = Some(ImportPopup::default());
+                }
+                KeyCode::Char('t') => {
+                    self.tls = Some(TlsPopup::new(self.backend.as_ref()));
+                }
+                KeyCode::Char('e') => {
+                    self.message = Some(match export_csv(&self.items) {
+                        Ok(path) => {
+                            format!("Exported {} users to {}", self.items.len(), path.display())
+                        }
+                        Err(err) => format!("Export failed: {}", err),
+                    });
+                }
+                KeyCode::Enter => {
+                    if let Some(user) = self.state.selected().and_then(|i| self.items.get(i)) {
+                        self.show_popup = true;
+                        self.inputs.init(user, self.backend.as_ref());
+                    }
                 }
-                // KeyCode::Enter => {
-                //     if !self.items.is_empty() {
-                //         self.show_popup = true;
-                //         self.inputs.is_new = false;
-                //         self.inputs.init(&mut self.items, &mut self.state);
-                //     }
-                // }
                 _ => {}
             }
         }
     }
 
+    /// Locks the selected user, or unlocks it when it is locked.
+    fn toggle_lock(&mut self) {
+        let Some(user) = self.state.selected().and_then(|i| self.items.get(i)) else {
+            return;
+        };
+        let locked = !user.locked;
+        self.message = Some(match self.backend.set_locked(user, locked) {
+            Ok(_) if locked => format!("Locked {}", user.username),
+            Ok(_) => format!("Unlocked {}", user.username),
+            Err(err) if locked => format!("Error locking {}: {}", user.username, err),
+            Err(err) => format!("Error unlocking {}: {}", user.username, err),
+        });
+        self.reload();
+    }
+
+    /// Reads the users from the backend again, keeping the selection in range.
+    fn reload(&mut self) {
+        match self.backend.load_users() {
+            Ok(users) => {
+                self.items = users;
+                self.usage = quota_usage(self.backend.as_ref(), &self.items);
+                self.longest_item_lens = constraint_len_calculator(&self.items, &self.usage);
+                let selected = self
+                    .state
+                    .selected()
+                    .map(|i| i.min(self.items.len().saturating_sub(1)));
+                self.state.select(selected);
+            }
+            Err(err) => {
+                tracing::error!("Error reading FTP users: {}", err);
+                self.message = Some(format!("Error reading FTP users: {}", err));
+            }
+        }
+    }
+
     pub fn next_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) if i < self.items.len() - 1 => i + 1,
             _ => 0,
         };
         self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
     }
 
     pub fn previous_row(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) if i > 0 => i - 1,
             _ => self.items.len() - 1,
         };
         self.state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
     }
 
     fn first_row(&mut self) {
@@ -182,7 +479,7 @@ impl FtpTable {
     }
 
     fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
-        let header = ["User", "Document Root"]
+        let header = ["User", "Protocol", "Document Root", "Quota Use"]
             .into_iter()
             .map(|title| Cell::from(Text::from(format!("\n{}\n", title)))) // Adds top and bottom padding
             .collect::<Row>()
@@ -193,11 +490,21 @@ impl FtpTable {
                 0 => self.styles.normal_row_color,
                 _ => self.styles.alt_row_color,
             };
-            let item = data.ref_array();
-            item.into_iter()
+            let usage = self.usage.get(i).map(String::as_str).unwrap_or("");
+            let [username, protocol, doc_root] = data.ref_array();
+            let user = format!("{}\n{}", username, data.status());
+            let style = if data.is_expired() {
+                self.styles.expired_row_style
+            } else if data.locked {
+                self.styles.locked_row_style
+            } else {
+                self.styles.row_style
+            };
+            [user.as_str(), protocol, doc_root, usage]
+                .into_iter()
                 .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
                 .collect::<Row>()
-                .style(self.styles.row_style.bg(color))
+                .style(style.bg(color))
                 .height(ITEM_HEIGHT.try_into().unwrap())
         });
         let bar = " ▌ ";
@@ -206,7 +513,9 @@ impl FtpTable {
             [
                 // + 1 is for padding.
                 Constraint::Length(self.longest_item_lens.0 + 8),
+                Constraint::Length(PROTOCOL_WIDTH),
                 Constraint::Min(self.longest_item_lens.1 + 1),
+                Constraint::Length(self.longest_item_lens.2 + 1),
             ],
         )
         .header(header)
@@ -247,15 +556,39 @@ impl FtpTable {
             &mut self.scroll_state,
         );
     }
+}
 
-    fn render_popup(area: Rect, buf: &mut Buffer) {}
+/// The quota use column: the bytes in the document root, against the quota
+/// when there is one.
+fn quota_usage(backend: &dyn FtpBackend, items: &[FtpUser]) -> Vec<String> {
+    items
+        .iter()
+        .map(|user| {
+            let Some((used, complete)) = backend.used_space(user) else {
+                return "-".to_string();
+            };
+            let used_text = if complete {
+                format_size(used)
+            } else {
+                format!("> {}", format_size(used))
+            };
+            match user.limits.quota_size {
+                Some(quota) if quota > 0 => format!(
+                    "{} / {} ({}%)",
+                    used_text,
+                    format_size(quota),
+                    used.saturating_mul(100) / quota
+                ),
+                _ => used_text,
+            }
+        })
+        .collect()
 }
 
-fn constraint_len_calculator(items: &[FtpUser]) -> (u16, u16) {
+fn constraint_len_calculator(items: &[FtpUser], usage: &[String]) -> (u16, u16, u16) {
     let username_len = items
         .iter()
-        .map(|user| user.username.as_str())
-        .map(UnicodeWidthStr::width)
+        .map(|user| user.username.width().max(user.status().width()))
         .max()
         .unwrap_or(0);
 
@@ -266,6 +599,14 @@ fn constraint_len_calculator(items: &[FtpUser]) -> (u16, u16) {
         .max()
         .unwrap_or(0);
 
+    let usage_len = usage
+        .iter()
+        .map(|usage| usage.as_str())
+        .map(UnicodeWidthStr::width)
+        .chain(["Quota Use".width()])
+        .max()
+        .unwrap_or(0);
+
     #[allow(clippy::cast_possible_truncation)]
-    (username_len as u16, doc_root_len as u16)
+    (username_len as u16, doc_root_len as u16, usage_len as u16)
 }
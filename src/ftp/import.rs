@@ -0,0 +1,766 @@
+use crate::ftp::backend::{FtpBackend, Limit};
+use crate::ftp::edit::validate_username;
+use crate::ftp::ftp::parse_size;
+use crate::ftp::provision::create_default_doc_root;
+use crate::ftp::{EditWindowStyles, FtpLimits, FtpUser, ImportStyles, Protocol, TableStyles};
+use crate::log::get_data_dir;
+use chrono::Local;
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, Paragraph, Row, Table,
+        TableState, Wrap,
+    },
+};
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use tui_textarea::TextArea;
+
+const PATH_INFO_TEXT: &str = " (Esc) Cancel | (Enter) Preview ";
+const PREVIEW_INFO_TEXT: &str = " (Esc) Back | (↓↑) Scroll | (Enter) Create the new users ";
+/// Column order of a file without a header line.
+const DEFAULT_COLUMNS: [Column; 5] = [
+    Column::Username,
+    Column::DocRoot,
+    Column::Quota,
+    Column::Password,
+    Column::Generate,
+];
+/// Hashes are not exported, so the `generate_password` column asks a
+/// re-import to create the accounts with new passwords.
+const EXPORT_HEADER: [&str; 10] = [
+    "username",
+    "protocol",
+    "doc_root",
+    "quota",
+    "upload_rate",
+    "download_rate",
+    "max_sessions",
+    "expires",
+    "locked",
+    "generate_password",
+];
+const GENERATED_LENGTH: usize = 16;
+/// Letters and digits that cannot be mistaken for each other when read out.
+const PASSWORD_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Username,
+    DocRoot,
+    Quota,
+    Password,
+    Generate,
+    Protocol,
+    Ignored,
+}
+
+impl Column {
+    /// Reads a header cell. Case, spaces and dashes do not matter.
+    fn from_header(name: &str) -> Self {
+        match name.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "username" | "user" | "login" => Column::Username,
+            "doc_root" | "docroot" | "document_root" | "home" => Column::DocRoot,
+            "quota" => Column::Quota,
+            "password" => Column::Password,
+            "generate_password" | "generate" => Column::Generate,
+            "protocol" => Column::Protocol,
+            _ => Column::Ignored,
+        }
+    }
+}
+
+/// What applying a row would do.
+pub enum RowStatus {
+    Create,
+    /// The user name is taken; nothing is changed.
+    Conflict(String),
+    Invalid(String),
+}
+
+enum PasswordSource {
+    Given(String),
+    Generate,
+}
+
+/// A row of the import file with the user it describes.
+pub struct ImportRow {
+    line: u64,
+    user: FtpUser,
+    password: Option<PasswordSource>,
+    quota: String,
+    status: RowStatus,
+    notes: Vec<String>,
+}
+
+fn parse_flag(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "" | "no" | "n" | "false" | "0" => Ok(false),
+        "yes" | "y" | "true" | "1" | "x" => Ok(true),
+        _ => Err(format!("generate_password {} is not yes or no", value)),
+    }
+}
+
+/// Checks a row and works out what importing it would do. `planned` holds
+/// the rows before it, to catch names given twice.
+fn plan_row(
+    line: u64,
+    columns: &[Column],
+    record: &csv::StringRecord,
+    ftp_users: &[FtpUser],
+    planned: &[ImportRow],
+    backend: &dyn FtpBackend,
+) -> ImportRow {
+    let field = |column: Column| {
+        columns
+            .iter()
+            .position(|candidate| *candidate == column)
+            .and_then(|index| record.get(index))
+            .unwrap_or("")
+            .trim()
+    };
+    let username = field(Column::Username);
+    let doc_root = field(Column::DocRoot);
+    let quota = field(Column::Quota);
+    let mut errors = Vec::new();
+    let mut notes = Vec::new();
+
+    if let Err(message) = validate_username(username) {
+        errors.push(format!("user name {}", message));
+    }
+    if doc_root.is_empty() {
+        errors.push("document root required".to_string());
+    } else if !doc_root.starts_with('/') || doc_root.contains(':') {
+        errors.push("document root must be an absolute path without ':'".to_string());
+    } else if !Path::new(doc_root).exists() {
+        notes.push("document root will be created".to_string());
+    }
+
+    let protocol = match field(Column::Protocol).to_lowercase().as_str() {
+        "" | "ftp" => Some(Protocol::Ftp),
+        "sftp" => Some(Protocol::Sftp),
+        other => {
+            errors.push(format!("unknown protocol {}", other));
+            None
+        }
+    };
+    if let Some(protocol) = protocol.filter(|protocol| !backend.protocols().contains(protocol)) {
+        errors.push(format!("{} is not set up", protocol.label()));
+    }
+
+    let quota_size = match parse_size(quota) {
+        _ if quota.is_empty() => None,
+        Ok(size) if size > 0 => Some(size),
+        Ok(_) => {
+            errors.push("quota must be above 0".to_string());
+            None
+        }
+        Err(message) => {
+            errors.push(format!("quota: {}", message));
+            None
+        }
+    };
+    if quota_size.is_some()
+        && (protocol == Some(Protocol::Sftp) || !backend.supports_limit(Limit::QuotaSize))
+    {
+        notes.push("quota not supported, ignored".to_string());
+    }
+
+    let given = field(Column::Password);
+    let password = match parse_flag(field(Column::Generate)) {
+        Err(message) => {
+            errors.push(message);
+            None
+        }
+        Ok(true) if !given.is_empty() => {
+            errors.push("both a password and generate_password".to_string());
+            None
+        }
+        Ok(true) => Some(PasswordSource::Generate),
+        Ok(false) if given.is_empty() => {
+            errors.push("no password and no generate_password".to_string());
+            None
+        }
+        Ok(false) if given.contains(':') => {
+            errors.push("password must not contain ':'".to_string());
+            None
+        }
+        Ok(false) => Some(PasswordSource::Given(given.to_string())),
+    };
+
+    let status = if !errors.is_empty() {
+        RowStatus::Invalid(errors.join("; "))
+    } else if ftp_users.iter().any(|user| user.username == username) {
+        RowStatus::Conflict("user exists".to_string())
+    } else if let Some(earlier) = planned.iter().find(|row| row.user.username == username) {
+        RowStatus::Conflict(format!("also on line {}", earlier.line))
+    } else {
+        RowStatus::Create
+    };
+    let user = FtpUser {
+        protocol: protocol.unwrap_or(Protocol::Ftp),
+        limits: FtpLimits {
+            quota_size: quota_size.filter(|_| backend.supports_limit(Limit::QuotaSize)),
+            ..FtpLimits::default()
+        },
+        ..FtpUser::new(username, doc_root)
+    };
+    ImportRow {
+        line,
+        user,
+        password,
+        quota: quota.to_string(),
+        status,
+        notes,
+    }
+}
+
+/// Reads a CSV of users and plans the import without changing anything.
+/// A header line names the columns (`username`, `doc_root`, `quota`,
+/// `password`, `generate_password`, `protocol`); without one the first five
+/// are expected in that order.
+pub fn plan_import(
+    path: &Path,
+    ftp_users: &[FtpUser],
+    backend: &dyn FtpBackend,
+) -> io::Result<Vec<ImportRow>> {
+    let content = fs::read_to_string(path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut columns = DEFAULT_COLUMNS.to_vec();
+    let mut rows: Vec<ImportRow> = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|err| io::Error::other(err.to_string()))?;
+        if index == 0
+            && record
+                .iter()
+                .any(|cell| Column::from_header(cell) == Column::Username)
+        {
+            columns = record.iter().map(Column::from_header).collect();
+            continue;
+        }
+        if record.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        // The position of a record is where the reader started, before the
+        // blank lines it skipped.
+        let line = record.position().map_or(index as u64 + 1, |position| {
+            let skipped = content[position.byte() as usize..]
+                .chars()
+                .take_while(|c| *c == '\n' || *c == '\r')
+                .filter(|c| *c == '\n')
+                .count();
+            position.line() + skipped as u64
+        });
+        let row = plan_row(line, &columns, &record, ftp_users, &rows, backend);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// A random password from `/dev/urandom`.
+fn generate_password() -> io::Result<String> {
+    let mut random = fs::File::open("/dev/urandom")?;
+    // Bytes above the largest multiple of the alphabet size are dropped so
+    // every character is equally likely.
+    let limit = 256 - 256 % PASSWORD_CHARS.len();
+    let mut password = String::new();
+    let mut byte = [0u8; 1];
+    while password.len() < GENERATED_LENGTH {
+        random.read_exact(&mut byte)?;
+        if (byte[0] as usize) < limit {
+            password.push(PASSWORD_CHARS[byte[0] as usize % PASSWORD_CHARS.len()] as char);
+        }
+    }
+    Ok(password)
+}
+
+/// Writes the generated passwords, readable by the owner only, into the data
+/// directory.
+fn save_passwords(passwords: &[(String, String)]) -> io::Result<PathBuf> {
+    let directory = get_data_dir();
+    fs::create_dir_all(&directory)?;
+    let path = directory.join(format!(
+        "ftp-import-passwords-{}.csv",
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    let mut writer = csv::Writer::from_writer(file);
+    let write = |writer: &mut csv::Writer<fs::File>| -> csv::Result<()> {
+        writer.write_record(["username", "password"])?;
+        for (username, password) in passwords {
+            writer.write_record([username, password])?;
+        }
+        writer.flush()?;
+        Ok(())
+    };
+    write(&mut writer).map_err(|err| io::Error::other(err.to_string()))?;
+    Ok(path)
+}
+
+/// Creates the users of the rows marked `Create` and their missing
+/// document roots. Generated passwords are saved before any account is
+/// created, and nothing is created when they cannot be. Returns a summary
+/// for the message line.
+pub fn apply_import(rows: &[ImportRow], backend: &dyn FtpBackend) -> String {
+    let mut accounts = Vec::new();
+    let mut generated = Vec::new();
+    let mut failures = Vec::new();
+    for row in rows
+        .iter()
+        .filter(|row| matches!(row.status, RowStatus::Create))
+    {
+        let password = match &row.password {
+            Some(PasswordSource::Given(password)) => password.clone(),
+            Some(PasswordSource::Generate) => match generate_password() {
+                Ok(password) => {
+                    generated.push((row.user.username.clone(), password.clone()));
+                    password
+                }
+                Err(err) => {
+                    failures.push(format!("{}: {}", row.user.username, err));
+                    continue;
+                }
+            },
+            None => continue,
+        };
+        accounts.push((row, password));
+    }
+
+    let passwords_path = if generated.is_empty() {
+        None
+    } else {
+        match save_passwords(&generated) {
+            Ok(path) => Some(path),
+            Err(err) => {
+                tracing::error!("Error saving generated passwords: {}", err);
+                return format!(
+                    "Import stopped, saving the generated passwords failed: {}",
+                    err
+                );
+            }
+        }
+    };
+
+    let mut created = Vec::new();
+    for (row, password) in accounts {
+        match backend.add_user(&row.user, Some(&password)) {
+            Ok(_) => created.push(row.user.username.clone()),
+            Err(err) => {
+                tracing::error!("Error importing {}: {}", row.user.username, err);
+                failures.push(format!("{}: {}", row.user.username, err));
+            }
+        }
+    }
+
+    // The stored users carry the ids the backend picked for them.
+    let stored = backend.load_users().unwrap_or_default();
+    for user in stored
+        .iter()
+        .filter(|user| created.contains(&user.username))
+    {
+        if !Path::new(&user.doc_root).exists() {
+            if let Err(err) = create_default_doc_root(user) {
+                failures.push(format!("{}: {}", user.doc_root, err));
+            }
+        }
+    }
+
+    let mut message = format!("Imported {} users", created.len());
+    if let Some(path) = passwords_path {
+        message.push_str(&format!(", passwords in {}", path.display()));
+    }
+    if let Some(first) = failures.first() {
+        message.push_str(&format!(", {} failed ({})", failures.len(), first));
+    }
+    message
+}
+
+/// Exports `ftp_users` into the data directory and returns the path of the
+/// file. Password hashes are left out; importing the file again generates
+/// new passwords.
+pub fn export_csv(ftp_users: &[FtpUser]) -> io::Result<PathBuf> {
+    let directory = get_data_dir();
+    fs::create_dir_all(&directory)?;
+    let path = directory.join(format!(
+        "ftp-users-{}.csv",
+        Local::now().format("%Y%m%d%H%M")
+    ));
+    let number = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    let write = || -> csv::Result<()> {
+        let mut writer = csv::Writer::from_path(&path)?;
+        writer.write_record(EXPORT_HEADER)?;
+        for user in ftp_users {
+            writer.write_record([
+                user.username.clone(),
+                user.protocol.label().to_lowercase(),
+                user.doc_root.clone(),
+                number(user.limits.quota_size),
+                number(user.limits.upload_rate),
+                number(user.limits.download_rate),
+                number(user.limits.max_sessions.map(u64::from)),
+                user.expires
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                user.locked.to_string(),
+                "yes".to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    };
+    write().map_err(|err| io::Error::other(err.to_string()))?;
+    Ok(path)
+}
+
+pub enum ImportAction {
+    None,
+    /// Close the popup and show the message.
+    Close(String),
+}
+
+/// Asks for a CSV file, previews what importing it would do and applies it.
+pub struct ImportPopup {
+    styles: EditWindowStyles,
+    table_styles: TableStyles,
+    import_styles: ImportStyles,
+    path: TextArea<'static>,
+    /// The planned rows once the file has been read.
+    rows: Option<Vec<ImportRow>>,
+    state: TableState,
+    error: Option<String>,
+}
+
+impl Default for ImportPopup {
+    fn default() -> Self {
+        let mut path = TextArea::default();
+        path.set_placeholder_text("Path of the CSV file, e.g. /root/customers.csv");
+        path.set_cursor_line_style(Style::default());
+        Self {
+            styles: EditWindowStyles::new(),
+            table_styles: TableStyles::new(),
+            import_styles: ImportStyles::new(),
+            path,
+            rows: None,
+            state: TableState::default().with_selected(0),
+            error: None,
+        }
+    }
+}
+
+impl ImportPopup {
+    fn count(&self, status: fn(&RowStatus) -> bool) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|row| status(&row.status))
+            .count()
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: event::KeyEvent,
+        ftp_users: &[FtpUser],
+        backend: &dyn FtpBackend,
+    ) -> ImportAction {
+        let Some(rows) = &self.rows else {
+            match key.code {
+                KeyCode::Esc => return ImportAction::Close("Nothing imported".to_string()),
+                KeyCode::Enter => {
+                    let value = self.path.lines().first().cloned().unwrap_or_default();
+                    match plan_import(Path::new(value.trim()), ftp_users, backend) {
+                        Ok(rows) if rows.is_empty() => {
+                            self.error = Some(format!("{} has no users", value.trim()));
+                        }
+                        Ok(rows) => {
+                            self.rows = Some(rows);
+                            self.state.select(Some(0));
+                        }
+                        Err(err) => self.error = Some(format!("{}: {}", value.trim(), err)),
+                    }
+                }
+                _ => {
+                    if self.path.input(key) {
+                        self.error = None;
+                    }
+                }
+            }
+            return ImportAction::None;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.rows = None,
+            KeyCode::Char('j') | KeyCode::Down => {
+                let i = self.state.selected().map_or(0, |i| i + 1);
+                self.state.select(Some(i.min(rows.len().saturating_sub(1))));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let i = self.state.selected().map_or(0, |i| i.saturating_sub(1));
+                self.state.select(Some(i));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::Char('G') | KeyCode::End => {
+                self.state.select(Some(rows.len().saturating_sub(1)));
+            }
+            KeyCode::Enter if self.count(|status| matches!(status, RowStatus::Create)) > 0 => {
+                return ImportAction::Close(apply_import(rows, backend));
+            }
+            _ => {}
+        }
+        ImportAction::None
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let info = if self.rows.is_some() {
+            PREVIEW_INFO_TEXT
+        } else {
+            PATH_INFO_TEXT
+        };
+        let height = if self.rows.is_some() { area.height } else { 14 };
+        let area = popup_area(area, 90, height);
+        Widget::render(Clear, area, buf);
+        let block = Block::default()
+            .title(" Import FTP users from CSV ")
+            .title_bottom(Line::from(info).centered())
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        if self.rows.is_some() {
+            self.render_preview(inner, buf);
+        } else {
+            self.render_path(inner, buf);
+        }
+    }
+
+    fn render_path(&mut self, area: Rect, buf: &mut Buffer) {
+        let [input_area, help_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(1)])
+            .margin(1)
+            .areas(area);
+        let border_style = if self.error.is_some() {
+            self.styles.invalid_input_style
+        } else {
+            self.styles.selected_input_border_style
+        };
+        self.path.set_cursor_style(self.styles.cursor_style);
+        self.path.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title("CSV file"),
+        );
+        self.path.render(input_area, buf);
+
+        let mut lines = Vec::new();
+        if let Some(error) = &self.error {
+            lines.push(Line::styled(error.clone(), self.styles.error_style));
+        }
+        lines.extend([
+            Line::raw("Columns: username, doc_root, quota, password, generate_password."),
+            Line::raw(
+                "A header line may name them in any order and add a protocol column (ftp or sftp).",
+            ),
+            Line::raw("Give either a password or generate_password=yes for each user."),
+            Line::raw("Nothing is changed before you confirm the preview."),
+        ]);
+        let help = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .style(self.styles.title_style);
+        Widget::render(help, help_area, buf);
+    }
+
+    fn render_preview(&mut self, area: Rect, buf: &mut Buffer) {
+        let [summary_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(area);
+        let creates = self.count(|status| matches!(status, RowStatus::Create));
+        let conflicts = self.count(|status| matches!(status, RowStatus::Conflict(_)));
+        let invalid = self.count(|status| matches!(status, RowStatus::Invalid(_)));
+        let summary = Line::from(vec![
+            Span::raw(" Dry run: "),
+            Span::styled(
+                format!("{} to create", creates),
+                self.import_styles.create_style,
+            ),
+            Span::raw(", "),
+            Span::styled(
+                format!("{} conflicts", conflicts),
+                self.import_styles.conflict_style,
+            ),
+            Span::raw(", "),
+            Span::styled(
+                format!("{} invalid", invalid),
+                self.import_styles.invalid_style,
+            ),
+            Span::raw(". Only the new users are created."),
+        ]);
+        Widget::render(
+            Paragraph::new(summary).style(self.styles.title_style),
+            summary_area,
+            buf,
+        );
+
+        let header = [
+            "Line",
+            "Result",
+            "User",
+            "Protocol",
+            "Document root",
+            "Quota",
+            "Password",
+            "Notes",
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(self.table_styles.header_style);
+        let rows = self.rows.iter().flatten().map(|row| {
+            let (result, style, detail) = match &row.status {
+                RowStatus::Create => ("create", self.import_styles.create_style, None),
+                RowStatus::Conflict(reason) => {
+                    ("conflict", self.import_styles.conflict_style, Some(reason))
+                }
+                RowStatus::Invalid(reason) => {
+                    ("invalid", self.import_styles.invalid_style, Some(reason))
+                }
+            };
+            let password = match row.password {
+                Some(PasswordSource::Given(_)) => "given",
+                Some(PasswordSource::Generate) => "generated",
+                None => "",
+            };
+            let notes = detail
+                .into_iter()
+                .chain(row.notes.iter())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("; ");
+            Row::new([
+                Cell::from(row.line.to_string()),
+                Cell::from(Span::styled(result, style)),
+                Cell::from(row.user.username.clone()),
+                Cell::from(row.user.protocol.label()),
+                Cell::from(row.user.doc_root.clone()),
+                Cell::from(row.quota.clone()),
+                Cell::from(password),
+                Cell::from(Span::styled(notes, self.import_styles.note_style)),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(5),
+                Constraint::Length(9),
+                Constraint::Length(16),
+                Constraint::Length(9),
+                Constraint::Min(16),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.table_styles.selected_row_style)
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(" ▌ ");
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+    }
+}
+
+fn popup_area(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Max(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ftp::pureftpd::PureftpdBackend;
+
+    fn backend() -> PureftpdBackend {
+        PureftpdBackend::new(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pureftpd/pureftpd.passwd"),
+        )
+    }
+
+    fn statuses(rows: &[ImportRow]) -> Vec<(u64, &str, String)> {
+        rows.iter()
+            .map(|row| {
+                let status = match &row.status {
+                    RowStatus::Create => "create".to_string(),
+                    RowStatus::Conflict(reason) => format!("conflict: {}", reason),
+                    RowStatus::Invalid(reason) => format!("invalid: {}", reason),
+                };
+                (row.line, row.user.username.as_str(), status)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plan_import_classifies_rows() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ftp-import.csv");
+        let ftp_users = [FtpUser::new("bob", "/srv/ftp/bob")];
+
+        let rows = plan_import(&path, &ftp_users, &backend()).unwrap();
+        assert_eq!(
+            statuses(&rows),
+            [
+                (2, "alice", "create".to_string()),
+                (3, "bob", "conflict: user exists".to_string()),
+                (4, "dave", "create".to_string()),
+                (5, "dave", "conflict: also on line 4".to_string()),
+                (
+                    7,
+                    "erin",
+                    "invalid: document root must be an absolute path without ':'; \
+                     no password and no generate_password"
+                        .to_string()
+                ),
+                (
+                    8,
+                    "frank",
+                    "invalid: quota must be above 0; both a password and generate_password"
+                        .to_string()
+                ),
+            ]
+        );
+        assert_eq!(rows[0].user.limits.quota_size, Some(10 * 1024 * 1024));
+        assert!(matches!(rows[2].password, Some(PasswordSource::Generate)));
+    }
+
+    #[test]
+    fn exported_files_import_again() {
+        let path = std::env::temp_dir().join(format!("tuixel-import-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            format!(
+                "{}\ncarol,ftp,/srv/ftp/carol,1024,,,,,false,yes\n",
+                EXPORT_HEADER.join(",")
+            ),
+        )
+        .unwrap();
+
+        let rows = plan_import(&path, &[], &backend()).unwrap();
+        assert_eq!(statuses(&rows), [(2, "carol", "create".to_string())]);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,6 +1,46 @@
+pub mod backend;
+pub use backend::FtpBackend;
+
+pub mod browser;
+pub use browser::FileBrowser;
+
+pub mod edit;
+pub use edit::Inputs;
+
 pub mod ftp;
+pub use ftp::FtpLimits;
 pub use ftp::FtpTable;
 pub use ftp::FtpUser;
+pub use ftp::Protocol;
+
+pub mod import;
+pub use import::ImportPopup;
+
+pub mod proftpd;
+pub use proftpd::ProftpdBackend;
+
+pub mod provision;
+pub use provision::{ProvisionPopup, RemovalPopup};
+
+pub mod pureftpd;
+pub use pureftpd::PureftpdBackend;
+
+pub mod settings;
+pub use settings::VsftpdSettings;
+
+pub mod sftp;
+pub use sftp::SftpBackend;
+
+pub mod stats;
+pub use stats::FtpStats;
 
 pub mod styles;
-pub use styles::TableStyles;
+pub use styles::{
+    BrowserStyles, EditWindowStyles, ImportStyles, SettingsStyles, TableStyles, TlsStyles,
+};
+
+pub mod tls;
+pub use tls::{TlsConfig, TlsPopup};
+
+pub mod vsftpd;
+pub use vsftpd::VsftpdBackend;
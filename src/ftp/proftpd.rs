@@ -0,0 +1,614 @@
+use crate::ftp::backend::{
+    hash_password, is_locked_hash, lock_hash, read_lines, shared_ids, upload_warnings,
+    write_private_lines, FtpBackend, Limit, PasswdEntry,
+};
+use crate::ftp::{FtpLimits, FtpUser, TlsConfig};
+use crate::log::PROJECT_NAME;
+use lazy_static::lazy_static;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Locations of `proftpd.conf` on Debian and on Red Hat based systems.
+const CONF_PATHS: [&str; 2] = ["/etc/proftpd/proftpd.conf", "/etc/proftpd.conf"];
+const DEFAULT_AUTH_USER_FILE: &str = "/etc/proftpd/ftpd.passwd";
+/// Virtual users usually do not get a login shell, which needs
+/// `RequireValidShell off` in `proftpd.conf`.
+const DEFAULT_SHELL: &str = "/bin/false";
+/// `TransferRate` takes kilobytes per second.
+const KILOBYTE: f64 = 1024.0;
+/// How deep `Include` directives are followed, in case they loop.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+lazy_static! {
+    pub static ref CONF_ENV: String = format!("{}_PROFTPD_CONF", PROJECT_NAME.clone());
+    pub static ref AUTH_USER_FILE_ENV: String =
+        format!("{}_PROFTPD_AUTH_USER_FILE", PROJECT_NAME.clone());
+}
+
+/// Reads and writes ProFTPD virtual users in an `ftpasswd` format
+/// `AuthUserFile`: `user:hash:uid:gid:gecos:home:shell`, where the home
+/// directory is the document root.
+pub struct ProftpdBackend {
+    conf_path: PathBuf,
+    auth_user_file: Option<PathBuf>,
+}
+
+impl ProftpdBackend {
+    pub fn new(conf_path: &Path) -> Self {
+        Self {
+            conf_path: conf_path.to_path_buf(),
+            auth_user_file: None,
+        }
+    }
+
+    /// Uses `TUIXEL_PROFTPD_CONF`, or the first `proftpd.conf` that exists.
+    /// `TUIXEL_PROFTPD_AUTH_USER_FILE` overrides the `AuthUserFile` directive.
+    pub fn from_env() -> Self {
+        let conf_path = std::env::var(CONF_ENV.clone())
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                CONF_PATHS
+                    .iter()
+                    .map(PathBuf::from)
+                    .find(|path| path.exists())
+                    .unwrap_or_else(|| PathBuf::from(CONF_PATHS[0]))
+            });
+        let mut backend = Self::new(&conf_path);
+        backend.auth_user_file = std::env::var(AUTH_USER_FILE_ENV.clone())
+            .ok()
+            .map(PathBuf::from);
+        backend
+    }
+
+    /// The `AuthUserFile` directive of `proftpd.conf` and the files it
+    /// includes. Directive names are case insensitive; the last one wins.
+    pub fn auth_user_file(&self) -> io::Result<PathBuf> {
+        if let Some(path) = &self.auth_user_file {
+            return Ok(path.clone());
+        }
+        let path = Self::lines_with_includes(&self.conf_path, 0)?
+            .iter()
+            .rev()
+            .filter_map(|line| Self::directive(line))
+            .find(|(name, _)| name == "authuserfile")
+            .and_then(|(_, arguments)| {
+                arguments
+                    .first()
+                    .map(|path| path.trim_matches('"').to_string())
+            })
+            .unwrap_or_else(|| DEFAULT_AUTH_USER_FILE.to_string());
+        Ok(PathBuf::from(path))
+    }
+
+    /// The lines of `path` with its `Include` directives replaced by the
+    /// files they name. A directory includes all of its files, and a `*` in
+    /// the last component matches file names.
+    fn lines_with_includes(path: &Path, depth: usize) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        for line in read_lines(path)? {
+            let include = match Self::directive(&line) {
+                Some((name, arguments)) if name == "include" && depth < MAX_INCLUDE_DEPTH => {
+                    arguments
+                        .first()
+                        .map(|argument| argument.trim_matches('"').to_string())
+                }
+                _ => None,
+            };
+            let Some(include) = include else {
+                lines.push(line);
+                continue;
+            };
+            let include = match path.parent() {
+                Some(directory) => directory.join(include),
+                None => PathBuf::from(include),
+            };
+            for file in Self::included_files(&include) {
+                lines.extend(Self::lines_with_includes(&file, depth + 1)?);
+            }
+        }
+        Ok(lines)
+    }
+
+    fn included_files(include: &Path) -> Vec<PathBuf> {
+        let name = include
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (directory, pattern) = if include.is_dir() {
+            (include, None)
+        } else if let Some((prefix, suffix)) = name.split_once('*') {
+            (
+                include.parent().unwrap_or(Path::new("/")),
+                Some((prefix, suffix)),
+            )
+        } else {
+            return vec![include.to_path_buf()];
+        };
+        let mut files: Vec<PathBuf> = fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match pattern {
+                    Some((prefix, suffix)) => name.starts_with(prefix) && name.ends_with(suffix),
+                    None => !name.starts_with('.'),
+                }
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Applies `change` to the entries of the `AuthUserFile` and writes it
+    /// back. Lines that are not entries are kept as they are.
+    fn modify<F>(&self, change: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Vec<String>) -> io::Result<()>,
+    {
+        let path = self.auth_user_file()?;
+        let mut lines = read_lines(&path)?;
+        change(&mut lines)?;
+        write_private_lines(&path, &lines)
+    }
+
+    /// The `<IfUser username>` section of `proftpd.conf` as the range of its
+    /// opening and closing lines.
+    fn user_section(lines: &[String], username: &str) -> Option<(usize, usize)> {
+        let start = lines.iter().position(|line| {
+            let line = line.trim();
+            match (line.get(..8), line.get(8..)) {
+                (Some(tag), Some(name)) => {
+                    tag.eq_ignore_ascii_case("<IfUser ")
+                        && name.trim_end_matches('>').trim() == username
+                }
+                _ => false,
+            }
+        })?;
+        let end = lines[start..]
+            .iter()
+            .position(|line| line.trim().eq_ignore_ascii_case("</IfUser>"))?;
+        Some((start, start + end))
+    }
+
+    /// Splits a directive line into its lowercase name and arguments.
+    fn directive(line: &str) -> Option<(String, Vec<&str>)> {
+        let mut words = line.split_whitespace();
+        let name = words.next().filter(|name| !name.starts_with('#'))?;
+        Some((name.to_lowercase(), words.collect()))
+    }
+
+    /// The limits of `username`, read from its `<IfUser>` section.
+    fn limits(lines: &[String], username: &str) -> FtpLimits {
+        let mut limits = FtpLimits::default();
+        let Some((start, end)) = Self::user_section(lines, username) else {
+            return limits;
+        };
+        for (name, arguments) in lines[start + 1..end]
+            .iter()
+            .filter_map(|line| Self::directive(line))
+        {
+            match (name.as_str(), arguments.as_slice()) {
+                ("transferrate", [commands, rate, ..]) => {
+                    // `kilobytes-per-sec[:free-bytes]`
+                    let rate = rate
+                        .split(':')
+                        .next()
+                        .and_then(|rate| rate.parse::<f64>().ok())
+                        .map(|rate| (rate * KILOBYTE) as u64);
+                    let commands = commands.to_uppercase();
+                    if commands.split(',').any(|command| command == "RETR") {
+                        limits.download_rate = rate;
+                    }
+                    if commands.split(',').any(|command| command == "STOR") {
+                        limits.upload_rate = rate;
+                    }
+                }
+                ("maxclientsperuser", [count, ..]) => {
+                    limits.max_sessions = count.parse().ok();
+                }
+                _ => {}
+            }
+        }
+        limits
+    }
+
+    /// Writes the limits of `user` to its `<IfUser>` section, renaming the
+    /// section of `old_username`. Other directives in the section are kept;
+    /// a section left empty is removed.
+    fn set_limits(lines: &mut Vec<String>, old_username: &str, user: Option<&FtpUser>) {
+        let mut directives = Vec::new();
+        if let Some(user) = user {
+            let limits = &user.limits;
+            for (command, rate) in [
+                ("RETR", limits.download_rate),
+                ("STOR,APPE,STOU", limits.upload_rate),
+            ] {
+                if let Some(rate) = rate {
+                    directives.push(format!(
+                        "  TransferRate {} {:.2}",
+                        command,
+                        rate as f64 / KILOBYTE
+                    ));
+                }
+            }
+            if let Some(count) = limits.max_sessions {
+                directives.push(format!("  MaxClientsPerUser {}", count));
+            }
+        }
+
+        let (start, end) = match Self::user_section(lines, old_username) {
+            Some(section) => section,
+            None if directives.is_empty() => return,
+            None => {
+                lines.push(String::new());
+                lines.push(format!("<IfUser {}>", old_username));
+                lines.push("</IfUser>".to_string());
+                (lines.len() - 2, lines.len() - 1)
+            }
+        };
+        let mut body: Vec<String> = lines[start + 1..end]
+            .iter()
+            .filter(|line| {
+                !Self::directive(line)
+                    .is_some_and(|(name, _)| name == "transferrate" || name == "maxclientsperuser")
+            })
+            .cloned()
+            .collect();
+        body.extend(directives);
+
+        if body.iter().all(|line| line.trim().is_empty()) {
+            // Along with the blank line added in front of it.
+            let start = if start > 0 && lines[start - 1].trim().is_empty() {
+                start - 1
+            } else {
+                start
+            };
+            lines.drain(start..=end);
+        } else {
+            if let Some(user) = user {
+                lines[start] = format!("<IfUser {}>", user.username);
+            }
+            lines.splice(start + 1..end, body);
+        }
+    }
+
+    /// Applies `change` to the lines of `proftpd.conf` and writes it back.
+    fn modify_conf<F>(&self, change: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Vec<String>),
+    {
+        let mut lines = read_lines(&self.conf_path)?;
+        change(&mut lines);
+        let mut content = lines.join("\n");
+        content.push('\n');
+        fs::write(&self.conf_path, content)
+    }
+
+    fn position(lines: &[String], username: &str) -> Option<usize> {
+        lines
+            .iter()
+            .position(|line| PasswdEntry::parse(line).is_some_and(|entry| entry.name == username))
+    }
+}
+
+impl FtpBackend for ProftpdBackend {
+    fn load_users(&self) -> io::Result<Vec<FtpUser>> {
+        let path = self.auth_user_file()?;
+        let conf = read_lines(&self.conf_path)?;
+        Ok(read_lines(&path)?
+            .iter()
+            .filter_map(|line| PasswdEntry::parse(line))
+            .map(|entry| FtpUser {
+                uid: Some(entry.uid),
+                gid: Some(entry.gid),
+                shell: Some(entry.shell),
+                limits: Self::limits(&conf, &entry.name),
+                locked: is_locked_hash(&entry.password),
+                ..FtpUser::new(&entry.name, &entry.home)
+            })
+            .collect())
+    }
+
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()> {
+        let hash = match password {
+            Some(password) => hash_password(password)?,
+            // An invalid hash until a password is set.
+            None => "!".to_string(),
+        };
+        self.modify(|lines| {
+            if Self::position(lines, &user.username).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", user.username),
+                ));
+            }
+            let (uid, gid) = shared_ids(
+                lines
+                    .iter()
+                    .filter_map(|line| PasswdEntry::parse(line))
+                    .map(|entry| (entry.uid, entry.gid)),
+            );
+            let entry = PasswdEntry {
+                name: user.username.clone(),
+                password: hash,
+                uid: user.uid.unwrap_or(uid),
+                gid: user.gid.unwrap_or(gid),
+                gecos: String::new(),
+                home: user.doc_root.clone(),
+                shell: user.shell.clone().unwrap_or(DEFAULT_SHELL.to_string()),
+            };
+            lines.push(entry.to_line());
+            Ok(())
+        })?;
+        self.modify_conf(|lines| Self::set_limits(lines, &user.username, Some(user)))
+    }
+
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        let hash = password.map(hash_password).transpose()?;
+        self.modify(|lines| {
+            let index = Self::position(lines, old_username)
+                .ok_or_else(|| io::Error::other(format!("{} does not exist", old_username)))?;
+            if old_username != user.username && Self::position(lines, &user.username).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", user.username),
+                ));
+            }
+            // Parsed by `position` above.
+            let mut entry = PasswdEntry::parse(&lines[index]).unwrap();
+            entry.name = user.username.clone();
+            entry.home = user.doc_root.clone();
+            if let Some(uid) = user.uid {
+                entry.uid = uid;
+            }
+            if let Some(gid) = user.gid {
+                entry.gid = gid;
+            }
+            if let Some(shell) = &user.shell {
+                entry.shell = shell.clone();
+            }
+            if let Some(hash) = hash {
+                // A new password leaves a locked account locked.
+                entry.password = lock_hash(&hash, is_locked_hash(&entry.password))?;
+            }
+            lines[index] = entry.to_line();
+            Ok(())
+        })?;
+        self.modify_conf(|lines| Self::set_limits(lines, old_username, Some(user)))
+    }
+
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()> {
+        let username = user.username.as_str();
+        self.modify(|lines| {
+            lines
+                .retain(|line| PasswdEntry::parse(line).is_none_or(|entry| entry.name != username));
+            Ok(())
+        })?;
+        self.modify_conf(|lines| Self::set_limits(lines, username, None))
+    }
+
+    /// Locks the hash the way `ftpasswd --lock` does.
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()> {
+        self.modify(|lines| {
+            let index = Self::position(lines, &user.username)
+                .ok_or_else(|| io::Error::other(format!("{} does not exist", user.username)))?;
+            // Parsed by `position` above.
+            let mut entry = PasswdEntry::parse(&lines[index]).unwrap();
+            entry.password = lock_hash(&entry.password, locked)?;
+            lines[index] = entry.to_line();
+            Ok(())
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self.auth_user_file() {
+            Ok(path) => format!("ProFTPD ({})", path.display()),
+            Err(_) => format!("ProFTPD ({})", self.conf_path.display()),
+        }
+    }
+
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+        upload_warnings(user, owner, group, mode)
+    }
+
+    /// `TLSEngine on` with the `TLSRSACertificateFile` and
+    /// `TLSRSACertificateKeyFile` directives of `proftpd.conf` and the files
+    /// it includes, usually `tls.conf`. The last of each wins.
+    fn tls_config(&self) -> io::Result<TlsConfig> {
+        let lines = Self::lines_with_includes(&self.conf_path, 0)?;
+        let last = |directive: &str| {
+            lines
+                .iter()
+                .rev()
+                .filter_map(|line| Self::directive(line))
+                .find(|(name, _)| name == directive)
+                .and_then(|(_, arguments)| {
+                    arguments
+                        .first()
+                        .map(|value| value.trim_matches('"').to_string())
+                })
+        };
+        let engine = last("tlsengine").unwrap_or("off".to_string());
+        let cert_file = last("tlsrsacertificatefile").map(PathBuf::from);
+        let key_file = last("tlsrsacertificatekeyfile")
+            .map(PathBuf::from)
+            .or_else(|| cert_file.clone());
+        Ok(TlsConfig {
+            enabled: engine.eq_ignore_ascii_case("on"),
+            setting: format!("TLSEngine {}", engine),
+            cert_file,
+            key_file,
+        })
+    }
+
+    /// Rates and sessions go to an `<IfUser>` section, which needs
+    /// `mod_ifsession`. Quotas need `mod_quotatab` tables tuixel does not
+    /// manage.
+    fn supports_limit(&self, limit: Limit) -> bool {
+        limit != Limit::QuotaSize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Copies the fixture server into a directory of its own, so that tests
+    /// can change it.
+    fn fixture(name: &str) -> (PathBuf, ProftpdBackend) {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proftpd");
+        let directory =
+            std::env::temp_dir().join(format!("tuixel-proftpd-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("conf.d")).unwrap();
+        for file in ["proftpd.conf", "conf.d/auth.conf", "ftpd.passwd"] {
+            fs::copy(source.join(file), directory.join(file)).unwrap();
+        }
+
+        let mut backend = ProftpdBackend::new(&directory.join("proftpd.conf"));
+        backend.auth_user_file = Some(directory.join("ftpd.passwd"));
+        (directory, backend)
+    }
+
+    fn entry(directory: &Path, username: &str) -> Option<PasswdEntry> {
+        read_lines(&directory.join("ftpd.passwd"))
+            .unwrap()
+            .iter()
+            .filter_map(|line| PasswdEntry::parse(line))
+            .find(|entry| entry.name == username)
+    }
+
+    fn conf(directory: &Path) -> String {
+        fs::read_to_string(directory.join("proftpd.conf")).unwrap()
+    }
+
+    #[test]
+    fn passwd_entries_round_trip() {
+        let lines = read_lines(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proftpd/ftpd.passwd"),
+        )
+        .unwrap();
+        assert!(PasswdEntry::parse(&lines[0]).is_none());
+        for line in &lines[1..] {
+            assert_eq!(&PasswdEntry::parse(line).unwrap().to_line(), line);
+        }
+        assert!(PasswdEntry::parse("dave:x:nobody:2001::/srv/ftp/dave:/bin/false").is_none());
+    }
+
+    #[test]
+    fn auth_user_file_follows_includes() {
+        let (directory, _) = fixture("auth-user-file");
+        let backend = ProftpdBackend::new(&directory.join("proftpd.conf"));
+
+        assert_eq!(
+            backend.auth_user_file().unwrap(),
+            PathBuf::from("/etc/proftpd/virtual.passwd")
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn load_users_reads_limits_and_locks() {
+        let (directory, backend) = fixture("load");
+
+        let users = backend.load_users().unwrap();
+        let loaded: Vec<_> = users
+            .iter()
+            .map(|user| {
+                (
+                    user.username.as_str(),
+                    user.locked,
+                    user.limits.download_rate,
+                    user.limits.upload_rate,
+                    user.limits.max_sessions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            loaded,
+            [
+                ("bob", false, Some(2048), None, Some(2)),
+                ("carol", true, None, Some(4096), None),
+            ]
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn add_user_appends_entry_and_limits() {
+        let (directory, backend) = fixture("add");
+        let mut user = FtpUser::new("dave", "/srv/ftp/dave");
+        user.limits.max_sessions = Some(1);
+
+        backend.add_user(&user, None).unwrap();
+        assert_eq!(
+            entry(&directory, "dave").unwrap().to_line(),
+            "dave:!:2001:2001::/srv/ftp/dave:/bin/false"
+        );
+        assert!(conf(&directory).ends_with("\n<IfUser dave>\n  MaxClientsPerUser 1\n</IfUser>\n"));
+
+        let err = backend.add_user(&user, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn update_user_keeps_the_lock_and_renames_the_section() {
+        let (directory, backend) = fixture("update");
+        let mut user = FtpUser::new("caroline", "/srv/ftp/caroline");
+        user.limits.upload_rate = Some(1024);
+
+        backend
+            .update_user("carol", &user, Some("new password"))
+            .unwrap();
+        assert!(entry(&directory, "carol").is_none());
+        let updated = entry(&directory, "caroline").unwrap();
+        assert!(is_locked_hash(&updated.password));
+        assert_ne!(updated.password, "!$6$carol$hash");
+        assert_eq!(
+            (updated.uid, updated.home.as_str(), updated.shell.as_str()),
+            (2001, "/srv/ftp/caroline", "/bin/sh")
+        );
+        let conf = conf(&directory);
+        assert!(!conf.contains("<IfUser carol>"));
+        assert!(conf.contains("<IfUser caroline>\n  TransferRate STOR,APPE,STOU 1.00\n</IfUser>"));
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn set_limits_removes_sections_left_empty() {
+        let mut lines: Vec<String> =
+            conf(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proftpd"))
+                .lines()
+                .map(str::to_string)
+                .collect();
+
+        ProftpdBackend::set_limits(&mut lines, "carol", Some(&FtpUser::new("carol", "/")));
+        assert!(ProftpdBackend::user_section(&lines, "carol").is_none());
+        assert_eq!(lines.last().map(String::as_str), Some("</IfUser>"));
+
+        // Other directives keep a section alive.
+        ProftpdBackend::set_limits(&mut lines, "bob", None);
+        let (start, end) = ProftpdBackend::user_section(&lines, "bob").unwrap();
+        assert_eq!(lines[start + 1..end], ["  # bob shares a slow uplink"]);
+    }
+
+    #[test]
+    fn user_section_ignores_multi_byte_lines() {
+        let lines = ["aññññ", "<IfUser ñandú>", "</IfUser>"].map(str::to_string);
+        assert_eq!(ProftpdBackend::user_section(&lines, "ñandú"), Some((1, 2)));
+        assert_eq!(ProftpdBackend::user_section(&lines, "bob"), None);
+    }
+}
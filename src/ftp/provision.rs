@@ -0,0 +1,524 @@
+use crate::ftp::backend::{group_id, shared_ids, system_user};
+use crate::ftp::{EditWindowStyles, FtpBackend, FtpUser, Protocol};
+use crate::log::{get_data_dir, PROJECT_NAME};
+use chrono::Local;
+use lazy_static::lazy_static;
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tui_textarea::TextArea;
+
+const DEFAULT_MODE: &str = "755";
+const ROOT: &str = "root";
+const ARCHIVE_DIRECTORY: &str = "ftp-archives";
+const PROVISION_INFO_TEXT: &str = " (Esc) Skip | (Tab) Next field | (Enter) Create directory ";
+const REMOVAL_INFO_TEXT: &str = " (Esc) Cancel | (k) Keep | (a) Archive | (r) Remove ";
+
+lazy_static! {
+    pub static ref SKELETON_ENV: String = format!("{}_FTP_SKELETON", PROJECT_NAME.clone());
+}
+
+/// The uid and gid the files of `user` are accessed as: the account of a
+/// virtual user, the system user of the same name, or the shared FTP account.
+pub fn account_ids(user: &FtpUser) -> (u32, u32) {
+    match (user.uid, user.gid) {
+        (Some(uid), Some(gid)) => (uid, gid),
+        _ => system_user(&user.username)
+            .map(|entry| (entry.uid, entry.gid))
+            .unwrap_or_else(|| shared_ids(std::iter::empty())),
+    }
+}
+
+/// Parses a user name or a numeric uid.
+fn parse_owner(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    value
+        .parse()
+        .ok()
+        .or_else(|| system_user(value).map(|entry| entry.uid))
+        .ok_or_else(|| format!("unknown user {}", value))
+}
+
+/// Parses a group name or a numeric gid.
+fn parse_group(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    value
+        .parse()
+        .ok()
+        .or_else(|| group_id(value))
+        .ok_or_else(|| format!("unknown group {}", value))
+}
+
+/// Parses an octal mode such as `755` or `2775`.
+fn parse_mode(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    match u32::from_str_radix(value, 8) {
+        Ok(mode) if (3..=4).contains(&value.len()) && mode <= 0o7777 => Ok(mode),
+        _ => Err(format!("{} is not an octal mode like 755", value)),
+    }
+}
+
+/// Copies the contents of `source` into `target`, handing every copy to
+/// `uid`:`gid`. Existing files are left alone.
+fn copy_tree(source: &Path, target: &Path, uid: u32, gid: u32) -> io::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let destination = target.join(entry.file_name());
+        if destination.exists() {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            fs::create_dir(&destination)?;
+            fs::set_permissions(&destination, entry.metadata()?.permissions())?;
+            copy_tree(&entry.path(), &destination, uid, gid)?;
+        } else if file_type.is_symlink() {
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &destination)?;
+        } else {
+            fs::copy(entry.path(), &destination)?;
+        }
+        std::os::unix::fs::lchown(&destination, Some(uid), Some(gid))?;
+    }
+    Ok(())
+}
+
+/// Creates `path` owned by `uid`:`gid` with `mode`, filled from `skeleton`
+/// when given.
+pub fn create_doc_root(
+    path: &Path,
+    uid: u32,
+    gid: u32,
+    mode: u32,
+    skeleton: Option<&Path>,
+) -> io::Result<()> {
+    fs::create_dir_all(path)?;
+    if let Some(skeleton) = skeleton {
+        copy_tree(skeleton, path, uid, gid)?;
+    }
+    std::os::unix::fs::chown(path, Some(uid), Some(gid))?;
+    // Set last, a read-only root would refuse the skeleton copy.
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+/// Creates the document root of `user` with the owner, mode and skeleton
+/// the provision popup starts with.
+pub fn create_default_doc_root(user: &FtpUser) -> io::Result<()> {
+    let (uid, gid) = match user.protocol {
+        Protocol::Sftp => (0, 0),
+        Protocol::Ftp => account_ids(user),
+    };
+    let mode = parse_mode(DEFAULT_MODE).map_err(io::Error::other)?;
+    let skeleton = std::env::var(SKELETON_ENV.clone())
+        .ok()
+        .filter(|value| !value.is_empty());
+    create_doc_root(
+        Path::new(&user.doc_root),
+        uid,
+        gid,
+        mode,
+        skeleton.as_deref().map(Path::new),
+    )
+}
+
+/// Packs `path` into a `tar.gz` in the `ftp-archives` data directory.
+pub fn archive_doc_root(path: &Path, username: &str) -> io::Result<PathBuf> {
+    let directory = get_data_dir().join(ARCHIVE_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+    let archive = directory.join(format!(
+        "{}-{}.tar.gz",
+        username,
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(io::Error::other(format!(
+            "cannot archive {}",
+            path.display()
+        )));
+    };
+
+    let output = Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(archive)
+}
+
+/// Another user whose document root is `path`, inside it or contains it.
+fn sharing_user<'a>(user: &FtpUser, ftp_users: &'a [FtpUser]) -> Option<&'a FtpUser> {
+    let path = Path::new(&user.doc_root);
+    ftp_users.iter().find(|other| {
+        other.username != user.username
+            && (Path::new(&other.doc_root).starts_with(path) || path.starts_with(&other.doc_root))
+    })
+}
+
+/// Warnings about a document root owned by `owner`:`group` with `mode`.
+pub fn doc_root_warnings(
+    backend: &dyn FtpBackend,
+    user: &FtpUser,
+    owner: u32,
+    group: u32,
+    mode: u32,
+) -> Vec<String> {
+    let mut warnings = backend.doc_root_warnings(user, owner, group, mode);
+    if mode & 0o002 != 0 {
+        warnings.push("the directory is world writable".to_string());
+    }
+    warnings
+}
+
+pub enum ProvisionAction {
+    None,
+    /// Close the popup and show the message.
+    Close(String),
+}
+
+/// Offers to create the missing document root of a new or changed user.
+pub struct ProvisionPopup {
+    styles: EditWindowStyles,
+    user: FtpUser,
+    inputs: [TextArea<'static>; 4],
+    current: usize,
+    error: Option<String>,
+}
+
+impl ProvisionPopup {
+    const TITLES: [&'static str; 4] = ["Owner", "Group", "Mode", "Skeleton directory"];
+
+    pub fn new(user: &FtpUser) -> Self {
+        let (uid, gid) = account_ids(user);
+        let (owner, group) = match user.protocol {
+            // sshd only accepts a chroot owned by root.
+            Protocol::Sftp => (ROOT.to_string(), ROOT.to_string()),
+            Protocol::Ftp => (
+                system_user(&user.username)
+                    .filter(|entry| entry.uid == uid)
+                    .map(|entry| entry.name)
+                    .unwrap_or_else(|| uid.to_string()),
+                gid.to_string(),
+            ),
+        };
+        let skeleton = std::env::var(SKELETON_ENV.clone()).unwrap_or_default();
+
+        let inputs = [owner, group, DEFAULT_MODE.to_string(), skeleton].map(|value| {
+            let mut input = TextArea::from([value]);
+            input.set_cursor_line_style(Style::default());
+            input.move_cursor(tui_textarea::CursorMove::End);
+            input
+        });
+        Self {
+            styles: EditWindowStyles::new(),
+            user: user.clone(),
+            inputs,
+            current: 0,
+            error: None,
+        }
+    }
+
+    fn value(&self, index: usize) -> &str {
+        self.inputs[index]
+            .lines()
+            .first()
+            .map(|s| s.as_str())
+            .unwrap_or("")
+    }
+
+    /// The owner, group and mode entered, or the first invalid field.
+    fn settings(&self) -> Result<(u32, u32, u32), String> {
+        Ok((
+            parse_owner(self.value(0))?,
+            parse_group(self.value(1))?,
+            parse_mode(self.value(2))?,
+        ))
+    }
+
+    pub fn handle_key(&mut self, key: event::KeyEvent) -> ProvisionAction {
+        match key.code {
+            KeyCode::Esc => ProvisionAction::Close(format!(
+                "Saved {}, {} was not created",
+                self.user.username, self.user.doc_root
+            )),
+            KeyCode::Tab => {
+                self.current = (self.current + 1) % self.inputs.len();
+                ProvisionAction::None
+            }
+            KeyCode::BackTab => {
+                self.current = (self.current + self.inputs.len() - 1) % self.inputs.len();
+                ProvisionAction::None
+            }
+            KeyCode::Enter => {
+                let (uid, gid, mode) = match self.settings() {
+                    Ok(settings) => settings,
+                    Err(err) => {
+                        self.error = Some(err);
+                        return ProvisionAction::None;
+                    }
+                };
+                let skeleton = Some(self.value(3).trim())
+                    .filter(|value| !value.is_empty())
+                    .map(Path::new);
+                match create_doc_root(Path::new(&self.user.doc_root), uid, gid, mode, skeleton) {
+                    Ok(_) => ProvisionAction::Close(format!(
+                        "Saved {} and created {}",
+                        self.user.username, self.user.doc_root
+                    )),
+                    Err(err) => {
+                        self.error =
+                            Some(format!("Error creating {}: {}", self.user.doc_root, err));
+                        ProvisionAction::None
+                    }
+                }
+            }
+            _ => {
+                if self.inputs[self.current].input(key) {
+                    self.error = None;
+                }
+                ProvisionAction::None
+            }
+        }
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, backend: &dyn FtpBackend) {
+        let warnings = match self.settings() {
+            Ok((uid, gid, mode)) => doc_root_warnings(backend, &self.user, uid, gid, mode),
+            Err(_) => Vec::new(),
+        };
+
+        let area = popup_area(area, 70, 20);
+        Widget::render(Clear, area, buf);
+        let block = Block::default()
+            .title(format!(" Create {} ", self.user.doc_root))
+            .title_bottom(Line::from(PROVISION_INFO_TEXT).centered())
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+
+        let [intro_area, fields_area, skeleton_area, notes_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(1),
+        ])
+        .margin(1)
+        .areas(inner);
+        let intro = Paragraph::new(format!(
+            "The document root of {} does not exist yet.",
+            self.user.username
+        ))
+        .style(self.styles.title_style);
+        Widget::render(intro, intro_area, buf);
+
+        let field_areas = Layout::horizontal([
+            Constraint::Percentage(40),
+            Constraint::Percentage(40),
+            Constraint::Percentage(20),
+        ])
+        .split(fields_area);
+        let errors = [
+            parse_owner(self.value(0)).err(),
+            parse_group(self.value(1)).err(),
+            parse_mode(self.value(2)).err(),
+            None,
+        ];
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            let input_area = if index < 3 {
+                field_areas[index]
+            } else {
+                skeleton_area
+            };
+            let border_style = match (&errors[index], index == self.current) {
+                (Some(_), _) => self.styles.invalid_input_style,
+                (None, true) => self.styles.selected_input_border_style,
+                (None, false) => self.styles.unselected_input_border_style,
+            };
+            let cursor_style = if index == self.current {
+                self.styles.cursor_style
+            } else {
+                Style::default()
+            };
+            input.set_cursor_style(cursor_style);
+            input.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(Self::TITLES[index]),
+            );
+            input.render(input_area, buf);
+        }
+
+        let mut lines: Vec<Line> = errors
+            .iter()
+            .flatten()
+            .chain(self.error.iter())
+            .map(|error| Line::styled(error.clone(), self.styles.error_style))
+            .collect();
+        for warning in &warnings {
+            lines.push(Line::from(vec![
+                Span::styled("⚠ ", self.styles.error_style),
+                Span::raw(warning.clone()),
+            ]));
+        }
+        let notes = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .style(self.styles.title_style);
+        Widget::render(notes, notes_area, buf);
+    }
+}
+
+/// What happens to the document root of a deleted user.
+#[derive(Clone, Copy)]
+pub enum DocRootFate {
+    Keep,
+    Archive,
+    Remove,
+}
+
+pub enum RemovalAction {
+    None,
+    Cancel,
+    Delete(DocRootFate),
+}
+
+/// Confirms deleting a user and asks whether to keep, archive or remove its
+/// document root.
+pub struct RemovalPopup {
+    styles: EditWindowStyles,
+    user: FtpUser,
+    shared_with: Option<String>,
+    exists: bool,
+}
+
+impl RemovalPopup {
+    pub fn new(user: &FtpUser, ftp_users: &[FtpUser]) -> Self {
+        Self {
+            styles: EditWindowStyles::new(),
+            user: user.clone(),
+            shared_with: sharing_user(user, ftp_users).map(|other| other.username.clone()),
+            exists: Path::new(&user.doc_root).is_dir(),
+        }
+    }
+
+    /// `/`, `/srv` and the like are never removed, whatever the user says.
+    fn shallow(&self) -> bool {
+        Path::new(&self.user.doc_root).components().count() < 3
+    }
+
+    pub fn user(&self) -> &FtpUser {
+        &self.user
+    }
+
+    pub fn handle_key(&self, key: event::KeyEvent) -> RemovalAction {
+        match key.code {
+            KeyCode::Esc => RemovalAction::Cancel,
+            KeyCode::Char('k') => RemovalAction::Delete(DocRootFate::Keep),
+            KeyCode::Char('a') if self.exists => RemovalAction::Delete(DocRootFate::Archive),
+            // Never remove a directory another account still uses.
+            KeyCode::Char('r') if self.exists && self.shared_with.is_none() && !self.shallow() => {
+                RemovalAction::Delete(DocRootFate::Remove)
+            }
+            _ => RemovalAction::None,
+        }
+    }
+
+    /// Applies `fate` to the document root once the user has been deleted.
+    pub fn finish(&self, fate: DocRootFate) -> String {
+        let path = Path::new(&self.user.doc_root);
+        match fate {
+            DocRootFate::Keep => format!("Deleted {}", self.user.username),
+            DocRootFate::Archive => match archive_doc_root(path, &self.user.username)
+                .and_then(|archive| fs::remove_dir_all(path).map(|_| archive))
+            {
+                Ok(archive) => format!(
+                    "Deleted {}, archived {} to {}",
+                    self.user.username,
+                    path.display(),
+                    archive.display()
+                ),
+                Err(err) => format!(
+                    "Deleted {}, archiving {} failed: {}",
+                    self.user.username,
+                    path.display(),
+                    err
+                ),
+            },
+            DocRootFate::Remove => match fs::remove_dir_all(path) {
+                Ok(_) => format!("Deleted {} and {}", self.user.username, path.display()),
+                Err(err) => format!(
+                    "Deleted {}, removing {} failed: {}",
+                    self.user.username,
+                    path.display(),
+                    err
+                ),
+            },
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area, 60, 9);
+        Widget::render(Clear, area, buf);
+        let block = Block::default()
+            .title(format!(" Delete {} ", self.user.username))
+            .title_bottom(Line::from(REMOVAL_INFO_TEXT).centered())
+            .style(self.styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.styles.window_border_style);
+
+        let mut lines = vec![Line::styled(
+            format!("Document root: {}", self.user.doc_root),
+            self.styles.title_style,
+        )];
+        if !self.exists {
+            lines.push(Line::raw(
+                "The directory does not exist, (k) deletes the user.",
+            ));
+        } else if self.shallow() {
+            lines.push(Line::styled(
+                "This is a top level directory, it cannot be removed.",
+                self.styles.error_style,
+            ));
+        } else if let Some(other) = &self.shared_with {
+            lines.push(Line::styled(
+                format!("{} shares this directory, it cannot be removed.", other),
+                self.styles.error_style,
+            ));
+        } else {
+            lines.push(Line::raw(
+                "Archive packs it into the data directory before removing it.",
+            ));
+        }
+        let report = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .block(block);
+        Widget::render(report, area, buf);
+    }
+}
+
+fn popup_area(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Max(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
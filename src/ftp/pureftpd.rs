@@ -0,0 +1,445 @@
+use crate::ftp::backend::{
+    directory_size, hash_password, is_locked_hash, lock_hash, read_lines, shared_ids,
+    upload_warnings, write_private_lines, FtpBackend, Limit,
+};
+use crate::ftp::{FtpLimits, FtpUser, TlsConfig};
+use crate::log::PROJECT_NAME;
+use lazy_static::lazy_static;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Locations of `pureftpd.passwd` on Debian and on other systems.
+const PASSWD_PATHS: [&str; 2] = ["/etc/pure-ftpd/pureftpd.passwd", "/etc/pureftpd.passwd"];
+/// Debian's wrapper reads one file per option from this directory.
+const CONF_DIR: &str = "/etc/pure-ftpd/conf";
+const CONF_PATHS: [&str; 2] = ["/etc/pure-ftpd/pure-ftpd.conf", "/etc/pure-ftpd.conf"];
+/// The certificate and key Pure-FTPd reads unless `CertFile` says otherwise.
+const DEFAULT_CERT_FILE: &str = "/etc/ssl/private/pure-ftpd.pem";
+const FIELD_COUNT: usize = 18;
+/// Pure-FTPd keeps the quota use of a user in this file in its home
+/// directory: `<files> <bytes>`.
+const QUOTA_FILE: &str = ".ftpquota";
+
+// Columns of a `pureftpd.passwd` line, as written by `pure-pw`.
+const NAME: usize = 0;
+const PASSWORD: usize = 1;
+const UID: usize = 2;
+const GID: usize = 3;
+const HOME: usize = 5;
+const UPLOAD_RATE: usize = 6;
+const DOWNLOAD_RATE: usize = 7;
+const MAX_SESSIONS: usize = 10;
+const QUOTA_FILES: usize = 11;
+const QUOTA_SIZE: usize = 12;
+const ALLOWED_CLIENT_IPS: usize = 15;
+const DENIED_CLIENT_IPS: usize = 16;
+
+lazy_static! {
+    pub static ref PASSWD_ENV: String = format!("{}_PUREFTPD_PASSWD", PROJECT_NAME.clone());
+    pub static ref PDB_ENV: String = format!("{}_PUREFTPD_PDB", PROJECT_NAME.clone());
+}
+
+/// A line of `pureftpd.passwd`:
+/// `user:hash:uid:gid:gecos:home:upload bandwidth:download bandwidth:upload
+/// ratio:download ratio:max sessions:files quota:size quota:allowed local
+/// IPs:denied local IPs:allowed client IPs:denied client IPs:time
+/// restrictions`. Bandwidths are in bytes per second and the size quota in
+/// bytes. Columns tuixel does not edit are written back unchanged.
+struct PureftpdEntry {
+    fields: Vec<String>,
+}
+
+impl PureftpdEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields: Vec<String> = line.split(':').map(str::to_string).collect();
+        if line.trim_start().starts_with('#') || fields.len() < HOME + 1 {
+            return None;
+        }
+        fields[UID].parse::<u32>().ok()?;
+        fields[GID].parse::<u32>().ok()?;
+        fields.resize(FIELD_COUNT.max(fields.len()), String::new());
+        Some(Self { fields })
+    }
+
+    fn new(name: &str) -> Self {
+        let mut fields = vec![String::new(); FIELD_COUNT];
+        fields[NAME] = name.to_string();
+        Self { fields }
+    }
+
+    fn name(&self) -> &str {
+        &self.fields[NAME]
+    }
+
+    fn number<T: std::str::FromStr>(&self, index: usize) -> Option<T> {
+        self.fields[index].parse().ok()
+    }
+
+    fn set_number<T: ToString>(&mut self, index: usize, value: Option<T>) {
+        self.fields[index] = value.map(|value| value.to_string()).unwrap_or_default();
+    }
+
+    fn list(&self, index: usize) -> Vec<String> {
+        self.fields[index]
+            .split(',')
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn to_user(&self) -> FtpUser {
+        FtpUser {
+            uid: self.number(UID),
+            gid: self.number(GID),
+            limits: FtpLimits {
+                quota_size: self.number(QUOTA_SIZE),
+                quota_files: self.number(QUOTA_FILES),
+                upload_rate: self.number(UPLOAD_RATE),
+                download_rate: self.number(DOWNLOAD_RATE),
+                max_sessions: self.number(MAX_SESSIONS),
+            },
+            allowed_ips: self.list(ALLOWED_CLIENT_IPS),
+            denied_ips: self.list(DENIED_CLIENT_IPS),
+            locked: is_locked_hash(&self.fields[PASSWORD]),
+            ..FtpUser::new(self.name(), &self.fields[HOME])
+        }
+    }
+
+    /// Copies the values of `user` into the entry. A uid or gid of `None`
+    /// keeps the current one.
+    fn apply(&mut self, user: &FtpUser) {
+        self.fields[NAME] = user.username.clone();
+        self.fields[HOME] = user.doc_root.clone();
+        if let Some(uid) = user.uid {
+            self.fields[UID] = uid.to_string();
+        }
+        if let Some(gid) = user.gid {
+            self.fields[GID] = gid.to_string();
+        }
+        self.set_number(QUOTA_SIZE, user.limits.quota_size);
+        self.set_number(QUOTA_FILES, user.limits.quota_files);
+        self.set_number(UPLOAD_RATE, user.limits.upload_rate);
+        self.set_number(DOWNLOAD_RATE, user.limits.download_rate);
+        self.set_number(MAX_SESSIONS, user.limits.max_sessions);
+        self.fields[ALLOWED_CLIENT_IPS] = user.allowed_ips.join(",");
+        self.fields[DENIED_CLIENT_IPS] = user.denied_ips.join(",");
+    }
+
+    fn to_line(&self) -> String {
+        self.fields.join(":")
+    }
+}
+
+/// Reads and writes Pure-FTPd virtual users in `pureftpd.passwd` and
+/// rebuilds `pureftpd.pdb` with `pure-pw mkdb` after every change.
+pub struct PureftpdBackend {
+    passwd_path: PathBuf,
+    pdb_path: PathBuf,
+}
+
+impl PureftpdBackend {
+    /// The database defaults to `pureftpd.pdb` next to `passwd_path`.
+    pub fn new(passwd_path: &Path) -> Self {
+        Self {
+            passwd_path: passwd_path.to_path_buf(),
+            pdb_path: passwd_path.with_extension("pdb"),
+        }
+    }
+
+    /// Uses `TUIXEL_PUREFTPD_PASSWD`, or the first `pureftpd.passwd` that
+    /// exists, and `TUIXEL_PUREFTPD_PDB` for the database.
+    pub fn from_env() -> Self {
+        let passwd_path = std::env::var(PASSWD_ENV.clone())
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                PASSWD_PATHS
+                    .iter()
+                    .map(PathBuf::from)
+                    .find(|path| path.exists())
+                    .unwrap_or_else(|| PathBuf::from(PASSWD_PATHS[0]))
+            });
+        let mut backend = Self::new(&passwd_path);
+        if let Ok(pdb_path) = std::env::var(PDB_ENV.clone()) {
+            backend.pdb_path = PathBuf::from(pdb_path);
+        }
+        backend
+    }
+
+    fn position(lines: &[String], username: &str) -> Option<usize> {
+        lines.iter().position(|line| {
+            PureftpdEntry::parse(line).is_some_and(|entry| entry.name() == username)
+        })
+    }
+
+    /// A server option from its file in `CONF_DIR`, or else from the first
+    /// `pure-ftpd.conf` that exists.
+    fn option(name: &str) -> Option<String> {
+        if let Ok(value) = fs::read_to_string(Path::new(CONF_DIR).join(name)) {
+            return Some(value.trim().to_string());
+        }
+        let conf = CONF_PATHS
+            .iter()
+            .map(Path::new)
+            .find(|path| path.exists())?;
+        read_lines(conf).ok()?.iter().find_map(|line| {
+            let (option, value) = line.trim().split_once(char::is_whitespace)?;
+            (option == name).then(|| value.trim().to_string())
+        })
+    }
+
+    /// Applies `change` to the lines of `pureftpd.passwd`, writes it back and
+    /// rebuilds the database Pure-FTPd reads.
+    fn modify<F>(&self, change: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Vec<String>) -> io::Result<()>,
+    {
+        let mut lines = read_lines(&self.passwd_path)?;
+        change(&mut lines)?;
+        write_private_lines(&self.passwd_path, &lines)?;
+        self.make_database()
+    }
+
+    fn make_database(&self) -> io::Result<()> {
+        let output = Command::new("pure-pw")
+            .arg("mkdb")
+            .arg(&self.pdb_path)
+            .arg("-f")
+            .arg(&self.passwd_path)
+            .output()
+            .map_err(|err| {
+                io::Error::other(format!(
+                    "saved {}, but pure-pw mkdb failed: {}",
+                    self.passwd_path.display(),
+                    err
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "saved {}, but pure-pw mkdb failed: {}",
+                self.passwd_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl FtpBackend for PureftpdBackend {
+    fn load_users(&self) -> io::Result<Vec<FtpUser>> {
+        Ok(read_lines(&self.passwd_path)?
+            .iter()
+            .filter_map(|line| PureftpdEntry::parse(line))
+            .map(|entry| entry.to_user())
+            .collect())
+    }
+
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()> {
+        let hash = match password {
+            Some(password) => hash_password(password)?,
+            // An invalid hash until a password is set.
+            None => "!".to_string(),
+        };
+        self.modify(|lines| {
+            if Self::position(lines, &user.username).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", user.username),
+                ));
+            }
+            let (uid, gid) = shared_ids(
+                lines
+                    .iter()
+                    .filter_map(|line| PureftpdEntry::parse(line))
+                    .filter_map(|entry| Some((entry.number(UID)?, entry.number(GID)?))),
+            );
+            let mut entry = PureftpdEntry::new(&user.username);
+            entry.fields[PASSWORD] = hash;
+            entry.fields[UID] = uid.to_string();
+            entry.fields[GID] = gid.to_string();
+            entry.apply(user);
+            lines.push(entry.to_line());
+            Ok(())
+        })
+    }
+
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        let hash = password.map(hash_password).transpose()?;
+        self.modify(|lines| {
+            let index = Self::position(lines, old_username)
+                .ok_or_else(|| io::Error::other(format!("{} does not exist", old_username)))?;
+            if old_username != user.username && Self::position(lines, &user.username).is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", user.username),
+                ));
+            }
+            // Parsed by `position` above.
+            let mut entry = PureftpdEntry::parse(&lines[index]).unwrap();
+            entry.apply(user);
+            if let Some(hash) = hash {
+                // A new password leaves a locked account locked.
+                let locked = is_locked_hash(&entry.fields[PASSWORD]);
+                entry.fields[PASSWORD] = lock_hash(&hash, locked)?;
+            }
+            lines[index] = entry.to_line();
+            Ok(())
+        })
+    }
+
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()> {
+        let username = user.username.as_str();
+        self.modify(|lines| {
+            lines.retain(|line| {
+                PureftpdEntry::parse(line).is_none_or(|entry| entry.name() != username)
+            });
+            Ok(())
+        })
+    }
+
+    /// `pure-pw` has no lock, so the hash gets the `!` of a locked system
+    /// password, which no password matches.
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()> {
+        self.modify(|lines| {
+            let index = Self::position(lines, &user.username)
+                .ok_or_else(|| io::Error::other(format!("{} does not exist", user.username)))?;
+            // Parsed by `position` above.
+            let mut entry = PureftpdEntry::parse(&lines[index]).unwrap();
+            entry.fields[PASSWORD] = lock_hash(&entry.fields[PASSWORD], locked)?;
+            lines[index] = entry.to_line();
+            Ok(())
+        })
+    }
+
+    fn describe(&self) -> String {
+        format!("Pure-FTPd ({})", self.passwd_path.display())
+    }
+
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+        upload_warnings(user, owner, group, mode)
+    }
+
+    fn supports_limit(&self, _limit: Limit) -> bool {
+        true
+    }
+
+    /// `TLS` is 0 without TLS, 1 to accept it and 2 or 3 to require it.
+    /// The certificate and key share one file, unless `CertFileAndKey`
+    /// names two.
+    fn tls_config(&self) -> io::Result<TlsConfig> {
+        let level = Self::option("TLS").unwrap_or("0".to_string());
+        let (cert_file, key_file) = match Self::option("CertFileAndKey") {
+            Some(value) => {
+                let files: Vec<&str> = value
+                    .split('"')
+                    .map(str::trim)
+                    .filter(|file| !file.is_empty())
+                    .collect();
+                match files[..] {
+                    [cert_file, key_file] => (cert_file.to_string(), key_file.to_string()),
+                    _ => (value.clone(), value),
+                }
+            }
+            None => {
+                let cert_file = Self::option("CertFile").unwrap_or(DEFAULT_CERT_FILE.to_string());
+                (cert_file.clone(), cert_file)
+            }
+        };
+        Ok(TlsConfig {
+            enabled: level != "0",
+            setting: format!("TLS {}", level),
+            cert_file: Some(PathBuf::from(cert_file)),
+            key_file: Some(PathBuf::from(key_file)),
+        })
+    }
+
+    /// Reads `.ftpquota`, which Pure-FTPd keeps up to date for users with a
+    /// quota, before counting the files.
+    fn used_space(&self, user: &FtpUser) -> Option<(u64, bool)> {
+        fs::read_to_string(Path::new(&user.doc_root).join(QUOTA_FILE))
+            .ok()
+            .and_then(|content| content.split_whitespace().nth(1)?.parse().ok())
+            .map(|bytes| (bytes, true))
+            .or_else(|| directory_size(Path::new(&user.doc_root)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pureftpd/pureftpd.passwd")
+    }
+
+    fn fixture_lines() -> Vec<String> {
+        read_lines(&fixture_path()).unwrap()
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let lines = fixture_lines();
+        assert!(PureftpdEntry::parse(&lines[0]).is_none());
+        for line in &lines[1..3] {
+            assert_eq!(&PureftpdEntry::parse(line).unwrap().to_line(), line);
+        }
+        // Lines of older versions get the missing columns.
+        assert_eq!(
+            PureftpdEntry::parse(&lines[3]).unwrap().to_line(),
+            format!("{}{}", lines[3], ":".repeat(12))
+        );
+        assert!(PureftpdEntry::parse("erin:hash:nobody:2001::/srv/ftp/erin").is_none());
+    }
+
+    #[test]
+    fn load_users_reads_limits_and_addresses() {
+        let users = PureftpdBackend::new(&fixture_path()).load_users().unwrap();
+        assert_eq!(users.len(), 3);
+
+        let bob = &users[0];
+        assert_eq!(bob.doc_root, "/srv/ftp/bob/./");
+        assert_eq!(
+            (bob.uid, bob.gid, bob.locked),
+            (Some(2001), Some(2001), false)
+        );
+        let limits = &bob.limits;
+        assert_eq!(
+            (limits.quota_size, limits.quota_files, limits.max_sessions),
+            (Some(1_048_576), Some(100), Some(3))
+        );
+        assert_eq!(
+            (limits.upload_rate, limits.download_rate),
+            (Some(10240), Some(20480))
+        );
+        assert_eq!(bob.allowed_ips, ["10.0.0.0/8", "192.168.1.5"]);
+        assert_eq!(bob.denied_ips, ["203.0.113.9"]);
+
+        assert!(users[1].locked);
+        assert_eq!(users[1].limits.quota_size, None);
+        assert_eq!(users[1].limits.upload_rate, None);
+        assert!(users[1].allowed_ips.is_empty());
+    }
+
+    #[test]
+    fn apply_keeps_the_columns_it_does_not_edit() {
+        let mut entry = PureftpdEntry::parse(&fixture_lines()[1]).unwrap();
+        let mut user = entry.to_user();
+        user.username = "robert".to_string();
+        user.limits.upload_rate = None;
+        user.limits.quota_size = Some(2048);
+        user.allowed_ips = vec!["10.0.0.1".to_string()];
+        user.denied_ips.clear();
+
+        entry.apply(&user);
+        assert_eq!(
+            entry.to_line(),
+            "robert:$1$bob$hash:2001:2001:Bob Builder:/srv/ftp/bob/./::20480:1:2:3:100:2048:192.0.2.1::10.0.0.1::0800-1800"
+        );
+    }
+}
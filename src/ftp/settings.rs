@@ -0,0 +1,859 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::ftp::vsftpd::{parse_bool, ConfigFile};
+use crate::ftp::{EditWindowStyles, FtpTable, SettingsStyles, TableStyles, VsftpdBackend};
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyModifiers, MouseEvent},
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, TableState,
+    },
+};
+use std::collections::HashSet;
+use std::io;
+use std::process::Command;
+use tui_textarea::{CursorMove, TextArea};
+
+const SERVICE: &str = "vsftpd";
+/// Unchanged lines shown around each change in the review.
+const DIFF_CONTEXT: usize = 2;
+const REVIEW_INFO_TEXT: &str = " (Enter) Write | (Esc) Back to editing ";
+const RESTART_INFO_TEXT: &str = " (y) Restart | (n) Later ";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OptionType {
+    Bool,
+    Number {
+        min: u64,
+        max: u64,
+    },
+    /// A umask or file mode such as `022`.
+    Octal,
+    Path,
+    Text,
+}
+
+/// An option of `vsftpd.conf` as documented in vsftpd.conf(5).
+pub struct OptionSpec {
+    pub name: &'static str,
+    pub section: &'static str,
+    pub kind: OptionType,
+    /// The built-in value, empty when vsftpd has none.
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+const PORT: OptionType = OptionType::Number { min: 0, max: 65535 };
+const COUNT: OptionType = OptionType::Number {
+    min: 0,
+    max: u32::MAX as u64,
+};
+
+const fn option(
+    section: &'static str,
+    name: &'static str,
+    kind: OptionType,
+    default: &'static str,
+    description: &'static str,
+) -> OptionSpec {
+    OptionSpec {
+        name,
+        section,
+        kind,
+        default,
+        description,
+    }
+}
+
+#[rustfmt::skip]
+pub const OPTIONS: &[OptionSpec] = &[
+    option("Network", "listen", OptionType::Bool, "NO", "Run standalone on IPv4 instead of from inetd"),
+    option("Network", "listen_ipv6", OptionType::Bool, "NO", "Run standalone on IPv6 (and IPv4 on dual stack hosts)"),
+    option("Network", "listen_port", PORT, "21", "Port for incoming control connections"),
+    option("Network", "listen_address", OptionType::Text, "", "IPv4 address to listen on, all when empty"),
+    option("Network", "ftpd_banner", OptionType::Text, "", "Greeting shown when a client connects"),
+    option("Access", "anonymous_enable", OptionType::Bool, "NO", "Allow anonymous logins as ftp or anonymous"),
+    option("Access", "local_enable", OptionType::Bool, "NO", "Allow system accounts to log in"),
+    option("Access", "write_enable", OptionType::Bool, "NO", "Allow commands that change the filesystem"),
+    option("Access", "local_umask", OptionType::Octal, "077", "Umask for files created by local users"),
+    option("Access", "file_open_mode", OptionType::Octal, "0666", "Permissions of uploaded files before the umask"),
+    option("Access", "anon_upload_enable", OptionType::Bool, "NO", "Allow anonymous users to upload files"),
+    option("Access", "anon_mkdir_write_enable", OptionType::Bool, "NO", "Allow anonymous users to create directories"),
+    option("Access", "anon_root", OptionType::Path, "", "Directory anonymous users start in"),
+    option("Access", "no_anon_password", OptionType::Bool, "NO", "Do not ask anonymous users for a password"),
+    option("Chroot", "chroot_local_user", OptionType::Bool, "NO", "Chroot local users into their home directory"),
+    option("Chroot", "chroot_list_enable", OptionType::Bool, "NO", "Invert chroot_local_user for the users in chroot_list_file"),
+    option("Chroot", "chroot_list_file", OptionType::Path, "/etc/vsftpd.chroot_list", "Users chrooted, or exempted with chroot_local_user=YES"),
+    option("Chroot", "allow_writeable_chroot", OptionType::Bool, "NO", "Allow a chroot the user can write to"),
+    option("Chroot", "passwd_chroot_enable", OptionType::Bool, "NO", "Chroot at /./ in the home directory from /etc/passwd"),
+    option("Passive mode", "pasv_enable", OptionType::Bool, "YES", "Allow passive data connections"),
+    option("Passive mode", "pasv_min_port", PORT, "0", "Lowest port for passive connections, 0 for any"),
+    option("Passive mode", "pasv_max_port", PORT, "0", "Highest port for passive connections, 0 for any"),
+    option("Passive mode", "pasv_address", OptionType::Text, "", "Address announced for passive connections, e.g. behind NAT"),
+    option("Passive mode", "pasv_addr_resolve", OptionType::Bool, "NO", "Resolve pasv_address as a host name"),
+    option("Passive mode", "port_enable", OptionType::Bool, "YES", "Allow active (PORT) data connections"),
+    option("Passive mode", "connect_from_port_20", OptionType::Bool, "NO", "Make active data connections from port 20"),
+    option("TLS", "ssl_enable", OptionType::Bool, "NO", "Support FTPS connections"),
+    option("TLS", "rsa_cert_file", OptionType::Path, "/usr/share/ssl/certs/vsftpd.pem", "Certificate for TLS connections"),
+    option("TLS", "rsa_private_key_file", OptionType::Path, "", "Private key, read from rsa_cert_file when empty"),
+    option("TLS", "force_local_logins_ssl", OptionType::Bool, "YES", "Require TLS for the logins of local users"),
+    option("TLS", "force_local_data_ssl", OptionType::Bool, "YES", "Require TLS for the data of local users"),
+    option("TLS", "allow_anon_ssl", OptionType::Bool, "NO", "Allow anonymous users to use TLS"),
+    option("TLS", "ssl_tlsv1", OptionType::Bool, "YES", "Allow TLS v1 connections"),
+    option("TLS", "ssl_sslv2", OptionType::Bool, "NO", "Allow SSL v2 connections"),
+    option("TLS", "ssl_sslv3", OptionType::Bool, "NO", "Allow SSL v3 connections"),
+    option("TLS", "require_ssl_reuse", OptionType::Bool, "YES", "Require data connections to reuse the TLS session"),
+    option("TLS", "ssl_ciphers", OptionType::Text, "DES-CBC3-SHA", "OpenSSL cipher list"),
+    option("Users", "userlist_enable", OptionType::Bool, "NO", "Check logins against userlist_file"),
+    option("Users", "userlist_deny", OptionType::Bool, "YES", "Refuse the listed users instead of allowing only them"),
+    option("Users", "userlist_file", OptionType::Path, "/etc/vsftpd.user_list", "The user list"),
+    option("Users", "user_config_dir", OptionType::Path, "", "Directory with a per-user configuration file for each user"),
+    option("Users", "user_sub_token", OptionType::Text, "", "Token replaced by the user name in local_root"),
+    option("Users", "local_root", OptionType::Path, "", "Directory local users start in instead of their home"),
+    option("Users", "pam_service_name", OptionType::Text, "ftp", "PAM service used to authenticate"),
+    option("Limits", "max_clients", COUNT, "0", "Maximum number of clients, 0 for no limit"),
+    option("Limits", "max_per_ip", COUNT, "0", "Maximum clients from one address, 0 for no limit"),
+    option("Limits", "local_max_rate", COUNT, "0", "Transfer rate of local users in bytes per second, 0 for no limit"),
+    option("Limits", "anon_max_rate", COUNT, "0", "Transfer rate of anonymous users in bytes per second, 0 for no limit"),
+    option("Limits", "idle_session_timeout", COUNT, "300", "Seconds an idle session stays connected"),
+    option("Limits", "data_connection_timeout", COUNT, "300", "Seconds a stalled data transfer stays connected"),
+    option("Logging", "xferlog_enable", OptionType::Bool, "NO", "Log uploads and downloads"),
+    option("Logging", "xferlog_file", OptionType::Path, "/var/log/xferlog", "Transfer log in xferlog format"),
+    option("Logging", "xferlog_std_format", OptionType::Bool, "NO", "Write the transfer log in the wu-ftpd xferlog format"),
+    option("Logging", "vsftpd_log_file", OptionType::Path, "/var/log/vsftpd.log", "vsftpd style log"),
+    option("Logging", "dual_log_enable", OptionType::Bool, "NO", "Write both the xferlog and the vsftpd log"),
+    option("Logging", "log_ftp_protocol", OptionType::Bool, "NO", "Log every FTP command and response"),
+];
+
+/// The type and default of `name`; options missing from `OPTIONS` are
+/// edited as text.
+fn spec(name: &str) -> Option<&'static OptionSpec> {
+    OPTIONS.iter().find(|spec| spec.name == name)
+}
+
+/// Checks `value` against the type of the option and returns it in the
+/// form vsftpd documents, e.g. `YES` for booleans.
+pub fn validate_value(kind: OptionType, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    match kind {
+        OptionType::Bool => match value.to_uppercase().as_str() {
+            "YES" | "TRUE" | "1" => Ok("YES".to_string()),
+            "NO" | "FALSE" | "0" => Ok("NO".to_string()),
+            _ => Err("expected YES or NO".to_string()),
+        },
+        OptionType::Number { min, max } => match value.parse::<u64>() {
+            Ok(number) if (min..=max).contains(&number) => Ok(number.to_string()),
+            _ => Err(format!("expected a number from {} to {}", min, max)),
+        },
+        OptionType::Octal => {
+            if (3..=4).contains(&value.len()) && u32::from_str_radix(value, 8).is_ok() {
+                Ok(value.to_string())
+            } else {
+                Err("expected an octal mode like 022".to_string())
+            }
+        }
+        OptionType::Path => {
+            if value.starts_with('/') {
+                Ok(value.to_string())
+            } else {
+                Err("expected an absolute path".to_string())
+            }
+        }
+        OptionType::Text => {
+            if value.is_empty() {
+                Err("empty, reset the option to use the default".to_string())
+            } else {
+                Ok(value.to_string())
+            }
+        }
+    }
+}
+
+/// Problems with combinations of options that vsftpd refuses or that cannot
+/// work.
+pub fn check_config(conf: &ConfigFile) -> Vec<String> {
+    let mut errors = Vec::new();
+    let value = |name: &str| {
+        conf.get(name)
+            .or_else(|| spec(name).map(|spec| spec.default))
+            .unwrap_or("")
+    };
+
+    for spec in OPTIONS {
+        if let Some(current) = conf.get(spec.name) {
+            if let Err(message) = validate_value(spec.kind, current) {
+                errors.push(format!("{}: {}", spec.name, message));
+            }
+        }
+    }
+    if parse_bool(value("listen")) && parse_bool(value("listen_ipv6")) {
+        errors.push("listen and listen_ipv6 cannot both be YES".to_string());
+    }
+    let min_port = value("pasv_min_port").parse::<u64>().unwrap_or(0);
+    let max_port = value("pasv_max_port").parse::<u64>().unwrap_or(0);
+    if min_port != 0 && max_port != 0 && min_port > max_port {
+        errors.push("pasv_min_port is above pasv_max_port".to_string());
+    }
+    if parse_bool(value("ssl_enable")) && !std::path::Path::new(value("rsa_cert_file")).exists() {
+        errors.push(format!(
+            "ssl_enable=YES but rsa_cert_file {} does not exist",
+            value("rsa_cert_file")
+        ));
+    }
+    if !parse_bool(value("local_enable")) && !parse_bool(value("anonymous_enable")) {
+        errors.push("local_enable and anonymous_enable are both NO, nobody can log in".to_string());
+    }
+    errors
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+    /// Unchanged lines left out of the review.
+    Skipped(usize),
+}
+
+/// A line diff of `old` and `new` through their longest common subsequence,
+/// keeping `DIFF_CONTEXT` unchanged lines around each change.
+pub fn line_diff(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let (n, m) = (old.len(), new.len());
+    let mut common = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    let mut result = Vec::new();
+    let mut skipped = 0;
+    for (index, line) in lines.into_iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|change| change.abs_diff(index) <= DIFF_CONTEXT);
+        if near_change {
+            if skipped > 0 {
+                result.push(DiffLine::Skipped(skipped));
+                skipped = 0;
+            }
+            result.push(line);
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        result.push(DiffLine::Skipped(skipped));
+    }
+    result
+}
+
+/// Restarts vsftpd through systemd, or the `service` wrapper without it.
+fn restart_service() -> io::Result<()> {
+    let output = match Command::new("systemctl")
+        .arg("restart")
+        .arg(SERVICE)
+        .output()
+    {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Command::new("service")
+            .arg(SERVICE)
+            .arg("restart")
+            .output()?,
+        result => result?,
+    };
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A row of the settings table.
+struct SettingRow {
+    name: String,
+    section: &'static str,
+    kind: OptionType,
+    default: &'static str,
+    description: &'static str,
+}
+
+/// Edits the server options of `vsftpd.conf`. Changes are kept in memory
+/// until they are reviewed as a diff and written.
+pub struct VsftpdSettings {
+    state: TableState,
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    settings_styles: SettingsStyles,
+    input_styles: EditWindowStyles,
+    backend: VsftpdBackend,
+    original: ConfigFile,
+    conf: ConfigFile,
+    rows: Vec<SettingRow>,
+    /// The value being typed for the selected option.
+    editor: Option<TextArea<'static>>,
+    editor_error: Option<String>,
+    /// The changes shown for confirmation before writing.
+    review: Option<Vec<DiffLine>>,
+    restart_prompt: bool,
+    message: Option<String>,
+}
+
+impl ScreenTrait for VsftpdSettings {
+    fn new() -> Self {
+        let backend = VsftpdBackend::from_env();
+        let conf = ConfigFile::new(backend.conf_path());
+        let mut settings = Self {
+            state: TableState::default().with_selected(0),
+            scroll_state: ScrollbarState::new(0),
+            styles: TableStyles::new(),
+            settings_styles: SettingsStyles::new(),
+            input_styles: EditWindowStyles::new(),
+            backend,
+            original: conf.clone(),
+            conf,
+            rows: Vec::new(),
+            editor: None,
+            editor_error: None,
+            review: None,
+            restart_prompt: false,
+            message: None,
+        };
+        settings.reload();
+        settings
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let editor_height = if self.editor.is_some() { 3 } else { 0 };
+        let vertical = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(editor_height),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+
+        self.render_table(rects[0], buf);
+        self.render_scrollbar(rects[0], buf);
+        self.render_description(rects[1], buf);
+        self.render_editor(rects[2], buf);
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, rects[3], buf);
+        self.render_footer(
+            rects[4],
+            buf,
+            vec![
+                ("<Esc>", "Return to FTP users (discards unsaved changes)"),
+                ("<Enter>", "Edit / toggle"),
+                ("<x>", "Reset to default"),
+                ("<Ctrl+s>", "Review and write"),
+                ("<Ctrl+r>", "Reload"),
+            ],
+        );
+
+        if let Some(review) = &self.review {
+            self.render_review(rects[0], buf, review);
+        } else if self.restart_prompt {
+            self.render_restart_prompt(rects[0], buf);
+        }
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        let popup_open = self.editor.is_some() || self.review.is_some() || self.restart_prompt;
+        if key.code == KeyCode::Esc && !popup_open {
+            Some(Screen::FtpTable(FtpTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl VsftpdSettings {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        let ctrl_pressed = key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.restart_prompt {
+            match key.code {
+                KeyCode::Char('y') => {
+                    self.restart_prompt = false;
+                    self.message = Some(match restart_service() {
+                        Ok(_) => format!("Restarted {}", SERVICE),
+                        Err(err) => {
+                            tracing::error!("Error restarting {}: {}", SERVICE, err);
+                            format!("Error restarting {}: {}", SERVICE, err)
+                        }
+                    });
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.restart_prompt = false;
+                }
+                _ => {}
+            }
+        } else if self.review.is_some() {
+            match key.code {
+                KeyCode::Enter => {
+                    self.review = None;
+                    self.write();
+                }
+                KeyCode::Esc => {
+                    self.review = None;
+                }
+                _ => {}
+            }
+        } else if let Some(editor) = &mut self.editor {
+            match key.code {
+                KeyCode::Enter => self.apply_editor(),
+                KeyCode::Esc => {
+                    self.editor = None;
+                    self.editor_error = None;
+                }
+                _ => {
+                    if editor.input(key) {
+                        self.editor_error = None;
+                    }
+                }
+            }
+        } else {
+            match key.code {
+                KeyCode::Char('s') if ctrl_pressed => self.start_review(),
+                KeyCode::Char('r') if ctrl_pressed => self.reload(),
+                KeyCode::Char('j') | KeyCode::Down => {
+                    let i = self.state.selected().map_or(0, |i| i + 1);
+                    self.select(if i < self.rows.len() { i } else { 0 });
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    let i = match self.state.selected() {
+                        Some(i) if i > 0 => i - 1,
+                        _ => self.rows.len().saturating_sub(1),
+                    };
+                    self.select(i);
+                }
+                KeyCode::Char('g') | KeyCode::Home => self.select(0),
+                KeyCode::Char('G') | KeyCode::End => {
+                    self.select(self.rows.len().saturating_sub(1));
+                }
+                KeyCode::Enter => self.edit_selected(),
+                KeyCode::Char('x') | KeyCode::Delete => {
+                    if let Some(row) = self.selected_row() {
+                        let name = row.name.clone();
+                        self.conf.remove(&name);
+                        self.message = Some(format!("{} reset to its default", name));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn reload(&mut self) {
+        match self.backend.conf() {
+            Ok(conf) => {
+                self.message = Some(format!("Editing {}", conf.path().display()));
+                self.original = conf.clone();
+                self.conf = conf;
+            }
+            Err(err) => {
+                tracing::error!("Error reading vsftpd.conf: {}", err);
+                self.message = Some(format!("Error reading vsftpd.conf: {}", err));
+            }
+        }
+
+        // Options tuixel does not know are listed after the known ones.
+        let known: HashSet<&str> = OPTIONS.iter().map(|spec| spec.name).collect();
+        let mut other: Vec<String> = self
+            .conf
+            .lines()
+            .iter()
+            .filter_map(|line| {
+                let line = line.trim();
+                if line.starts_with('#') {
+                    return None;
+                }
+                let (name, _) = line.split_once('=')?;
+                Some(name.trim().to_string())
+            })
+            .filter(|name| !known.contains(name.as_str()))
+            .collect();
+        other.sort();
+        other.dedup();
+
+        self.rows = OPTIONS
+            .iter()
+            .map(|spec| SettingRow {
+                name: spec.name.to_string(),
+                section: spec.section,
+                kind: spec.kind,
+                default: spec.default,
+                description: spec.description,
+            })
+            .chain(other.into_iter().map(|name| SettingRow {
+                name,
+                section: "Other",
+                kind: OptionType::Text,
+                default: "",
+                description: "Not known to tuixel, edited as text",
+            }))
+            .collect();
+        let selected = self.state.selected().unwrap_or(0);
+        self.select(selected.min(self.rows.len().saturating_sub(1)));
+    }
+
+    fn selected_row(&self) -> Option<&SettingRow> {
+        self.state.selected().and_then(|i| self.rows.get(i))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.state.select(Some(index));
+        self.scroll_state = ScrollbarState::new(self.rows.len().saturating_sub(1)).position(index);
+    }
+
+    /// Toggles a boolean, or opens the editor for other types.
+    fn edit_selected(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        let name = row.name.clone();
+        let current = self.conf.get(&name).unwrap_or(row.default).to_string();
+        if row.kind == OptionType::Bool {
+            let value = if parse_bool(&current) { "NO" } else { "YES" };
+            self.conf.set(&name, value);
+            self.message = Some(format!("{}={}", name, value));
+        } else {
+            let mut editor = TextArea::from([current]);
+            editor.set_cursor_line_style(Style::default());
+            editor.set_cursor_style(self.input_styles.cursor_style);
+            editor.move_cursor(CursorMove::End);
+            self.editor = Some(editor);
+            self.editor_error = None;
+        }
+    }
+
+    fn apply_editor(&mut self) {
+        let (Some(row), Some(editor)) = (self.selected_row(), &self.editor) else {
+            return;
+        };
+        let value = editor.lines().first().cloned().unwrap_or_default();
+        match validate_value(row.kind, &value) {
+            Ok(value) => {
+                let name = row.name.clone();
+                self.conf.set(&name, &value);
+                self.message = Some(format!("{}={}", name, value));
+                self.editor = None;
+            }
+            Err(message) => self.editor_error = Some(message),
+        }
+    }
+
+    fn start_review(&mut self) {
+        let errors = check_config(&self.conf);
+        if !errors.is_empty() {
+            self.message = Some(format!("Not written: {}", errors.join("; ")));
+        } else if self.conf.lines() == self.original.lines() {
+            self.message = Some("No changes to write".to_string());
+        } else {
+            self.review = Some(line_diff(self.original.lines(), self.conf.lines()));
+        }
+    }
+
+    fn write(&mut self) {
+        match self.conf.save() {
+            Ok(_) => {
+                self.original = self.conf.clone();
+                self.message = Some(format!("Wrote {}", self.conf.path().display()));
+                self.restart_prompt = true;
+            }
+            Err(err) => {
+                tracing::error!("Error writing vsftpd.conf: {}", err);
+                self.message = Some(format!("Error writing vsftpd.conf: {}", err));
+            }
+        }
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let header = ["Section", "Option", "Value", "Default"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(1);
+        let rows = self.rows.iter().enumerate().map(|(i, row)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            let value = match self.conf.get(&row.name) {
+                Some(value) if self.original.get(&row.name) != Some(value) => {
+                    Cell::from(Span::styled(
+                        format!("{} *", value),
+                        self.settings_styles.changed_value_style,
+                    ))
+                }
+                Some(value) => Cell::from(value.to_string()),
+                None if self.original.get(&row.name).is_some() => Cell::from(Span::styled(
+                    "(default) *",
+                    self.settings_styles.changed_value_style,
+                )),
+                None => Cell::from(Span::styled(
+                    "(default)",
+                    self.settings_styles.default_value_style,
+                )),
+            };
+            Row::new([
+                Cell::from(row.section),
+                Cell::from(row.name.as_str()),
+                value,
+                Cell::from(row.default),
+            ])
+            .style(self.styles.row_style.bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Length(13),
+                Constraint::Length(24),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(self.styles.selected_row_style)
+        .style(self.styles.row_style.bg(self.styles.normal_row_color))
+        .highlight_symbol(" ▌ ")
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+
+    fn render_description(&self, area: Rect, buf: &mut Buffer) {
+        let description = self
+            .selected_row()
+            .map(|row| format!(" {}: {}", row.name, row.description))
+            .unwrap_or_default();
+        Widget::render(
+            Paragraph::new(description).style(self.settings_styles.default_value_style),
+            area,
+            buf,
+        );
+    }
+
+    fn render_editor(&mut self, area: Rect, buf: &mut Buffer) {
+        let name = self
+            .selected_row()
+            .map(|row| row.name.clone())
+            .unwrap_or_default();
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        let (title, border_style) = match &self.editor_error {
+            Some(error) => (
+                format!("{} ({})", name, error),
+                self.input_styles.invalid_input_style,
+            ),
+            None => (
+                format!("{} (Enter) Apply | (Esc) Cancel", name),
+                self.input_styles.selected_input_border_style,
+            ),
+        };
+        editor.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(title),
+        );
+        editor.render(area, buf);
+    }
+
+    fn render_review(&self, area: Rect, buf: &mut Buffer, review: &[DiffLine]) {
+        let area = popup_area(area, 80, review.len() as u16 + 2);
+        Widget::render(Clear, area, buf);
+        let lines: Vec<Line> = review
+            .iter()
+            .map(|line| match line {
+                DiffLine::Same(text) => Line::styled(
+                    format!("  {}", text),
+                    self.settings_styles.context_line_style,
+                ),
+                DiffLine::Added(text) => {
+                    Line::styled(format!("+ {}", text), self.settings_styles.added_line_style)
+                }
+                DiffLine::Removed(text) => Line::styled(
+                    format!("- {}", text),
+                    self.settings_styles.removed_line_style,
+                ),
+                DiffLine::Skipped(count) => Line::styled(
+                    format!("  … {} unchanged lines", count),
+                    self.settings_styles.default_value_style,
+                ),
+            })
+            .collect();
+        let review = Paragraph::new(Text::from(lines)).block(
+            Block::default()
+                .title(format!(" Changes to {} ", self.conf.path().display()))
+                .title_bottom(Line::from(REVIEW_INFO_TEXT).centered())
+                .style(self.input_styles.window_style)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(self.input_styles.window_border_style),
+        );
+        Widget::render(review, area, buf);
+    }
+
+    fn render_restart_prompt(&self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area, 50, 3);
+        Widget::render(Clear, area, buf);
+        let prompt = Paragraph::new(format!("Restart {} to apply the changes?", SERVICE))
+            .centered()
+            .block(
+                Block::default()
+                    .title_bottom(Line::from(RESTART_INFO_TEXT).centered())
+                    .style(self.input_styles.window_style)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Double)
+                    .border_style(self.input_styles.window_border_style),
+            );
+        Widget::render(prompt, area, buf);
+    }
+}
+
+fn popup_area(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Max(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn validate_value_checks_and_normalizes() {
+        assert_eq!(
+            validate_value(OptionType::Bool, " true "),
+            Ok("YES".to_string())
+        );
+        assert_eq!(validate_value(OptionType::Bool, "0"), Ok("NO".to_string()));
+        assert!(validate_value(OptionType::Bool, "maybe").is_err());
+        assert_eq!(validate_value(PORT, "021"), Ok("21".to_string()));
+        assert!(validate_value(PORT, "65536").is_err());
+        assert!(validate_value(PORT, "-1").is_err());
+        assert_eq!(
+            validate_value(OptionType::Octal, "0755"),
+            Ok("0755".to_string())
+        );
+        assert!(validate_value(OptionType::Octal, "089").is_err());
+        assert!(validate_value(OptionType::Octal, "22").is_err());
+        assert!(validate_value(OptionType::Path, "var/log/xferlog").is_err());
+        assert!(validate_value(OptionType::Text, "  ").is_err());
+    }
+
+    #[test]
+    fn check_config_reports_conflicting_options() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/vsftpd/vsftpd.conf");
+        let mut conf = ConfigFile::load(&fixture).unwrap();
+        assert!(check_config(&conf).is_empty());
+
+        conf.set("listen_ipv6", "YES");
+        conf.set("pasv_min_port", "50000");
+        conf.set("pasv_max_port", "40000");
+        conf.set("max_clients", "lots");
+        conf.set("ssl_enable", "YES");
+        conf.set("rsa_cert_file", "/nonexistent/tuixel.pem");
+        conf.set("local_enable", "NO");
+        assert_eq!(
+            check_config(&conf),
+            [
+                "max_clients: expected a number from 0 to 4294967295",
+                "listen and listen_ipv6 cannot both be YES",
+                "pasv_min_port is above pasv_max_port",
+                "ssl_enable=YES but rsa_cert_file /nonexistent/tuixel.pem does not exist",
+                "local_enable and anonymous_enable are both NO, nobody can log in",
+            ]
+        );
+    }
+
+    #[test]
+    fn line_diff_keeps_context_around_changes() {
+        let old = lines("a b c d e f g h i j");
+        let new = lines("a b c d x f g h i j k");
+        let same = |line: &str| DiffLine::Same(line.to_string());
+
+        assert_eq!(
+            line_diff(&old, &new),
+            [
+                DiffLine::Skipped(2),
+                same("c"),
+                same("d"),
+                DiffLine::Removed("e".to_string()),
+                DiffLine::Added("x".to_string()),
+                same("f"),
+                same("g"),
+                DiffLine::Skipped(1),
+                same("i"),
+                same("j"),
+                DiffLine::Added("k".to_string()),
+            ]
+        );
+        assert_eq!(line_diff(&old, &old), [DiffLine::Skipped(10)]);
+    }
+}
@@ -0,0 +1,650 @@
+use crate::ftp::backend::{
+    find_binary, group_id, group_members, read_lines, run_tool, set_system_expiry, shadow_status,
+    system_user, FtpBackend, PasswdEntry,
+};
+use crate::ftp::{FtpUser, Protocol};
+use crate::log::PROJECT_NAME;
+use lazy_static::lazy_static;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const DEFAULT_CONF: &str = "/etc/ssh/sshd_config";
+/// The SFTP server built into sshd, which needs no binaries in the chroot.
+const SFTP_COMMAND: &str = "internal-sftp";
+/// Shell of the accounts created for SFTP.
+const NOLOGIN_SHELL: &str = "/usr/sbin/nologin";
+/// Shells of accounts that cannot log in except through SFTP.
+const NOLOGIN_SHELLS: [&str; 2] = ["nologin", "false"];
+/// Unit names of the SSH server on Red Hat and on Debian based systems.
+const SSH_SERVICES: [&str; 2] = ["sshd", "ssh"];
+const INDENT: &str = "    ";
+
+lazy_static! {
+    pub static ref SSHD_CONF_ENV: String = format!("{}_SSHD_CONF", PROJECT_NAME.clone());
+}
+
+/// What a `Match` line selects. Blocks with several or other criteria are
+/// left alone.
+#[derive(PartialEq)]
+enum Criterion {
+    User,
+    Group,
+    Other,
+}
+
+/// A `Match` block of `sshd_config`, from its `Match` line to the last line
+/// before the next one that is not blank or a comment.
+struct MatchBlock {
+    start: usize,
+    end: usize,
+    criterion: Criterion,
+    names: Vec<String>,
+    chroot: Option<String>,
+    /// Whether `ForceCommand internal-sftp` limits the block to SFTP.
+    sftp_only: bool,
+    /// Users refused by `DenyUsers`, which is how tuixel locks an account.
+    denied: Vec<String>,
+}
+
+impl MatchBlock {
+    fn lists_user(&self, username: &str) -> bool {
+        self.sftp_only
+            && self.criterion == Criterion::User
+            && self.names.iter().any(|name| name == username)
+    }
+}
+
+/// Splits a line into its lowercased keyword and its unquoted value.
+/// Keywords are separated from values by white space or `=`.
+fn directive(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (keyword, value) = line
+        .split_once(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or((line, ""));
+    let value = value
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+        .trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((keyword.to_lowercase(), value.to_string()))
+}
+
+fn match_blocks(lines: &[String]) -> Vec<MatchBlock> {
+    let mut blocks: Vec<MatchBlock> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some((keyword, value)) = directive(line) else {
+            continue;
+        };
+        if keyword == "match" {
+            let tokens: Vec<&str> = value.split_whitespace().collect();
+            let (criterion, names) = match tokens[..] {
+                [kind, names] if kind.eq_ignore_ascii_case("user") => (Criterion::User, names),
+                [kind, names] if kind.eq_ignore_ascii_case("group") => (Criterion::Group, names),
+                _ => (Criterion::Other, ""),
+            };
+            blocks.push(MatchBlock {
+                start: index,
+                end: index + 1,
+                criterion,
+                names: names
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                chroot: None,
+                sftp_only: false,
+                denied: Vec::new(),
+            });
+        } else if let Some(block) = blocks.last_mut() {
+            block.end = index + 1;
+            match keyword.as_str() {
+                // sshd keeps the first value it finds for a keyword.
+                "chrootdirectory" if block.chroot.is_none() => block.chroot = Some(value),
+                "forcecommand" => {
+                    block.sftp_only = value.split_whitespace().next() == Some(SFTP_COMMAND);
+                }
+                "denyusers" => block
+                    .denied
+                    .extend(value.split_whitespace().map(str::to_string)),
+                _ => {}
+            }
+        }
+    }
+    blocks
+}
+
+/// Wildcards and negations select users tuixel cannot list.
+fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '!'])
+}
+
+/// Replaces the `%h`, `%u`, `%U` and `%%` tokens sshd expands in
+/// `ChrootDirectory`.
+fn expand_chroot(pattern: &str, entry: &PasswdEntry) -> String {
+    let mut path = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => path.push_str(&entry.home),
+            Some('u') => path.push_str(&entry.name),
+            Some('U') => path.push_str(&entry.uid.to_string()),
+            Some('%') => path.push('%'),
+            Some(other) => {
+                path.push('%');
+                path.push(other);
+            }
+            None => path.push('%'),
+        }
+    }
+    path
+}
+
+/// Writes `path` as a `ChrootDirectory` value.
+fn chroot_value(path: &str) -> String {
+    let path = path.replace('%', "%%");
+    if path.contains(char::is_whitespace) {
+        format!("\"{}\"", path)
+    } else {
+        path
+    }
+}
+
+/// Why sshd refuses `directory` as a chroot or as a directory above one,
+/// when it is owned by `owner` with `mode`.
+fn ownership_problem(directory: &Path, owner: u32, mode: u32) -> Option<String> {
+    if owner != 0 {
+        Some(format!("{} is not owned by root", directory.display()))
+    } else if mode & 0o022 != 0 {
+        Some(format!(
+            "{} is writable by group or others",
+            directory.display()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks the rules sshd enforces on a `ChrootDirectory`: it and every
+/// directory above it must be owned by root and not writable by group or
+/// others. Directories that do not exist yet are skipped.
+pub fn chroot_problems(path: &Path) -> Vec<String> {
+    path.ancestors()
+        .filter_map(|directory| {
+            let metadata = fs::metadata(directory).ok()?;
+            ownership_problem(directory, metadata.uid(), metadata.mode())
+        })
+        .collect()
+}
+
+/// Sets the password through `chpasswd`, which hashes it the way the system
+/// is configured to and keeps it off the command line.
+fn set_password(username: &str, password: &str) -> io::Result<()> {
+    let mut child = Command::new("chpasswd")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::other(format!("chpasswd failed: {}", err)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}:{}", username, password)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "chpasswd failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn reload_sshd() -> io::Result<()> {
+    let mut error = String::new();
+    for service in SSH_SERVICES {
+        let output = match Command::new("systemctl")
+            .arg("reload")
+            .arg(service)
+            .output()
+        {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Command::new("service")
+                .arg(service)
+                .arg("reload")
+                .output()?,
+            result => result?,
+        };
+        if output.status.success() {
+            return Ok(());
+        }
+        error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    }
+    Err(io::Error::other(error))
+}
+
+/// SFTP-only system accounts, chrooted by `Match User` and `Match Group`
+/// blocks of `sshd_config` with `ForceCommand internal-sftp`. Users get a
+/// `Match User` block of their own; group blocks are read but not changed.
+pub struct SftpBackend {
+    conf_path: PathBuf,
+}
+
+impl SftpBackend {
+    pub fn new(conf_path: &Path) -> Self {
+        Self {
+            conf_path: conf_path.to_path_buf(),
+        }
+    }
+
+    /// Uses `TUIXEL_SSHD_CONF`, or `/etc/ssh/sshd_config`. Returns `None`
+    /// when the file does not exist.
+    pub fn from_env() -> Option<Self> {
+        let conf_path = std::env::var(SSHD_CONF_ENV.clone())
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONF));
+        conf_path.exists().then(|| Self::new(&conf_path))
+    }
+
+    fn to_user(entry: &PasswdEntry, chroot: Option<&str>) -> FtpUser {
+        let doc_root = match chroot {
+            Some(chroot) if !chroot.eq_ignore_ascii_case("none") => expand_chroot(chroot, entry),
+            _ => entry.home.clone(),
+        };
+        FtpUser {
+            protocol: Protocol::Sftp,
+            uid: Some(entry.uid),
+            gid: Some(entry.gid),
+            shell: Some(entry.shell.clone()),
+            expires: shadow_status(&entry.name).1,
+            ..FtpUser::new(&entry.name, &doc_root)
+        }
+    }
+
+    /// The accounts of the SFTP-only blocks, with the chroot of the first
+    /// block matching each, as sshd picks it.
+    fn sftp_users(lines: &[String]) -> Vec<FtpUser> {
+        let mut users: Vec<FtpUser> = Vec::new();
+        for block in match_blocks(lines).iter().filter(|block| block.sftp_only) {
+            let names: Vec<String> = match block.criterion {
+                Criterion::User => block.names.clone(),
+                Criterion::Group => block
+                    .names
+                    .iter()
+                    .filter(|group| !is_pattern(group))
+                    .flat_map(|group| group_members(group))
+                    .collect(),
+                Criterion::Other => continue,
+            };
+            for name in names.iter().filter(|name| !is_pattern(name)) {
+                if users.iter().any(|user| &user.username == name) {
+                    continue;
+                }
+                if let Some(entry) = system_user(name) {
+                    let mut user = Self::to_user(&entry, block.chroot.as_deref());
+                    user.locked = block.denied.contains(name);
+                    users.push(user);
+                }
+            }
+        }
+        users
+    }
+
+    /// Removes `username` from the SFTP `Match User` blocks. A block left
+    /// without users goes, with the blank line before it.
+    fn remove_user(lines: &mut Vec<String>, username: &str) {
+        // Backwards, so the positions of the earlier blocks stay valid.
+        for block in match_blocks(lines).iter().rev() {
+            if !block.lists_user(username) {
+                continue;
+            }
+            let names: Vec<&str> = block
+                .names
+                .iter()
+                .map(String::as_str)
+                .filter(|name| *name != username)
+                .collect();
+            if names.is_empty() {
+                let start = match block.start.checked_sub(1) {
+                    Some(previous) if lines[previous].trim().is_empty() => previous,
+                    _ => block.start,
+                };
+                lines.drain(start..block.end);
+            } else {
+                lines[block.start] = format!("Match User {}", names.join(","));
+            }
+        }
+    }
+
+    /// Adds a `Match User` block for `user` in front of the other blocks, so
+    /// its chroot wins over the one of a group block.
+    fn insert_user_block(lines: &mut Vec<String>, user: &FtpUser) {
+        let mut block = vec![
+            format!("Match User {}", user.username),
+            format!("{}ChrootDirectory {}", INDENT, chroot_value(&user.doc_root)),
+            format!("{}ForceCommand {}", INDENT, SFTP_COMMAND),
+            format!("{}AllowTcpForwarding no", INDENT),
+            format!("{}X11Forwarding no", INDENT),
+        ];
+        if user.locked {
+            block.insert(1, deny_line(&user.username));
+        }
+        match match_blocks(lines).first() {
+            Some(first) => {
+                // Comments right above a block belong to it.
+                let mut index = first.start;
+                while index > 0 && lines[index - 1].trim_start().starts_with('#') {
+                    index -= 1;
+                }
+                lines.splice(index..index, block.into_iter().chain([String::new()]));
+            }
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.extend(block);
+            }
+        }
+    }
+
+    /// The lines with `user`, previously called `old_username`, chrooted to
+    /// its document root. A block of its own is renamed and updated in
+    /// place; otherwise one is added unless a group block already chroots
+    /// the user there.
+    fn with_user(lines: &[String], old_username: &str, user: &FtpUser) -> Vec<String> {
+        let mut lines = lines.to_vec();
+        let own = match_blocks(&lines)
+            .into_iter()
+            .find(|block| block.lists_user(old_username) && block.names.len() == 1);
+        if let Some(block) = own {
+            lines[block.start] = format!("Match User {}", user.username);
+            for line in &mut lines[block.start + 1..block.end] {
+                if directive(line)
+                    .is_some_and(|(keyword, value)| keyword == "denyusers" && value == old_username)
+                {
+                    *line = deny_line(&user.username);
+                }
+            }
+            let chroot = format!("{}ChrootDirectory {}", INDENT, chroot_value(&user.doc_root));
+            let position = (block.start + 1..block.end).find(|&index| {
+                directive(&lines[index]).is_some_and(|(keyword, _)| keyword == "chrootdirectory")
+            });
+            match position {
+                Some(index) => lines[index] = chroot,
+                None => lines.insert(block.start + 1, chroot),
+            }
+            return lines;
+        }
+
+        Self::remove_user(&mut lines, old_username);
+        let covered = Self::sftp_users(&lines)
+            .iter()
+            .any(|other| other.username == old_username && other.doc_root == user.doc_root);
+        if !covered {
+            Self::insert_user_block(&mut lines, user);
+        }
+        lines
+    }
+
+    /// The lines with `user` refused by a `DenyUsers` line in its own block,
+    /// which is split off a shared one first, or with the user taken out of
+    /// every `DenyUsers` line of the SFTP blocks.
+    fn with_lock(lines: &[String], user: &FtpUser, locked: bool) -> Vec<String> {
+        let mut lines = lines.to_vec();
+        let username = user.username.as_str();
+        if locked {
+            let own = match_blocks(&lines)
+                .into_iter()
+                .find(|block| block.lists_user(username) && block.names.len() == 1);
+            match own {
+                Some(block) if block.denied.iter().any(|name| name == username) => {}
+                Some(block) => lines.insert(block.start + 1, deny_line(username)),
+                None => {
+                    Self::remove_user(&mut lines, username);
+                    let locked_user = FtpUser {
+                        locked: true,
+                        ..user.clone()
+                    };
+                    Self::insert_user_block(&mut lines, &locked_user);
+                }
+            }
+            return lines;
+        }
+
+        // Backwards, so removing a line keeps the earlier positions valid.
+        for block in match_blocks(&lines)
+            .iter()
+            .rev()
+            .filter(|block| block.sftp_only)
+        {
+            for index in (block.start + 1..block.end).rev() {
+                let Some((keyword, value)) = directive(&lines[index]) else {
+                    continue;
+                };
+                if keyword != "denyusers" || !value.split_whitespace().any(|name| name == username)
+                {
+                    continue;
+                }
+                let names: Vec<&str> = value
+                    .split_whitespace()
+                    .filter(|name| *name != username)
+                    .collect();
+                if names.is_empty() {
+                    lines.remove(index);
+                } else {
+                    lines[index] = format!("{}DenyUsers {}", INDENT, names.join(" "));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Runs `sshd -t` on the changed configuration, written next to the real
+    /// one so relative `Include` paths resolve the same way.
+    fn check_config(&self, lines: &[String]) -> io::Result<()> {
+        let sshd = find_binary("sshd").ok_or_else(|| {
+            io::Error::other("sshd not found, so the change to sshd_config cannot be checked")
+        })?;
+        let temporary = self.conf_path.with_extension("tuixel-check");
+        fs::write(&temporary, content(lines))?;
+        let output = Command::new(sshd)
+            .arg("-t")
+            .arg("-f")
+            .arg(&temporary)
+            .output();
+        let _ = fs::remove_file(&temporary);
+        let output = output?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "sshd -t rejected the change: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes checked lines and has sshd read them again.
+    fn save(&self, lines: &[String]) -> io::Result<()> {
+        fs::write(&self.conf_path, content(lines))?;
+        reload_sshd().map_err(|err| {
+            io::Error::other(format!(
+                "saved {}, but reloading sshd failed: {}",
+                self.conf_path.display(),
+                err
+            ))
+        })
+    }
+}
+
+fn deny_line(username: &str) -> String {
+    format!("{}DenyUsers {}", INDENT, username)
+}
+
+fn content(lines: &[String]) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+fn check_chroot(path: &str) -> io::Result<()> {
+    match chroot_problems(Path::new(path)).first() {
+        Some(problem) => Err(io::Error::other(format!(
+            "sshd refuses the chroot: {}",
+            problem
+        ))),
+        None => Ok(()),
+    }
+}
+
+impl FtpBackend for SftpBackend {
+    fn load_users(&self) -> io::Result<Vec<FtpUser>> {
+        Ok(Self::sftp_users(&read_lines(&self.conf_path)?))
+    }
+
+    /// Creates a system account with no shell when there is none yet.
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()> {
+        check_chroot(&user.doc_root)?;
+        let lines = read_lines(&self.conf_path)?;
+        let changed = Self::with_user(&lines, &user.username, user);
+        self.check_config(&changed)?;
+        if system_user(&user.username).is_none() {
+            run_tool(
+                "useradd",
+                &[
+                    "--no-create-home",
+                    "--home-dir",
+                    "/",
+                    "--shell",
+                    NOLOGIN_SHELL,
+                    &user.username,
+                ],
+            )?;
+        }
+        if let Some(password) = password {
+            set_password(&user.username, password)?;
+        }
+        set_system_expiry(&user.username, user.expires)?;
+        self.save(&changed)
+    }
+
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        let lines = read_lines(&self.conf_path)?;
+        let unchanged = Self::sftp_users(&lines).iter().any(|current| {
+            current.username == old_username
+                && current.username == user.username
+                && current.doc_root == user.doc_root
+        });
+        let changed = if unchanged {
+            lines.clone()
+        } else {
+            check_chroot(&user.doc_root)?;
+            Self::with_user(&lines, old_username, user)
+        };
+        if changed != lines {
+            self.check_config(&changed)?;
+        }
+        if old_username != user.username {
+            run_tool("usermod", &["--login", &user.username, old_username])?;
+        }
+        if let Some(password) = password {
+            set_password(&user.username, password)?;
+        }
+        set_system_expiry(&user.username, user.expires)?;
+        if changed != lines {
+            self.save(&changed)?;
+        }
+        Ok(())
+    }
+
+    /// Also takes the account out of the SFTP groups it belongs to, and
+    /// deletes it when its shell shows it only existed for SFTP.
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()> {
+        let username = user.username.as_str();
+        let entry = system_user(username);
+        let lines = read_lines(&self.conf_path)?;
+        let mut changed = lines.clone();
+        Self::remove_user(&mut changed, username);
+
+        let groups: Vec<String> = match_blocks(&changed)
+            .into_iter()
+            .filter(|block| block.sftp_only && block.criterion == Criterion::Group)
+            .flat_map(|block| block.names)
+            .filter(|group| group_members(group).iter().any(|member| member == username))
+            .collect();
+        if let Some(group) = groups.iter().find(|group| {
+            entry
+                .as_ref()
+                .is_some_and(|entry| group_id(group) == Some(entry.gid))
+        }) {
+            return Err(io::Error::other(format!(
+                "{} gets SFTP through its primary group {}; change that first",
+                username, group
+            )));
+        }
+
+        if changed != lines {
+            self.check_config(&changed)?;
+        }
+        for group in &groups {
+            run_tool("gpasswd", &["--delete", username, group])?;
+        }
+        if changed != lines {
+            self.save(&changed)?;
+        }
+        let sftp_only = entry.is_some_and(|entry| {
+            NOLOGIN_SHELLS
+                .iter()
+                .any(|shell| entry.shell.ends_with(shell))
+        });
+        if sftp_only {
+            run_tool("userdel", &[username])?;
+        }
+        Ok(())
+    }
+
+    /// sshd checks a locked password for password logins only, so the
+    /// account is refused through `DenyUsers`, which also stops key logins.
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()> {
+        let lines = read_lines(&self.conf_path)?;
+        let changed = Self::with_lock(&lines, user, locked);
+        if changed != lines {
+            self.check_config(&changed)?;
+            self.save(&changed)?;
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        format!("SFTP ({})", self.conf_path.display())
+    }
+
+    fn supports_expiry(&self, _protocol: Protocol) -> bool {
+        true
+    }
+
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, _group: u32, mode: u32) -> Vec<String> {
+        let path = Path::new(&user.doc_root);
+        ownership_problem(path, owner, mode)
+            .into_iter()
+            .chain(path.parent().map(chroot_problems).unwrap_or_default())
+            .map(|problem| format!("sshd refuses the chroot: {}", problem))
+            .collect()
+    }
+
+    fn protocols(&self) -> Vec<Protocol> {
+        vec![Protocol::Sftp]
+    }
+}
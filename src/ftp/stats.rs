@@ -0,0 +1,939 @@
+use crate::app::{Screen, ScreenTrait};
+use crate::ftp::ftp::format_size;
+use crate::ftp::{FtpTable, TableStyles};
+use crate::log::PROJECT_NAME;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeDelta};
+use lazy_static::lazy_static;
+use ratatui::{
+    crossterm::event::{self, KeyCode, MouseEvent},
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::{Buffer, StatefulWidget, Widget},
+    text::{Line, Span},
+    widgets::{
+        Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        Table, TableState,
+    },
+};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// Transfer logs of vsftpd, ProFTPD and wu-ftpd style servers, and the
+/// system logs that hold failed logins.
+const LOG_PATHS: [&str; 6] = [
+    "/var/log/xferlog",
+    "/var/log/vsftpd.log",
+    "/var/log/proftpd/xferlog",
+    "/var/log/pure-ftpd/transfer.log",
+    "/var/log/auth.log",
+    "/var/log/secure",
+];
+const XFERLOG_TIME_FORMAT: &str = "%a %b %e %H:%M:%S %Y";
+const SYSLOG_TIME_FORMAT: &str = "%Y %b %e %H:%M:%S";
+/// Files listed in the most transferred view.
+const MAX_FILES: usize = 100;
+const BAR: &str = "█";
+
+lazy_static! {
+    pub static ref LOGS_ENV: String = format!("{}_FTP_LOGS", PROJECT_NAME.clone());
+    /// `Mon Oct 18 12:34:56 2026 [pid 1234] [alice] OK UPLOAD: Client "1.2.3.4", "/a.txt", 12 bytes, ...`
+    static ref VSFTPD_REGEX: Regex = Regex::new(
+        r#"^(?P<time>\w{3} \w{3} +\d+ \d\d:\d\d:\d\d \d{4}) \[pid \d+\](?: \[(?P<user>[^\]]*)\])? (?:OK|FAIL) (?P<action>[A-Z]+): Client "(?P<ip>[^"]+)"(?:, "(?P<file>[^"]*)", (?P<bytes>\d+) bytes)?"#
+    )
+    .unwrap();
+    /// The `host (name[1.2.3.4]) - USER alice (Login failed)` lines of ProFTPD
+    /// and the `(?@1.2.3.4) [WARNING] Authentication failed for user [alice]`
+    /// lines of Pure-FTPd.
+    static ref AUTH_FAILURE_REGEX: Regex = Regex::new(
+        r"(?:proftpd\[\d+\]: .*\(\S*\[(?P<proftpd_ip>[^\]]+)\]\) - USER (?P<proftpd_user>[^\s:]+):? (?:\(Login failed\)|no such user))|(?:pure-ftpd: \(\S*@(?P<pureftpd_ip>[^)]+)\) \[WARNING\] Authentication failed for user \[(?P<pureftpd_user>[^\]]*)\])"
+    )
+    .unwrap();
+    static ref SYSLOG_TIME_REGEX: Regex =
+        Regex::new(r"^(?:(?P<iso>\d{4}-\d\d-\d\dT\d\d:\d\d:\d\d)\S*|(?P<bsd>\w{3} +\d+ \d\d:\d\d:\d\d)) ").unwrap();
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Upload,
+    Download,
+}
+
+/// A line of an FTP log that the statistics count.
+pub enum LogEvent {
+    Transfer {
+        time: NaiveDateTime,
+        user: String,
+        file: String,
+        bytes: u64,
+        direction: Direction,
+    },
+    FailedLogin {
+        time: NaiveDateTime,
+        ip: String,
+        user: Option<String>,
+    },
+}
+
+impl LogEvent {
+    pub fn time(&self) -> NaiveDateTime {
+        match self {
+            LogEvent::Transfer { time, .. } | LogEvent::FailedLogin { time, .. } => *time,
+        }
+    }
+}
+
+/// Parses a line of an `xferlog`, a vsftpd log or a system log. Syslog
+/// timestamps have no year, so the one that puts the line before `now` is
+/// used.
+pub fn parse_line(line: &str, now: NaiveDateTime) -> Option<LogEvent> {
+    parse_vsftpd(line)
+        .or_else(|| parse_xferlog(line))
+        .or_else(|| parse_auth_failure(line, now))
+}
+
+/// `current-time transfer-time remote-host file-size filename transfer-type
+/// special-action-flag direction access-mode username service-name
+/// authentication-method authenticated-user-id completion-status`, where
+/// the current time takes five words.
+fn parse_xferlog(line: &str) -> Option<LogEvent> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() < 18 {
+        return None;
+    }
+    let time = NaiveDateTime::parse_from_str(&words[..5].join(" "), XFERLOG_TIME_FORMAT).ok()?;
+    let bytes = words[7].parse().ok()?;
+    // The file name may contain spaces; the nine fields after it do not.
+    let tail = &words[words.len() - 9..];
+    let direction = match tail[2] {
+        "i" => Direction::Upload,
+        "o" => Direction::Download,
+        _ => return None,
+    };
+    Some(LogEvent::Transfer {
+        time,
+        user: tail[4].to_string(),
+        file: words[8..words.len() - 9].join(" "),
+        bytes,
+        direction,
+    })
+}
+
+fn parse_vsftpd(line: &str) -> Option<LogEvent> {
+    let captures = VSFTPD_REGEX.captures(line)?;
+    let time = NaiveDateTime::parse_from_str(
+        &captures["time"]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+        XFERLOG_TIME_FORMAT,
+    )
+    .ok()?;
+    let user = captures.name("user").map(|user| user.as_str().to_string());
+    let ip = captures["ip"].trim_start_matches("::ffff:").to_string();
+    let direction = match &captures["action"] {
+        "LOGIN" if line.contains("] FAIL LOGIN") => {
+            return Some(LogEvent::FailedLogin { time, ip, user });
+        }
+        "UPLOAD" => Direction::Upload,
+        "DOWNLOAD" => Direction::Download,
+        _ => return None,
+    };
+    Some(LogEvent::Transfer {
+        time,
+        user: user.unwrap_or_default(),
+        file: captures.name("file")?.as_str().to_string(),
+        bytes: captures.name("bytes")?.as_str().parse().ok()?,
+        direction,
+    })
+}
+
+fn parse_auth_failure(line: &str, now: NaiveDateTime) -> Option<LogEvent> {
+    let captures = AUTH_FAILURE_REGEX.captures(line)?;
+    let time = syslog_time(line, now)?;
+    let ip = captures
+        .name("proftpd_ip")
+        .or_else(|| captures.name("pureftpd_ip"))?
+        .as_str()
+        .to_string();
+    let user = captures
+        .name("proftpd_user")
+        .or_else(|| captures.name("pureftpd_user"))
+        .map(|user| user.as_str().to_string())
+        .filter(|user| !user.is_empty());
+    Some(LogEvent::FailedLogin { time, ip, user })
+}
+
+fn syslog_time(line: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let captures = SYSLOG_TIME_REGEX.captures(line)?;
+    if let Some(iso) = captures.name("iso") {
+        return NaiveDateTime::parse_from_str(iso.as_str(), "%Y-%m-%dT%H:%M:%S").ok();
+    }
+    let bsd = captures["bsd"]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let time =
+        NaiveDateTime::parse_from_str(&format!("{} {}", now.year(), bsd), SYSLOG_TIME_FORMAT)
+            .ok()?;
+    if time > now + Duration::days(1) {
+        NaiveDateTime::parse_from_str(&format!("{} {}", now.year() - 1, bsd), SYSLOG_TIME_FORMAT)
+            .ok()
+    } else {
+        Some(time)
+    }
+}
+
+/// The log files read: `TUIXEL_FTP_LOGS` as a `:` separated list, or the
+/// usual locations that exist.
+pub fn log_paths() -> Vec<PathBuf> {
+    match std::env::var(LOGS_ENV.clone()) {
+        Ok(paths) => paths
+            .split(':')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        Err(_) => LOG_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .filter(|path| path.exists())
+            .collect(),
+    }
+}
+
+/// Reads every event of `paths`. Files that cannot be read are reported in
+/// the returned errors.
+pub fn load_events(paths: &[PathBuf], now: NaiveDateTime) -> (Vec<LogEvent>, Vec<String>) {
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match fs::read(path) {
+            Ok(content) => events.extend(
+                String::from_utf8_lossy(&content)
+                    .lines()
+                    .filter_map(|line| parse_line(line, now)),
+            ),
+            Err(err) => errors.push(format!("{}: {}", path.display(), err)),
+        }
+    }
+    events.sort_by_key(LogEvent::time);
+    (events, errors)
+}
+
+/// The period the statistics cover, ending now.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+    Hour,
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl TimeWindow {
+    pub fn next(&self) -> Self {
+        match self {
+            TimeWindow::Hour => TimeWindow::Day,
+            TimeWindow::Day => TimeWindow::Week,
+            TimeWindow::Week => TimeWindow::Month,
+            TimeWindow::Month => TimeWindow::All,
+            TimeWindow::All => TimeWindow::Hour,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            TimeWindow::Hour => TimeWindow::All,
+            TimeWindow::Day => TimeWindow::Hour,
+            TimeWindow::Week => TimeWindow::Day,
+            TimeWindow::Month => TimeWindow::Week,
+            TimeWindow::All => TimeWindow::Month,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeWindow::Hour => "Last hour",
+            TimeWindow::Day => "Last 24 hours",
+            TimeWindow::Week => "Last 7 days",
+            TimeWindow::Month => "Last 30 days",
+            TimeWindow::All => "All time",
+        }
+    }
+
+    fn length(&self) -> Option<TimeDelta> {
+        match self {
+            TimeWindow::Hour => Some(Duration::hours(1)),
+            TimeWindow::Day => Some(Duration::days(1)),
+            TimeWindow::Week => Some(Duration::days(7)),
+            TimeWindow::Month => Some(Duration::days(30)),
+            TimeWindow::All => None,
+        }
+    }
+
+    /// The width of a bar of the transfers over time.
+    fn bucket(&self) -> TimeDelta {
+        match self {
+            TimeWindow::Hour => Duration::minutes(5),
+            TimeWindow::Day => Duration::hours(1),
+            _ => Duration::days(1),
+        }
+    }
+
+    fn bucket_format(&self) -> &'static str {
+        match self {
+            TimeWindow::Hour | TimeWindow::Day => "%Y-%m-%d %H:%M",
+            _ => "%Y-%m-%d",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UserTraffic {
+    pub user: String,
+    pub uploaded: u64,
+    pub downloaded: u64,
+    pub transfers: usize,
+}
+
+pub struct FileTraffic {
+    pub file: String,
+    pub transfers: usize,
+    pub bytes: u64,
+}
+
+pub struct TimeBucket {
+    pub start: NaiveDateTime,
+    pub transfers: usize,
+    pub uploaded: u64,
+    pub downloaded: u64,
+}
+
+pub struct FailedLogins {
+    pub ip: String,
+    pub attempts: usize,
+    pub users: Vec<String>,
+    pub last: NaiveDateTime,
+}
+
+/// The statistics of the events inside a time window.
+#[derive(Default)]
+pub struct TransferStats {
+    pub users: Vec<UserTraffic>,
+    pub files: Vec<FileTraffic>,
+    pub timeline: Vec<TimeBucket>,
+    pub failed_logins: Vec<FailedLogins>,
+}
+
+/// Counts the events of `window` up to `now`.
+pub fn summarize(events: &[LogEvent], window: TimeWindow, now: NaiveDateTime) -> TransferStats {
+    let start = window.length().map(|length| now - length);
+    let in_window = events
+        .iter()
+        .filter(|event| start.is_none_or(|start| event.time() >= start) && event.time() <= now);
+
+    let bucket_seconds = window.bucket().num_seconds();
+    let bucket_of = |time: NaiveDateTime| {
+        let seconds = time.and_utc().timestamp();
+        DateTime::from_timestamp(seconds - seconds.rem_euclid(bucket_seconds), 0)
+            .map(|time| time.naive_utc())
+            .unwrap_or(time)
+    };
+
+    let mut users: HashMap<String, UserTraffic> = HashMap::new();
+    let mut files: HashMap<String, FileTraffic> = HashMap::new();
+    let mut timeline: BTreeMap<NaiveDateTime, TimeBucket> = BTreeMap::new();
+    let mut failed: HashMap<String, (usize, HashSet<String>, NaiveDateTime)> = HashMap::new();
+
+    // Bounded windows show empty periods too, so gaps are visible.
+    if let Some(start) = start {
+        let mut bucket = bucket_of(start);
+        while bucket <= now {
+            timeline.insert(bucket, TimeBucket::new(bucket));
+            bucket += window.bucket();
+        }
+    }
+
+    for event in in_window {
+        match event {
+            LogEvent::Transfer {
+                time,
+                user,
+                file,
+                bytes,
+                direction,
+            } => {
+                let traffic = users.entry(user.clone()).or_insert_with(|| UserTraffic {
+                    user: user.clone(),
+                    ..UserTraffic::default()
+                });
+                let bucket = timeline
+                    .entry(bucket_of(*time))
+                    .or_insert_with(|| TimeBucket::new(bucket_of(*time)));
+                traffic.transfers += 1;
+                bucket.transfers += 1;
+                match direction {
+                    Direction::Upload => {
+                        traffic.uploaded += bytes;
+                        bucket.uploaded += bytes;
+                    }
+                    Direction::Download => {
+                        traffic.downloaded += bytes;
+                        bucket.downloaded += bytes;
+                    }
+                }
+                let file_traffic = files.entry(file.clone()).or_insert_with(|| FileTraffic {
+                    file: file.clone(),
+                    transfers: 0,
+                    bytes: 0,
+                });
+                file_traffic.transfers += 1;
+                file_traffic.bytes += bytes;
+            }
+            LogEvent::FailedLogin { time, ip, user } => {
+                let entry = failed
+                    .entry(ip.clone())
+                    .or_insert_with(|| (0, HashSet::new(), *time));
+                entry.0 += 1;
+                entry.1.extend(user.clone());
+                entry.2 = entry.2.max(*time);
+            }
+        }
+    }
+
+    let mut users: Vec<UserTraffic> = users.into_values().collect();
+    users.sort_by(|a, b| {
+        (b.uploaded + b.downloaded)
+            .cmp(&(a.uploaded + a.downloaded))
+            .then_with(|| a.user.cmp(&b.user))
+    });
+    let mut files: Vec<FileTraffic> = files.into_values().collect();
+    files.sort_by(|a, b| {
+        b.transfers
+            .cmp(&a.transfers)
+            .then_with(|| b.bytes.cmp(&a.bytes))
+            .then_with(|| a.file.cmp(&b.file))
+    });
+    files.truncate(MAX_FILES);
+    let mut failed_logins: Vec<FailedLogins> = failed
+        .into_iter()
+        .map(|(ip, (attempts, users, last))| {
+            let mut users: Vec<String> = users.into_iter().collect();
+            users.sort();
+            FailedLogins {
+                ip,
+                attempts,
+                users,
+                last,
+            }
+        })
+        .collect();
+    failed_logins.sort_by(|a, b| b.attempts.cmp(&a.attempts).then_with(|| a.ip.cmp(&b.ip)));
+
+    TransferStats {
+        users,
+        files,
+        // Newest first, so recent activity is on screen.
+        timeline: timeline.into_values().rev().collect(),
+        failed_logins,
+    }
+}
+
+impl TimeBucket {
+    fn new(start: NaiveDateTime) -> Self {
+        Self {
+            start,
+            transfers: 0,
+            uploaded: 0,
+            downloaded: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StatsView {
+    Users,
+    Files,
+    Timeline,
+    FailedLogins,
+}
+
+impl StatsView {
+    const ALL: [StatsView; 4] = [
+        StatsView::Users,
+        StatsView::Files,
+        StatsView::Timeline,
+        StatsView::FailedLogins,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            StatsView::Users => "Users",
+            StatsView::Files => "Files",
+            StatsView::Timeline => "Over time",
+            StatsView::FailedLogins => "Failed logins",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            StatsView::Users => StatsView::Files,
+            StatsView::Files => StatsView::Timeline,
+            StatsView::Timeline => StatsView::FailedLogins,
+            StatsView::FailedLogins => StatsView::Users,
+        }
+    }
+}
+
+/// Transfer and login statistics from the FTP server logs.
+pub struct FtpStats {
+    state: TableState,
+    scroll_state: ScrollbarState,
+    styles: TableStyles,
+    events: Vec<LogEvent>,
+    stats: TransferStats,
+    window: TimeWindow,
+    view: StatsView,
+    message: Option<String>,
+}
+
+impl ScreenTrait for FtpStats {
+    fn new() -> Self {
+        let mut stats = Self {
+            state: TableState::default().with_selected(0),
+            scroll_state: ScrollbarState::new(0),
+            styles: TableStyles::new(),
+            events: Vec::new(),
+            stats: TransferStats::default(),
+            window: TimeWindow::Day,
+            view: StatsView::Users,
+            message: None,
+        };
+        stats.reload();
+        stats
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ]);
+        let rects = vertical.split(area);
+
+        self.render_tabs(rects[0], buf);
+        self.render_table(rects[1], buf);
+        self.render_scrollbar(rects[1], buf);
+        let message = Paragraph::new(self.message.as_deref().unwrap_or(""))
+            .style(self.styles.message_style)
+            .centered();
+        Widget::render(message, rects[2], buf);
+        self.render_footer(
+            rects[3],
+            buf,
+            vec![
+                ("<Esc>", "Return to FTP users"),
+                ("<Tab>", "Next view"),
+                ("<←→>", "Change time window"),
+                ("<↓↑>", "Move up and down"),
+                ("<r>", "Reload logs"),
+            ],
+        );
+    }
+
+    fn handle_screen(
+        &mut self,
+        key: event::KeyEvent,
+        _mouse: Option<MouseEvent>,
+    ) -> Option<Screen> {
+        if key.code == KeyCode::Esc {
+            Some(Screen::FtpTable(FtpTable::new()))
+        } else {
+            self.handle_keys(key);
+            None
+        }
+    }
+}
+
+impl FtpStats {
+    fn handle_keys(&mut self, key: event::KeyEvent) {
+        match key.code {
+            KeyCode::Tab => {
+                self.view = self.view.next();
+                self.select(0);
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.window = self.window.next();
+                self.summarize();
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.window = self.window.previous();
+                self.summarize();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let i = self.state.selected().map_or(0, |i| i + 1);
+                self.select(if i < self.row_count() { i } else { 0 });
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let i = match self.state.selected() {
+                    Some(i) if i > 0 => i - 1,
+                    _ => self.row_count().saturating_sub(1),
+                };
+                self.select(i);
+            }
+            KeyCode::Char('r') => {
+                self.reload();
+            }
+            _ => {}
+        }
+    }
+
+    /// Reads the logs again and recounts the current window.
+    fn reload(&mut self) {
+        let paths = log_paths();
+        let (events, errors) = load_events(&paths, Local::now().naive_local());
+        self.events = events;
+        self.summarize();
+        if paths.is_empty() {
+            self.message = Some(format!(
+                "No FTP logs found, set {} to the log files",
+                LOGS_ENV.clone()
+            ));
+        } else if !errors.is_empty() {
+            errors
+                .iter()
+                .for_each(|err| tracing::error!("Error reading FTP log {}", err));
+            self.message = Some(format!("Error reading {}", errors.join(", ")));
+        }
+    }
+
+    fn summarize(&mut self) {
+        self.stats = summarize(&self.events, self.window, Local::now().naive_local());
+        let transfers: usize = self.stats.users.iter().map(|user| user.transfers).sum();
+        let failed: usize = self
+            .stats
+            .failed_logins
+            .iter()
+            .map(|failed| failed.attempts)
+            .sum();
+        self.message = Some(format!(
+            "{}: {} transfers by {} users, {} failed logins",
+            self.window.label(),
+            transfers,
+            self.stats.users.len(),
+            failed
+        ));
+        self.select(0);
+    }
+
+    fn row_count(&self) -> usize {
+        match self.view {
+            StatsView::Users => self.stats.users.len(),
+            StatsView::Files => self.stats.files.len(),
+            StatsView::Timeline => self.stats.timeline.len(),
+            StatsView::FailedLogins => self.stats.failed_logins.len(),
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.state.select(Some(index));
+        self.scroll_state = ScrollbarState::new(self.row_count().saturating_sub(1)).position(index);
+    }
+
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+        let mut spans: Vec<Span> = Vec::new();
+        for view in StatsView::ALL {
+            let style = if view == self.view {
+                self.styles.header_style
+            } else {
+                self.styles.row_style
+            };
+            spans.push(Span::styled(format!(" {} ", view.label()), style));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(
+            format!("  ◀ {} ▶", self.window.label()),
+            self.styles.row_style,
+        ));
+        Widget::render(
+            Paragraph::new(Line::from(spans)).style(self.styles.message_style),
+            area,
+            buf,
+        );
+    }
+
+    /// The header, column widths and cells of the current view.
+    fn table_content(&self, width: u16) -> (Vec<&'static str>, Vec<Constraint>, Vec<Vec<String>>) {
+        match self.view {
+            StatsView::Users => (
+                vec!["User", "Uploaded", "Downloaded", "Transfers"],
+                vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(12),
+                    Constraint::Length(12),
+                    Constraint::Length(10),
+                ],
+                self.stats
+                    .users
+                    .iter()
+                    .map(|user| {
+                        vec![
+                            user.user.clone(),
+                            format_size(user.uploaded),
+                            format_size(user.downloaded),
+                            user.transfers.to_string(),
+                        ]
+                    })
+                    .collect(),
+            ),
+            StatsView::Files => (
+                vec!["File", "Transfers", "Bytes"],
+                vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(10),
+                    Constraint::Length(12),
+                ],
+                self.stats
+                    .files
+                    .iter()
+                    .map(|file| {
+                        vec![
+                            file.file.clone(),
+                            file.transfers.to_string(),
+                            format_size(file.bytes),
+                        ]
+                    })
+                    .collect(),
+            ),
+            StatsView::Timeline => {
+                let bar_width = width.saturating_sub(60) as u64;
+                let busiest = self
+                    .stats
+                    .timeline
+                    .iter()
+                    .map(|bucket| bucket.uploaded + bucket.downloaded)
+                    .max()
+                    .unwrap_or(0)
+                    .max(1);
+                (
+                    vec!["Period", "Transfers", "Uploaded", "Downloaded", ""],
+                    vec![
+                        Constraint::Length(17),
+                        Constraint::Length(10),
+                        Constraint::Length(12),
+                        Constraint::Length(12),
+                        Constraint::Fill(1),
+                    ],
+                    self.stats
+                        .timeline
+                        .iter()
+                        .map(|bucket| {
+                            let total = bucket.uploaded + bucket.downloaded;
+                            vec![
+                                bucket.start.format(self.window.bucket_format()).to_string(),
+                                bucket.transfers.to_string(),
+                                format_size(bucket.uploaded),
+                                format_size(bucket.downloaded),
+                                BAR.repeat((total * bar_width).div_ceil(busiest) as usize),
+                            ]
+                        })
+                        .collect(),
+                )
+            }
+            StatsView::FailedLogins => (
+                vec!["Source IP", "Attempts", "Last attempt", "Users tried"],
+                vec![
+                    Constraint::Length(40),
+                    Constraint::Length(10),
+                    Constraint::Length(17),
+                    Constraint::Fill(1),
+                ],
+                self.stats
+                    .failed_logins
+                    .iter()
+                    .map(|failed| {
+                        vec![
+                            failed.ip.clone(),
+                            failed.attempts.to_string(),
+                            failed.last.format("%Y-%m-%d %H:%M").to_string(),
+                            failed.users.join(", "),
+                        ]
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn render_table(&mut self, area: Rect, buf: &mut Buffer) {
+        let (header, widths, rows) = self.table_content(area.width);
+        let header = header
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(self.styles.header_style)
+            .height(1);
+        let rows = rows.into_iter().enumerate().map(|(i, cells)| {
+            let color = match i % 2 {
+                0 => self.styles.normal_row_color,
+                _ => self.styles.alt_row_color,
+            };
+            cells
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(self.styles.row_style.bg(color))
+        });
+        let t = Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(self.styles.selected_row_style)
+            .style(self.styles.row_style.bg(self.styles.normal_row_color))
+            .highlight_symbol(" ▌ ")
+            .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(t, area, buf, &mut self.state);
+    }
+
+    fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(self.styles.scrollbar_style);
+
+        StatefulWidget::render(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            buf,
+            &mut self.scroll_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn parse_fixture(content: &str) -> Vec<LogEvent> {
+        content
+            .lines()
+            .filter_map(|line| parse_line(line, now()))
+            .collect()
+    }
+
+    fn fixture_events() -> Vec<LogEvent> {
+        [
+            include_str!("../../tests/fixtures/xferlog"),
+            include_str!("../../tests/fixtures/vsftpd.log"),
+            include_str!("../../tests/fixtures/auth.log"),
+        ]
+        .into_iter()
+        .flat_map(parse_fixture)
+        .collect()
+    }
+
+    #[test]
+    fn xferlog_file_names_keep_their_spaces() {
+        let events = parse_fixture(include_str!("../../tests/fixtures/xferlog"));
+
+        // The line cut short after the direction is skipped.
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            LogEvent::Transfer {
+                user,
+                file,
+                bytes,
+                direction,
+                ..
+            } => {
+                assert_eq!(user, "alice");
+                assert_eq!(file, "/srv/ftp/My Quarterly Report.pdf");
+                assert_eq!(*bytes, 2048);
+                assert!(*direction == Direction::Upload);
+            }
+            LogEvent::FailedLogin { .. } => panic!("expected a transfer"),
+        }
+    }
+
+    #[test]
+    fn vsftpd_log_reads_transfers_and_failed_logins() {
+        let events = parse_fixture(include_str!("../../tests/fixtures/vsftpd.log"));
+
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            LogEvent::Transfer {
+                file, direction, ..
+            } => {
+                assert_eq!(file, "/pub/notes v2.txt");
+                assert!(*direction == Direction::Download);
+            }
+            LogEvent::FailedLogin { .. } => panic!("expected a transfer"),
+        }
+        match &events[1] {
+            LogEvent::FailedLogin { ip, user, .. } => {
+                assert_eq!(ip, "192.0.2.66");
+                assert_eq!(user.as_deref(), Some("mallory"));
+            }
+            LogEvent::Transfer { .. } => panic!("expected a failed login"),
+        }
+    }
+
+    #[test]
+    fn failed_logins_are_counted_per_ip() {
+        let stats = summarize(&fixture_events(), TimeWindow::Day, now());
+
+        let failed: Vec<(&str, usize, Vec<&str>)> = stats
+            .failed_logins
+            .iter()
+            .map(|failed| {
+                (
+                    failed.ip.as_str(),
+                    failed.attempts,
+                    failed.users.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                ("192.0.2.66", 3, vec!["admin", "mallory", "root"]),
+                ("198.51.100.9", 1, vec!["guest"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarize_only_counts_the_time_window() {
+        let events = fixture_events();
+        let transfers = |window| {
+            summarize(&events, window, now())
+                .users
+                .iter()
+                .map(|traffic| (traffic.user.clone(), traffic.transfers))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(transfers(TimeWindow::Hour), [("alice".to_string(), 2)]);
+        assert_eq!(
+            transfers(TimeWindow::Week),
+            [("bob".to_string(), 1), ("alice".to_string(), 2)]
+        );
+        assert_eq!(
+            transfers(TimeWindow::All),
+            [("bob".to_string(), 2), ("alice".to_string(), 2)]
+        );
+        let failed = summarize(&events, TimeWindow::Hour, now()).failed_logins;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].attempts, 2);
+    }
+}
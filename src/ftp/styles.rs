@@ -8,6 +8,9 @@ pub struct TableStyles {
     pub normal_row_color: Color,
     pub alt_row_color: Color,
     pub scrollbar_style: Style,
+    pub message_style: Style,
+    pub locked_row_style: Style,
+    pub expired_row_style: Style,
 }
 
 impl TableStyles {
@@ -25,6 +28,130 @@ impl TableStyles {
                 .fg(tailwind::GRAY.c300)
                 .bg(Color::DarkGray)
                 .add_modifier(Modifier::REVERSED),
+            message_style: Style::new()
+                .fg(tailwind::GRAY.c300)
+                .bg(tailwind::SLATE.c900),
+            locked_row_style: Style::new().fg(tailwind::AMBER.c300),
+            expired_row_style: Style::new().fg(tailwind::RED.c300),
+        }
+    }
+}
+
+pub struct EditWindowStyles {
+    pub window_style: Style,
+    pub window_border_style: Style,
+    pub title_style: Style,
+    pub title_border_style: Style,
+    pub unselected_input_border_style: Style,
+    pub selected_input_border_style: Style,
+    pub footer_style: Style,
+    pub invalid_input_style: Style,
+    pub invalid_cursor_style: Style,
+    pub cursor_style: Style,
+    pub error_style: Style,
+    pub disabled_hint_style: Style,
+}
+
+impl EditWindowStyles {
+    pub const fn new() -> Self {
+        Self {
+            window_style: Style::new().bg(tailwind::BLUE.c950),
+            window_border_style: Style::new().fg(Color::LightBlue),
+            title_style: Style::new().fg(Color::LightBlue),
+            title_border_style: Style::new().fg(Color::LightBlue),
+            unselected_input_border_style: Style::new().fg(Color::Gray),
+            selected_input_border_style: Style::new().fg(Color::LightCyan),
+            footer_style: Style::new()
+                .fg(tailwind::SLATE.c100)
+                .bg(tailwind::SLATE.c800),
+            invalid_input_style: Style::new().fg(Color::LightRed),
+            invalid_cursor_style: Style::new().bg(Color::LightRed),
+            cursor_style: Style::new().bg(Color::White),
+            error_style: Style::new().fg(Color::LightRed),
+            disabled_hint_style: Style::new()
+                .fg(tailwind::SLATE.c500)
+                .bg(tailwind::SLATE.c800)
+                .add_modifier(Modifier::CROSSED_OUT),
+        }
+    }
+}
+
+pub struct SettingsStyles {
+    pub default_value_style: Style,
+    pub changed_value_style: Style,
+    pub added_line_style: Style,
+    pub removed_line_style: Style,
+    pub context_line_style: Style,
+}
+
+impl SettingsStyles {
+    pub const fn new() -> Self {
+        Self {
+            default_value_style: Style::new()
+                .fg(tailwind::SLATE.c400)
+                .add_modifier(Modifier::ITALIC),
+            changed_value_style: Style::new()
+                .fg(tailwind::AMBER.c300)
+                .add_modifier(Modifier::BOLD),
+            added_line_style: Style::new().fg(tailwind::GREEN.c400),
+            removed_line_style: Style::new().fg(tailwind::RED.c400),
+            context_line_style: Style::new().fg(tailwind::SLATE.c400),
+        }
+    }
+}
+
+pub struct BrowserStyles {
+    pub directory_style: Style,
+    pub unreadable_style: Style,
+    pub unwritable_style: Style,
+    pub detail_style: Style,
+}
+
+impl BrowserStyles {
+    pub const fn new() -> Self {
+        Self {
+            directory_style: Style::new()
+                .fg(tailwind::SKY.c300)
+                .add_modifier(Modifier::BOLD),
+            unreadable_style: Style::new().fg(tailwind::RED.c400),
+            unwritable_style: Style::new().fg(tailwind::AMBER.c300),
+            detail_style: Style::new().fg(tailwind::SLATE.c400),
+        }
+    }
+}
+
+pub struct ImportStyles {
+    pub create_style: Style,
+    pub conflict_style: Style,
+    pub invalid_style: Style,
+    pub note_style: Style,
+}
+
+impl ImportStyles {
+    pub const fn new() -> Self {
+        Self {
+            create_style: Style::new().fg(tailwind::EMERALD.c300),
+            conflict_style: Style::new().fg(tailwind::AMBER.c300),
+            invalid_style: Style::new().fg(tailwind::RED.c400),
+            note_style: Style::new().fg(tailwind::SLATE.c400),
+        }
+    }
+}
+
+pub struct TlsStyles {
+    pub label_style: Style,
+    pub ok_style: Style,
+    pub warning_style: Style,
+    pub error_style: Style,
+}
+
+impl TlsStyles {
+    pub const fn new() -> Self {
+        Self {
+            label_style: Style::new().fg(tailwind::SLATE.c400),
+            ok_style: Style::new().fg(tailwind::EMERALD.c300),
+            warning_style: Style::new().fg(tailwind::AMBER.c300),
+            error_style: Style::new().fg(tailwind::RED.c400),
         }
     }
 }
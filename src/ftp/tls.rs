@@ -0,0 +1,405 @@
+use crate::ftp::backend::find_binary;
+use crate::ftp::{EditWindowStyles, FtpBackend, TlsStyles};
+use chrono::{DateTime, Utc};
+use ratatui::{
+    crossterm::event::{self, KeyCode},
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::{Buffer, Widget},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap},
+};
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+const INFO_TEXT: &str = " (Esc) Close | (r) Read again ";
+/// Certificates expiring within this many days are flagged.
+const EXPIRY_WARNING_DAYS: i64 = 30;
+const LABEL_WIDTH: usize = 13;
+
+/// Where an FTP server reads its TLS certificate and private key from.
+pub struct TlsConfig {
+    /// Whether the server offers explicit TLS (`AUTH TLS`).
+    pub enabled: bool,
+    /// The setting that turns TLS on or off, e.g. `ssl_enable=YES`.
+    pub setting: String,
+    pub cert_file: Option<PathBuf>,
+    /// The same file as `cert_file` when both are kept together.
+    pub key_file: Option<PathBuf>,
+}
+
+/// The parts of a certificate worth checking before clients complain.
+struct CertificateInfo {
+    subject: String,
+    issuer: String,
+    alt_names: Vec<String>,
+    not_before: DateTime<Utc>,
+    not_after: DateTime<Utc>,
+    /// The DER encoded `SubjectPublicKeyInfo`, to compare with the key.
+    public_key: Vec<u8>,
+}
+
+impl CertificateInfo {
+    fn days_left(&self) -> i64 {
+        (self.not_after - Utc::now()).num_days()
+    }
+}
+
+enum KeyCheck {
+    Matches,
+    Mismatch,
+    /// Why the key could not be compared.
+    Unchecked(String),
+}
+
+/// The certificate and key of a `TlsConfig`, read once when the popup
+/// opens.
+struct TlsStatus {
+    config: TlsConfig,
+    certificate: Option<Result<CertificateInfo, String>>,
+    key: Option<KeyCheck>,
+    warnings: Vec<String>,
+}
+
+/// Reads the first certificate of a PEM file, or of a DER file.
+fn read_certificate(path: &Path) -> io::Result<CertificateInfo> {
+    let data = fs::read(path)?;
+    let der = if data.windows(11).any(|window| window == b"-----BEGIN ") {
+        Pem::iter_from_buffer(&data)
+            .filter_map(Result::ok)
+            .find(|pem| pem.label == "CERTIFICATE")
+            .map(|pem| pem.contents)
+            .ok_or_else(|| io::Error::other("no CERTIFICATE block in the file"))?
+    } else {
+        data
+    };
+    let (_, certificate) = X509Certificate::from_der(&der)
+        .map_err(|err| io::Error::other(format!("not an X.509 certificate: {}", err)))?;
+
+    let alt_names = match certificate.subject_alternative_name() {
+        Ok(Some(extension)) => extension
+            .value
+            .general_names
+            .iter()
+            .map(general_name)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let time = |timestamp: i64| DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+    let validity = certificate.validity();
+    Ok(CertificateInfo {
+        subject: certificate.subject().to_string(),
+        issuer: certificate.issuer().to_string(),
+        alt_names,
+        not_before: time(validity.not_before.timestamp()),
+        not_after: time(validity.not_after.timestamp()),
+        public_key: certificate.public_key().raw.to_vec(),
+    })
+}
+
+/// Formats a subject alternative name the way `openssl x509 -text` does.
+fn general_name(name: &GeneralName) -> String {
+    match name {
+        GeneralName::DNSName(name) => format!("DNS:{}", name),
+        GeneralName::RFC822Name(address) => format!("email:{}", address),
+        GeneralName::URI(uri) => format!("URI:{}", uri),
+        GeneralName::IPAddress(bytes) => match bytes.len() {
+            4 => format!(
+                "IP:{}",
+                Ipv4Addr::from(<[u8; 4]>::try_from(*bytes).unwrap())
+            ),
+            16 => format!(
+                "IP:{}",
+                Ipv6Addr::from(<[u8; 16]>::try_from(*bytes).unwrap())
+            ),
+            _ => "IP:<invalid>".to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// Derives the public key of `key_file` with `openssl pkey` and compares it
+/// with the one of the certificate. Encrypted keys are not unlocked; the FTP
+/// servers cannot ask for a passphrase either.
+fn check_key(key_file: &Path, certificate: &CertificateInfo) -> KeyCheck {
+    let Some(openssl) = find_binary("openssl") else {
+        return KeyCheck::Unchecked("openssl not found".to_string());
+    };
+    let output = Command::new(openssl)
+        .args([
+            "pkey", "-pubout", "-outform", "DER", "-passin", "pass:", "-in",
+        ])
+        .arg(key_file)
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            if output.stdout == certificate.public_key {
+                KeyCheck::Matches
+            } else {
+                KeyCheck::Mismatch
+            }
+        }
+        Ok(output) => KeyCheck::Unchecked(format!(
+            "openssl cannot read the key: {}",
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .next()
+                .unwrap_or_default()
+        )),
+        Err(err) => KeyCheck::Unchecked(format!("openssl failed: {}", err)),
+    }
+}
+
+impl TlsStatus {
+    fn read(config: TlsConfig) -> Self {
+        let mut warnings = Vec::new();
+        let cert_file = config.cert_file.as_deref().filter(|path| path.exists());
+        let key_file = config.key_file.as_deref().filter(|path| path.exists());
+        if config.enabled {
+            match &config.cert_file {
+                None => warnings.push("TLS is enabled but no certificate is set".to_string()),
+                Some(path) if cert_file.is_none() => warnings.push(format!(
+                    "TLS is enabled but the certificate {} does not exist",
+                    path.display()
+                )),
+                Some(_) => {}
+            }
+            match &config.key_file {
+                Some(path) if key_file.is_none() && config.key_file != config.cert_file => warnings
+                    .push(format!(
+                        "TLS is enabled but the private key {} does not exist",
+                        path.display()
+                    )),
+                _ => {}
+            }
+        }
+
+        let certificate =
+            cert_file.map(|path| read_certificate(path).map_err(|err| err.to_string()));
+        let key = match (&certificate, key_file) {
+            (Some(Ok(certificate)), Some(key_file)) => Some(check_key(key_file, certificate)),
+            _ => None,
+        };
+
+        match &certificate {
+            Some(Ok(certificate)) => {
+                let days = certificate.days_left();
+                if certificate.not_after < Utc::now() {
+                    warnings.push(format!("The certificate expired {} days ago", -days));
+                } else if days < EXPIRY_WARNING_DAYS {
+                    warnings.push(format!("The certificate expires in {} days", days));
+                }
+                if certificate.not_before > Utc::now() {
+                    warnings.push(format!(
+                        "The certificate is not valid before {}",
+                        certificate.not_before.format("%Y-%m-%d %H:%M UTC")
+                    ));
+                }
+            }
+            Some(Err(err)) => warnings.push(format!("Cannot read the certificate: {}", err)),
+            None => {}
+        }
+        if let Some(KeyCheck::Mismatch) = key {
+            warnings.push("The private key does not belong to the certificate".to_string());
+        }
+        if let Some(key_file) = key_file {
+            let mode = fs::metadata(key_file)
+                .map(|metadata| metadata.permissions().mode())
+                .unwrap_or(0);
+            if mode & 0o004 != 0 {
+                warnings.push(format!(
+                    "The private key {} is readable by every user",
+                    key_file.display()
+                ));
+            }
+        }
+
+        Self {
+            config,
+            certificate,
+            key,
+            warnings,
+        }
+    }
+}
+
+/// Shows the certificate the FTP server presents for FTPS, and whether the
+/// server will be able to use it.
+pub struct TlsPopup {
+    styles: TlsStyles,
+    window_styles: EditWindowStyles,
+    status: Result<TlsStatus, String>,
+}
+
+impl TlsPopup {
+    pub fn new(backend: &dyn FtpBackend) -> Self {
+        Self {
+            styles: TlsStyles::new(),
+            window_styles: EditWindowStyles::new(),
+            status: backend
+                .tls_config()
+                .map(TlsStatus::read)
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    /// Returns `false` once the popup should close.
+    pub fn handle_key(&mut self, key: event::KeyEvent, backend: &dyn FtpBackend) -> bool {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => false,
+            KeyCode::Char('r') => {
+                *self = Self::new(backend);
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn field<'a>(&self, label: &'a str, value: String, style: Style) -> Line<'a> {
+        Line::from(vec![
+            Span::styled(format!("{:<LABEL_WIDTH$}", label), self.styles.label_style),
+            Span::styled(value, style),
+        ])
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        let status = match &self.status {
+            Ok(status) => status,
+            Err(err) => {
+                return vec![Line::styled(
+                    format!("Cannot read the TLS settings: {}", err),
+                    self.styles.error_style,
+                )]
+            }
+        };
+        let path = |path: &Option<PathBuf>| match path {
+            Some(path) => path.display().to_string(),
+            None => "not set".to_string(),
+        };
+        let config = &status.config;
+        let mut lines = vec![
+            self.field(
+                "TLS",
+                format!(
+                    "{} ({})",
+                    if config.enabled { "on" } else { "off" },
+                    config.setting
+                ),
+                if config.enabled {
+                    self.styles.ok_style
+                } else {
+                    self.styles.warning_style
+                },
+            ),
+            self.field("Certificate", path(&config.cert_file), Style::default()),
+            self.field("Private key", path(&config.key_file), Style::default()),
+            Line::raw(""),
+        ];
+
+        match &status.certificate {
+            Some(Ok(certificate)) => {
+                let days = certificate.days_left();
+                let validity_style = if certificate.not_after < Utc::now() {
+                    self.styles.error_style
+                } else if days < EXPIRY_WARNING_DAYS {
+                    self.styles.warning_style
+                } else {
+                    self.styles.ok_style
+                };
+                let issuer = if certificate.issuer == certificate.subject {
+                    format!("{} (self-signed)", certificate.issuer)
+                } else {
+                    certificate.issuer.clone()
+                };
+                let alt_names = if certificate.alt_names.is_empty() {
+                    "none".to_string()
+                } else {
+                    certificate.alt_names.join(", ")
+                };
+                lines.extend([
+                    self.field("Subject", certificate.subject.clone(), Style::default()),
+                    self.field("Alt names", alt_names, Style::default()),
+                    self.field("Issuer", issuer, Style::default()),
+                    self.field(
+                        "Valid",
+                        format!(
+                            "{} to {} ({} days left)",
+                            certificate.not_before.format("%Y-%m-%d"),
+                            certificate.not_after.format("%Y-%m-%d"),
+                            days.max(0)
+                        ),
+                        validity_style,
+                    ),
+                ]);
+            }
+            Some(Err(_)) => {}
+            None => lines.push(Line::raw("No certificate to show.")),
+        }
+        match &status.key {
+            Some(KeyCheck::Matches) => lines.push(self.field(
+                "Key",
+                "matches the certificate".to_string(),
+                self.styles.ok_style,
+            )),
+            Some(KeyCheck::Mismatch) => lines.push(self.field(
+                "Key",
+                "does not match the certificate".to_string(),
+                self.styles.error_style,
+            )),
+            Some(KeyCheck::Unchecked(reason)) => lines.push(self.field(
+                "Key",
+                format!("not checked, {}", reason),
+                self.styles.warning_style,
+            )),
+            None => {}
+        }
+
+        if !status.warnings.is_empty() {
+            lines.push(Line::raw(""));
+        }
+        for warning in &status.warnings {
+            let style = if config.enabled {
+                self.styles.error_style
+            } else {
+                self.styles.warning_style
+            };
+            lines.push(Line::styled(format!("! {}", warning), style));
+        }
+        lines
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let lines = self.lines();
+        #[allow(clippy::cast_possible_truncation)]
+        let height = lines.len() as u16 + 4;
+        let area = popup_area(area, 80, height);
+        Widget::render(Clear, area, buf);
+        let block = Block::default()
+            .title(" FTPS certificate ")
+            .title_bottom(Line::from(INFO_TEXT).centered())
+            .style(self.window_styles.window_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.window_styles.window_border_style);
+        let paragraph = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .block(block.padding(Padding::uniform(1)));
+        Widget::render(paragraph, area, buf);
+    }
+}
+
+fn popup_area(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Max(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
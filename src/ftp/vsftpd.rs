@@ -0,0 +1,692 @@
+use crate::ftp::backend::{
+    is_writable, run_tool, set_system_expiry, set_system_locked, shadow_status, system_user,
+    user_groups, FtpBackend, Limit,
+};
+use crate::ftp::{FtpLimits, FtpUser, Protocol, TlsConfig};
+use crate::log::PROJECT_NAME;
+use lazy_static::lazy_static;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Locations of `vsftpd.conf` on Debian and on Red Hat based systems.
+const CONF_PATHS: [&str; 2] = ["/etc/vsftpd.conf", "/etc/vsftpd/vsftpd.conf"];
+const DEFAULT_USERLIST_FILE: &str = "/etc/vsftpd.user_list";
+const DEFAULT_CHROOT_LIST_FILE: &str = "/etc/vsftpd.chroot_list";
+const DEFAULT_RSA_CERT_FILE: &str = "/usr/share/ssl/certs/vsftpd.pem";
+
+lazy_static! {
+    pub static ref CONF_ENV: String = format!("{}_VSFTPD_CONF", PROJECT_NAME.clone());
+    pub static ref USERLIST_ENV: String = format!("{}_VSFTPD_USERLIST", PROJECT_NAME.clone());
+    pub static ref USER_CONFIG_DIR_ENV: String =
+        format!("{}_VSFTPD_USER_CONFIG_DIR", PROJECT_NAME.clone());
+}
+
+/// Parses a vsftpd boolean, which may be written as `YES`, `TRUE` or `1`.
+pub fn parse_bool(value: &str) -> bool {
+    matches!(value.to_uppercase().as_str(), "YES" | "TRUE" | "1")
+}
+
+/// A file of `option=value` lines as read by vsftpd, used for `vsftpd.conf`
+/// and for the per-user files in `user_config_dir`. Comments, blank lines and
+/// the order of options are kept when the file is written back.
+#[derive(Clone)]
+pub struct ConfigFile {
+    path: PathBuf,
+    lines: Vec<String>,
+}
+
+impl ConfigFile {
+    /// An empty configuration that is written to `path`.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Loads `path`; a missing file gives an empty configuration.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            lines: content.lines().map(str::to_string).collect(),
+            ..Self::new(path)
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Splits an option line into option and value, skipping comments.
+    fn option(line: &str) -> Option<(&str, &str)> {
+        let line = line.trim();
+        if line.starts_with('#') {
+            return None;
+        }
+        let (option, value) = line.split_once('=')?;
+        Some((option.trim(), value.trim()))
+    }
+
+    /// The value of `option`. vsftpd applies the last occurrence.
+    pub fn get(&self, option: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .rev()
+            .filter_map(|line| Self::option(line))
+            .find(|(name, _)| *name == option)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_bool(&self, option: &str, default: bool) -> bool {
+        self.get(option).map(parse_bool).unwrap_or(default)
+    }
+
+    /// Sets `option`, replacing its first occurrence and dropping the others,
+    /// or appends it when the file does not have it yet.
+    pub fn set(&mut self, option: &str, value: &str) {
+        let line = format!("{}={}", option, value);
+        let mut replaced = false;
+        self.lines.retain_mut(|existing| {
+            if Self::option(existing).is_some_and(|(name, _)| name == option) {
+                if replaced {
+                    return false;
+                }
+                *existing = line.clone();
+                replaced = true;
+            }
+            true
+        });
+        if !replaced {
+            self.lines.push(line);
+        }
+    }
+
+    /// Removes every occurrence of `option`.
+    pub fn remove(&mut self, option: &str) {
+        self.lines
+            .retain(|line| Self::option(line).is_none_or(|(name, _)| name != option));
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn content(&self) -> String {
+        let mut content = self.lines.join("\n");
+        content.push('\n');
+        content
+    }
+
+    /// Writes the file in place, which keeps the mode and owner of an
+    /// existing file.
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(&self.path, self.content())
+    }
+}
+
+/// Reads and writes the FTP accounts of a vsftpd server: the users listed in
+/// `userlist_file` and the per-user files in `user_config_dir`, whose
+/// `local_root` is the document root of the user.
+pub struct VsftpdBackend {
+    conf_path: PathBuf,
+    userlist_path: Option<PathBuf>,
+    user_config_dir: Option<PathBuf>,
+}
+
+impl VsftpdBackend {
+    pub fn new(conf_path: &Path) -> Self {
+        Self {
+            conf_path: conf_path.to_path_buf(),
+            userlist_path: None,
+            user_config_dir: None,
+        }
+    }
+
+    /// Uses `TUIXEL_VSFTPD_CONF`, or the first `vsftpd.conf` that exists.
+    /// `TUIXEL_VSFTPD_USERLIST` and `TUIXEL_VSFTPD_USER_CONFIG_DIR` override
+    /// the paths configured in `vsftpd.conf`.
+    pub fn from_env() -> Self {
+        let conf_path = std::env::var(CONF_ENV.clone())
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                CONF_PATHS
+                    .iter()
+                    .map(PathBuf::from)
+                    .find(|path| path.exists())
+                    .unwrap_or_else(|| PathBuf::from(CONF_PATHS[0]))
+            });
+        let mut backend = Self::new(&conf_path);
+        backend.userlist_path = std::env::var(USERLIST_ENV.clone()).ok().map(PathBuf::from);
+        backend.user_config_dir = std::env::var(USER_CONFIG_DIR_ENV.clone())
+            .ok()
+            .map(PathBuf::from);
+        backend
+    }
+
+    pub fn conf_path(&self) -> &Path {
+        &self.conf_path
+    }
+
+    pub fn conf(&self) -> io::Result<ConfigFile> {
+        ConfigFile::load(&self.conf_path)
+    }
+
+    fn userlist_path(&self, conf: &ConfigFile) -> PathBuf {
+        self.userlist_path.clone().unwrap_or_else(|| {
+            PathBuf::from(conf.get("userlist_file").unwrap_or(DEFAULT_USERLIST_FILE))
+        })
+    }
+
+    fn user_config_dir(&self, conf: &ConfigFile) -> Option<PathBuf> {
+        self.user_config_dir
+            .clone()
+            .or_else(|| conf.get("user_config_dir").map(PathBuf::from))
+    }
+
+    /// The per-user configuration file of `username`.
+    pub fn user_config(&self, username: &str) -> io::Result<ConfigFile> {
+        let conf = self.conf()?;
+        let directory = self.user_config_dir(&conf).ok_or_else(|| {
+            io::Error::other(format!(
+                "user_config_dir is not set in {}",
+                self.conf_path.display()
+            ))
+        })?;
+        ConfigFile::load(&directory.join(username))
+    }
+
+    /// The userlist is only consulted with `userlist_enable=YES`. With
+    /// `userlist_deny=NO` it lists the users allowed to log in, otherwise the
+    /// users that are refused.
+    fn userlist_mode(conf: &ConfigFile) -> Option<UserlistMode> {
+        if !conf.get_bool("userlist_enable", false) {
+            None
+        } else if conf.get_bool("userlist_deny", true) {
+            Some(UserlistMode::Deny)
+        } else {
+            Some(UserlistMode::Allow)
+        }
+    }
+
+    fn read_userlist(path: &Path) -> io::Result<Vec<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(content.lines().map(str::to_string).collect()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn write_userlist(path: &Path, lines: &[String]) -> io::Result<()> {
+        let mut content = lines.join("\n");
+        content.push('\n');
+        fs::write(path, content)
+    }
+
+    /// Adds `username` to the userlist, or removes it, keeping the other lines.
+    fn update_userlist(&self, conf: &ConfigFile, username: &str, listed: bool) -> io::Result<()> {
+        let path = self.userlist_path(conf);
+        let mut lines = Self::read_userlist(&path)?;
+        let present = lines.iter().any(|line| line.trim() == username);
+        if listed && !present {
+            lines.push(username.to_string());
+        } else if !listed && present {
+            lines.retain(|line| line.trim() != username);
+        } else {
+            return Ok(());
+        }
+        Self::write_userlist(&path, &lines)
+    }
+
+    /// Sets the userlist entry so that `username` may or may not log in.
+    fn set_allowed(&self, conf: &ConfigFile, username: &str, allowed: bool) -> io::Result<()> {
+        match Self::userlist_mode(conf) {
+            Some(UserlistMode::Allow) => self.update_userlist(conf, username, allowed),
+            Some(UserlistMode::Deny) => self.update_userlist(conf, username, !allowed),
+            None => Ok(()),
+        }
+    }
+
+    /// With `chroot_local_user=YES` every user is chrooted except those in
+    /// `chroot_list_file`; without it only the listed users are.
+    fn is_chrooted(conf: &ConfigFile, username: &str) -> bool {
+        let listed = conf.get_bool("chroot_list_enable", false)
+            && Self::read_userlist(Path::new(
+                conf.get("chroot_list_file")
+                    .unwrap_or(DEFAULT_CHROOT_LIST_FILE),
+            ))
+            .unwrap_or_default()
+            .iter()
+            .any(|line| line.trim() == username);
+        conf.get_bool("chroot_local_user", false) != listed
+    }
+
+    /// Loads the user from its per-user file, if there is one.
+    fn load_user(&self, conf: &ConfigFile, username: &str) -> io::Result<FtpUser> {
+        let user_config = match self.user_config_dir(conf) {
+            Some(directory) => Some(ConfigFile::load(&directory.join(username))?),
+            None => None,
+        };
+        let rate = user_config
+            .as_ref()
+            .and_then(|user_config| user_config.get("local_max_rate"))
+            .and_then(|rate| rate.parse::<u64>().ok())
+            .filter(|rate| *rate > 0);
+        let user_root = user_config
+            .as_ref()
+            .and_then(|user_config| user_config.get("local_root"))
+            .map(str::to_string);
+        let root = user_root
+            .or_else(|| conf.get("local_root").map(str::to_string))
+            .or_else(|| system_user(username).map(|entry| entry.home))
+            .unwrap_or_default();
+
+        // `user_sub_token=$USER` lets one local_root serve every user.
+        let doc_root = match conf.get("user_sub_token") {
+            Some(token) if !token.is_empty() => root.replace(token, username),
+            _ => root,
+        };
+        let (locked, expires) = shadow_status(username);
+        Ok(FtpUser {
+            limits: FtpLimits {
+                upload_rate: rate,
+                download_rate: rate,
+                ..FtpLimits::default()
+            },
+            expires,
+            locked,
+            ..FtpUser::new(username, &doc_root)
+        })
+    }
+
+    /// Writes the document root and limits of `user` to its per-user file.
+    /// vsftpd has a single `local_max_rate` for both directions, so the lower
+    /// of the two rates is used.
+    fn apply(user_config: &mut ConfigFile, user: &FtpUser) {
+        user_config.set("local_root", &user.doc_root);
+        let rate = match (user.limits.upload_rate, user.limits.download_rate) {
+            (Some(upload), Some(download)) => Some(upload.min(download)),
+            (upload, download) => upload.or(download),
+        };
+        match rate {
+            Some(rate) => user_config.set("local_max_rate", &rate.to_string()),
+            None => user_config.remove("local_max_rate"),
+        }
+    }
+}
+
+impl FtpBackend for VsftpdBackend {
+    /// Lists the users allowed by the userlist together with every user that
+    /// has a file in `user_config_dir`. The document root is the per-user
+    /// `local_root`, then the global `local_root`, then the home directory.
+    fn load_users(&self) -> io::Result<Vec<FtpUser>> {
+        let conf = self.conf()?;
+        let mut usernames = Vec::new();
+
+        let userlist = match Self::userlist_mode(&conf) {
+            Some(_) => Self::read_userlist(&self.userlist_path(&conf))?
+                .into_iter()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .collect(),
+            None => Vec::new(),
+        };
+        if Self::userlist_mode(&conf) == Some(UserlistMode::Allow) {
+            usernames.extend(userlist.iter().cloned());
+        }
+
+        if let Some(directory) = self.user_config_dir(&conf) {
+            match fs::read_dir(&directory) {
+                Ok(entries) => {
+                    let mut names: Vec<String> = entries
+                        .filter_map(Result::ok)
+                        .filter(|entry| entry.path().is_file())
+                        .map(|entry| entry.file_name().to_string_lossy().to_string())
+                        .filter(|name| !name.starts_with('.'))
+                        .collect();
+                    names.sort();
+                    usernames.extend(names);
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        usernames.retain(|name| seen.insert(name.clone()));
+        if Self::userlist_mode(&conf) == Some(UserlistMode::Deny) {
+            usernames.retain(|name| !userlist.contains(name));
+        }
+
+        usernames
+            .into_iter()
+            .map(|username| self.load_user(&conf, &username))
+            .collect()
+    }
+
+    /// Creates the per-user file with the document root and lets the user
+    /// log in through the userlist. vsftpd authenticates system accounts, so
+    /// the account must exist and the password is set with `chpasswd`. The
+    /// per-user file is removed again when a later step fails.
+    fn add_user(&self, user: &FtpUser, password: Option<&str>) -> io::Result<()> {
+        if system_user(&user.username).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} has no system account", user.username),
+            ));
+        }
+        let conf = self.conf()?;
+        let mut user_config = self.user_config(&user.username)?;
+        if user_config.path().exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", user_config.path().display()),
+            ));
+        }
+        Self::apply(&mut user_config, user);
+        user_config.save()?;
+        let result = password
+            .map_or(Ok(()), |password| {
+                set_system_password(&user.username, password)
+            })
+            .and_then(|_| match user.expires {
+                Some(_) => set_system_expiry(&user.username, user.expires),
+                None => Ok(()),
+            })
+            .and_then(|_| self.set_allowed(&conf, &user.username, true));
+        if result.is_err() {
+            let _ = fs::remove_file(user_config.path());
+        }
+        result
+    }
+
+    /// Writes the changes to the user previously called `old_username`,
+    /// renaming its system account, per-user file and userlist entry when
+    /// the name changed.
+    fn update_user(
+        &self,
+        old_username: &str,
+        user: &FtpUser,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        let conf = self.conf()?;
+        let mut user_config = self.user_config(old_username)?;
+        let old_path = user_config.path().to_path_buf();
+        Self::apply(&mut user_config, user);
+
+        let renamed = old_username != user.username;
+        if renamed {
+            let new_config = self.user_config(&user.username)?;
+            if new_config.path().exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", new_config.path().display()),
+                ));
+            }
+            run_tool("usermod", &["--login", &user.username, old_username])?;
+            user_config.path = new_config.path;
+        }
+
+        if let Some(password) = password {
+            set_system_password(&user.username, password)?;
+            // chpasswd writes the new hash without the lock.
+            if user.locked {
+                set_system_locked(&user.username, true)?;
+            }
+        }
+        set_system_expiry(&user.username, user.expires)?;
+        user_config.save()?;
+
+        if renamed {
+            if old_path.exists() {
+                fs::remove_file(old_path)?;
+            }
+            self.set_allowed(&conf, old_username, false)?;
+            self.set_allowed(&conf, &user.username, true)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes the per-user file and takes the user off the userlist.
+    fn delete_user(&self, user: &FtpUser) -> io::Result<()> {
+        let username = user.username.as_str();
+        let conf = self.conf()?;
+        if let Some(directory) = self.user_config_dir(&conf) {
+            match fs::remove_file(directory.join(username)) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.set_allowed(&conf, username, false)
+    }
+
+    /// Locks the password of the system account, which PAM then refuses.
+    fn set_locked(&self, user: &FtpUser, locked: bool) -> io::Result<()> {
+        set_system_locked(&user.username, locked)
+    }
+
+    fn describe(&self) -> String {
+        format!("vsftpd ({})", self.conf_path.display())
+    }
+
+    /// Expiry dates of the system accounts, which PAM enforces.
+    fn supports_expiry(&self, _protocol: Protocol) -> bool {
+        true
+    }
+
+    /// Transfers are limited by `local_max_rate`. Quotas are left to the
+    /// filesystem and connection limits are global in vsftpd.
+    fn supports_limit(&self, limit: Limit) -> bool {
+        matches!(limit, Limit::UploadRate | Limit::DownloadRate)
+    }
+
+    /// TLS is turned on by `ssl_enable=YES`. The key is read from the
+    /// certificate file unless `rsa_private_key_file` is set.
+    fn tls_config(&self) -> io::Result<TlsConfig> {
+        let conf = self.conf()?;
+        let enabled = conf.get_bool("ssl_enable", false);
+        let cert_file = conf.get("rsa_cert_file").unwrap_or(DEFAULT_RSA_CERT_FILE);
+        let key_file = conf
+            .get("rsa_private_key_file")
+            .filter(|path| !path.is_empty())
+            .unwrap_or(cert_file);
+        Ok(TlsConfig {
+            enabled,
+            setting: format!("ssl_enable={}", if enabled { "YES" } else { "NO" }),
+            cert_file: Some(PathBuf::from(cert_file)),
+            key_file: Some(PathBuf::from(key_file)),
+        })
+    }
+
+    /// vsftpd logs in system accounts only, and refuses to chroot into a
+    /// directory the user can write to unless `allow_writeable_chroot=YES`.
+    fn doc_root_warnings(&self, user: &FtpUser, owner: u32, group: u32, mode: u32) -> Vec<String> {
+        let Ok(conf) = self.conf() else {
+            return Vec::new();
+        };
+        let Some(account) = system_user(&user.username) else {
+            return vec![format!(
+                "{} is not a system user, vsftpd will not let it log in",
+                user.username
+            )];
+        };
+        let writable = is_writable(
+            account.uid,
+            &user_groups(&user.username),
+            owner,
+            group,
+            mode,
+        );
+        if Self::is_chrooted(&conf, &user.username)
+            && writable
+            && !conf.get_bool("allow_writeable_chroot", false)
+        {
+            vec![
+                "vsftpd refuses a chroot the user can write to (500 OOPS: refusing to run with \
+                  writable root inside chroot()). Make the root read-only for the user and add a \
+                  writable subdirectory, or set allow_writeable_chroot=YES"
+                    .to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum UserlistMode {
+    Allow,
+    Deny,
+}
+
+/// Sets the password of a system account through `chpasswd`.
+fn set_system_password(username: &str, password: &str) -> io::Result<()> {
+    let mut process = Command::new("chpasswd")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(stdin) = process.stdin.as_mut() {
+        stdin.write_all(format!("{}:{}\n", username, password).as_bytes())?;
+    }
+    let output = process.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Copies the fixture server into a directory of its own, so that tests
+    /// can change it.
+    fn fixture(name: &str) -> (PathBuf, VsftpdBackend) {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/vsftpd");
+        let directory =
+            std::env::temp_dir().join(format!("tuixel-vsftpd-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("users")).unwrap();
+        for file in ["vsftpd.conf", "user_list", "users/bob", "users/carol"] {
+            fs::copy(source.join(file), directory.join(file)).unwrap();
+        }
+
+        let mut backend = VsftpdBackend::new(&directory.join("vsftpd.conf"));
+        backend.userlist_path = Some(directory.join("user_list"));
+        backend.user_config_dir = Some(directory.join("users"));
+        (directory, backend)
+    }
+
+    fn userlist(directory: &Path) -> Vec<String> {
+        VsftpdBackend::read_userlist(&directory.join("user_list")).unwrap()
+    }
+
+    #[test]
+    fn load_users_merges_userlist_and_user_config_dir() {
+        let (directory, backend) = fixture("load");
+
+        let users = backend.load_users().unwrap();
+        let loaded: Vec<(&str, &str, Option<u64>)> = users
+            .iter()
+            .map(|user| {
+                (
+                    user.username.as_str(),
+                    user.doc_root.as_str(),
+                    user.limits.upload_rate,
+                )
+            })
+            .collect();
+        assert_eq!(
+            loaded,
+            [
+                ("alice", "/srv/ftp/alice", None),
+                ("bob", "/srv/ftp/shared/bob", Some(2048)),
+                ("carol", "/srv/ftp/carol", None),
+            ]
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn add_user_writes_user_config_and_userlist() {
+        let (directory, backend) = fixture("add");
+        let mut user = FtpUser::new("daemon", "/srv/ftp/daemon");
+        user.limits.upload_rate = Some(4096);
+        user.limits.download_rate = Some(1024);
+
+        backend.add_user(&user, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(directory.join("users/daemon")).unwrap(),
+            "local_root=/srv/ftp/daemon\nlocal_max_rate=1024\n"
+        );
+        assert!(userlist(&directory).contains(&"daemon".to_string()));
+
+        let err = backend.add_user(&user, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn add_user_without_system_account_writes_nothing() {
+        let (directory, backend) = fixture("add-missing");
+        let user = FtpUser::new("tuixel-no-such-user", "/srv/ftp/missing");
+
+        let err = backend.add_user(&user, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(!directory.join("users/tuixel-no-such-user").exists());
+        assert_eq!(
+            userlist(&directory),
+            ["# users allowed to log in", "alice", "bob"]
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn update_user_rewrites_user_config() {
+        let (directory, backend) = fixture("update");
+        let user = FtpUser::new("bob", "/srv/ftp/bob");
+
+        backend.update_user("bob", &user, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(directory.join("users/bob")).unwrap(),
+            "local_root=/srv/ftp/bob\n"
+        );
+
+        // Renaming onto an existing per-user file is refused before the
+        // system account is touched.
+        let renamed = FtpUser::new("carol", "/srv/ftp/bob");
+        let err = backend.update_user("bob", &renamed, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(directory.join("users/bob").exists());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn delete_user_removes_user_config_and_userlist_entry() {
+        let (directory, backend) = fixture("delete");
+
+        backend
+            .delete_user(&FtpUser::new("bob", "/srv/ftp/shared/bob"))
+            .unwrap();
+        assert!(!directory.join("users/bob").exists());
+        assert_eq!(userlist(&directory), ["# users allowed to log in", "alice"]);
+
+        let usernames: Vec<String> = backend
+            .load_users()
+            .unwrap()
+            .into_iter()
+            .map(|user| user.username)
+            .collect();
+        assert_eq!(usernames, ["alice", "carol"]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -23,7 +23,13 @@ use app::App;
 
 /// The main entry point of the application.
 /// Initializes the terminal, runs the application, and restores the terminal to its original state.
+/// `tuixel record <id> -- <command>` runs a cron job and records it in the run history instead.
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(cron::history::RECORD_COMMAND) {
+        std::process::exit(cron::history::record(&args[2..]));
+    }
+
     let mut terminal = init(CrosstermBackend::new(io::stdout())).unwrap();
     //initialize_logging().unwrap();
 